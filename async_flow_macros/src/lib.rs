@@ -0,0 +1,80 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, FnArg, ItemFn, Pat};
+
+/// Turns `async fn upload(ctx: FlowCtx, ...) -> Result<()>` into
+/// `fn upload(...) -> Arc<AsyncCoroutine>`, a factory for a coroutine named
+/// `upload` that is only spawned when the flow first steps it.
+#[proc_macro_attribute]
+pub fn flow_task(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(proc_macro2::Span::call_site(), "flow_task takes no arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    let function = parse_macro_input!(item as ItemFn);
+    match expand(function) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(function: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &function.sig;
+    if sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(sig.fn_token, "flow_task requires an async fn"));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(&sig.generics, "flow_task functions cannot be generic"));
+    }
+
+    let mut inputs = sig.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Typed(_)) => {}
+        Some(receiver) => {
+            return Err(syn::Error::new_spanned(receiver, "flow_task cannot take self"));
+        }
+        None => {
+            return Err(syn::Error::new_spanned(
+                &sig.inputs,
+                "flow_task functions take a FlowCtx as their first argument",
+            ));
+        }
+    }
+
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    for input in inputs {
+        let FnArg::Typed(typed) = input else {
+            return Err(syn::Error::new_spanned(input, "flow_task cannot take self"));
+        };
+        let Pat::Ident(pat) = typed.pat.as_ref() else {
+            return Err(syn::Error::new_spanned(&typed.pat, "flow_task arguments must be plain identifiers"));
+        };
+        params.push(typed);
+        args.push(pat.ident.clone());
+    }
+
+    let attrs = &function.attrs;
+    let vis = &function.vis;
+    let name = &sig.ident;
+    let name_str = name.to_string();
+    let inner = format_ident!("__flow_task_{}", name);
+    let mut inner_fn = function.clone();
+    inner_fn.attrs.clear();
+    inner_fn.vis = syn::Visibility::Inherited;
+    inner_fn.sig.ident = inner.clone();
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis fn #name(#(#params),*) -> ::std::sync::Arc<::async_flow::AsyncCoroutine> {
+            #inner_fn
+
+            ::std::sync::Arc::new(::async_flow::AsyncCoroutine::lazy(
+                #name_str,
+                move |ctx: ::async_flow::FlowCtx| #inner(ctx, #(#args),*),
+            ))
+        }
+    })
+}
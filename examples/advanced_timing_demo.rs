@@ -267,7 +267,7 @@ async fn demo_timer_orchestration(root: &Arc<Node>) -> Result<()> {
     let wave1_complete = Arc::new(AtomicBool::new(false));
     
     for i in 1..=3 {
-        let timer = Arc::new(Timer::new(Duration::from_millis(200 + i * 50))).named(&format!("Wave1Timer_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_millis(200 + i * 50))).named(format!("Wave1Timer_{}", i));
         
         timer.set_elapsed_callback(move || {
             println!("  🌊 Wave 1 Timer {} completed", i);
@@ -291,7 +291,7 @@ async fn demo_timer_orchestration(root: &Arc<Node>) -> Result<()> {
     let wave2_complete = Arc::new(AtomicBool::new(false));
     
     for i in 1..=2 {
-        let timer = Arc::new(Timer::new(Duration::from_millis(150 + i * 75))).named(&format!("Wave2Timer_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_millis(150 + i * 75))).named(format!("Wave2Timer_{}", i));
         
         timer.set_elapsed_callback(move || {
             println!("  🌊 Wave 2 Timer {} completed", i);
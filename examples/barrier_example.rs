@@ -28,9 +28,7 @@ async fn main() -> Result<()> {
     let task3 = Arc::new(AsyncCoroutine::new(async_task("Gamma", 200)))
         .named("Task3");
 
-    barrier.add_child(task1).await;
-    barrier.add_child(task2).await;
-    barrier.add_child(task3).await;
+    barrier.add_children([task1 as Arc<dyn Generator>, task2, task3]).await;
 
     let after_barrier = Arc::new(AsyncCoroutine::new(async {
         println!("All barrier tasks completed! Proceeding...");
@@ -40,8 +38,7 @@ async fn main() -> Result<()> {
     })).named("AfterBarrier");
 
     let sequence = Arc::new(Sequence::new()).named("BarrierSequence");
-    sequence.add_child(barrier).await;
-    sequence.add_child(after_barrier).await;
+    sequence.add_children([barrier as Arc<dyn Generator>, after_barrier]).await;
 
     root.add_child(sequence).await;
 
@@ -10,29 +10,27 @@ async fn main() -> Result<()> {
     let kernel = AsyncKernel::new();
     let root = kernel.root();
 
-    let future1 = Arc::new(AsyncFuture::<String>::new()).named("DataFuture");
-    let future2 = Arc::new(AsyncFuture::<u32>::new()).named("CountFuture");
+    let (promise1, future1) = AsyncFuture::<String>::named_pair("DataFuture");
+    let (promise2, future2) = AsyncFuture::<u32>::named_pair("CountFuture");
 
     let data_producer = Arc::new(AsyncCoroutine::new({
-        let future1 = future1.clone();
         async move {
             println!("Producing data...");
             sleep(Duration::from_millis(400)).await;
-            future1.set_value("Hello AsyncFlow!".to_string()).await;
+            promise1.set_value("Hello AsyncFlow!".to_string()).await;
             println!("Data produced");
             Ok(())
         }
     })).named("DataProducer");
 
     let count_producer = Arc::new(AsyncCoroutine::new({
-        let future2 = future2.clone();
         async move {
             println!("Counting...");
             for i in 1..=5 {
                 println!("Count: {}", i);
                 sleep(Duration::from_millis(100)).await;
             }
-            future2.set_value(42).await;
+            promise2.set_value(42).await;
             println!("Counting complete");
             Ok(())
         }
@@ -58,8 +58,6 @@ async fn main() -> Result<()> {
     let kernel2 = AsyncKernel::new();
     let root2 = kernel2.root();
     
-    let barrier = Arc::new(Barrier::new()).named("ParallelTasks");
-    
     let download1 = Arc::new(AsyncCoroutine::new(async {
         println!("📥 Downloading file1.pdf...");
         sleep(Duration::from_secs(5)).await;
@@ -81,10 +79,6 @@ async fn main() -> Result<()> {
         Ok(())
     })).named("DownloadFile3");
     
-    barrier.add_child(download1).await;
-    barrier.add_child(download2).await; 
-    barrier.add_child(download3).await;
-    
     let compress_task = Arc::new(AsyncCoroutine::new(async {
         println!("🗜️  Compressing all files into archive...");
         sleep(Duration::from_secs(3)).await;
@@ -92,9 +86,12 @@ async fn main() -> Result<()> {
         Ok(())
     })).named("CompressFiles");
     
-    let final_sequence = Arc::new(Sequence::new()).named("DownloadAndCompress");
-    final_sequence.add_child(barrier).await;
-    final_sequence.add_child(compress_task).await;
+    let final_sequence = flow! {
+        sequence "DownloadAndCompress" {
+            barrier "ParallelTasks" { task download1, task download2, task download3 },
+            task compress_task
+        }
+    };
     
     root2.add_child(final_sequence).await;
     kernel2.run_until_complete().await?;
@@ -161,26 +158,24 @@ async fn main() -> Result<()> {
     let kernel4 = AsyncKernel::new();
     let root4 = kernel4.root();
     
-    let config_future = Arc::new(AsyncFuture::<String>::new()).named("ConfigData");
-    let auth_future = Arc::new(AsyncFuture::<u32>::new()).named("AuthToken");
+    let (config_promise, config_future) = AsyncFuture::<String>::named_pair("ConfigData");
+    let (auth_promise, auth_future) = AsyncFuture::<u32>::named_pair("AuthToken");
     
     let config_loader = Arc::new(AsyncCoroutine::new({
-        let config_future = config_future.clone();
         async move {
             println!("⚙️  Loading configuration from server...");
             sleep(Duration::from_secs(3)).await;
-            config_future.set_value("production-config-v2.1".to_string()).await;
+            config_promise.set_value("production-config-v2.1".to_string()).await;
             println!("✅ Configuration loaded");
             Ok(())
         }
     })).named("ConfigLoader");
     
     let auth_service = Arc::new(AsyncCoroutine::new({
-        let auth_future = auth_future.clone();
         async move {
             println!("🔐 Authenticating with service...");
             sleep(Duration::from_secs(4)).await;
-            auth_future.set_value(12345678).await;
+            auth_promise.set_value(12345678).await;
             println!("✅ Authentication successful");
             Ok(())
         }
@@ -207,9 +202,7 @@ async fn main() -> Result<()> {
     })).named("MainService");
     
     let startup_barrier = Arc::new(Barrier::new()).named("StartupServices");
-    startup_barrier.add_child(config_loader).await;
-    startup_barrier.add_child(auth_service).await;
-    startup_barrier.add_child(main_service).await;
+    startup_barrier.add_children([config_loader as Arc<dyn Generator>, auth_service, main_service]).await;
     
     root4.add_child(startup_barrier).await;
     kernel4.run_until_complete().await?;
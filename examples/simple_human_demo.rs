@@ -102,26 +102,24 @@ async fn main() -> Result<()> {
     println!("--------------------------------------------");
     
     let kernel3 = AsyncKernel::new();
-    let config_future = Arc::new(AsyncFuture::<String>::new()).named("ConfigData");
-    let auth_future = Arc::new(AsyncFuture::<u32>::new()).named("AuthToken");
+    let (config_promise, config_future) = AsyncFuture::<String>::named_pair("ConfigData");
+    let (auth_promise, auth_future) = AsyncFuture::<u32>::named_pair("AuthToken");
     
     let config_loader = Arc::new(AsyncCoroutine::new({
-        let config_future = config_future.clone();
         async move {
             println!("⚙️  Loading system configuration...");
             sleep(Duration::from_millis(1400)).await;
-            config_future.set_value("production-v2.1.3".to_string()).await;
+            config_promise.set_value("production-v2.1.3".to_string()).await;
             println!("✅ Configuration loaded");
             Ok(())
         }
     })).named("ConfigLoader");
     
     let auth_service = Arc::new(AsyncCoroutine::new({
-        let auth_future = auth_future.clone();
         async move {
             println!("🔐 Authenticating with remote service...");
             sleep(Duration::from_millis(1800)).await;
-            auth_future.set_value(87654321).await;
+            auth_promise.set_value(87654321).await;
             println!("✅ Authentication successful");
             Ok(())
         }
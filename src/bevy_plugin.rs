@@ -0,0 +1,28 @@
+//! Bevy integration, behind the `bevy` feature: `AsyncFlowPlugin` ticks the
+//! flow in the `Update` schedule with the frame's `Time` delta.
+
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::resource::Resource;
+use bevy_ecs::system::{Res, ResMut};
+use bevy_time::Time;
+use crate::{ExternalTickDriver, Generator};
+
+#[derive(Resource)]
+pub struct FlowDriver(pub ExternalTickDriver);
+
+#[derive(Default)]
+pub struct AsyncFlowPlugin;
+
+impl Plugin for AsyncFlowPlugin {
+    fn build(&self, app: &mut App) {
+        let driver = ExternalTickDriver::new().expect("failed to build the async_flow runtime");
+        app.insert_resource(FlowDriver(driver))
+            .add_systems(Update, tick_flow);
+    }
+}
+
+fn tick_flow(mut driver: ResMut<FlowDriver>, time: Res<Time>) {
+    if let Err(e) = driver.0.tick(time.delta()) {
+        driver.0.kernel().logger().error(format!("Flow tick failed: {}", e));
+    }
+}
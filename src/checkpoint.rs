@@ -0,0 +1,82 @@
+//! Saving a flow's progress so a long-running pipeline can resume after a
+//! process restart. Checkpoints are plain serde data; persist them in any
+//! format.
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::flow::{Generator, NodeSnapshot};
+use crate::Result;
+
+/// Captured by `AsyncKernel::checkpoint` and applied to an equivalent tree
+/// with `AsyncKernel::restore`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlowCheckpoint {
+    /// The root's subtree: states, sequence indices and timer remainders.
+    pub tree: NodeSnapshot,
+    /// Names of root children that already finished and were cleared from
+    /// the tree. Unnamed root children cannot be recognised here and rerun.
+    pub finished: Vec<String>,
+}
+
+type NodeFactory = Box<dyn Fn() -> Arc<dyn Generator> + Send + Sync>;
+
+/// Builds root children by name, so `AsyncKernel::restore` can rebuild the
+/// parts of a checkpointed tree that a restarted process has not added.
+#[derive(Default)]
+pub struct NodeRegistry {
+    factories: HashMap<String, NodeFactory>,
+}
+
+impl NodeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `factory` for root children named `name`; what it builds is
+    /// given that name.
+    pub fn register<F>(mut self, name: impl Into<String>, factory: F) -> Self
+    where
+        F: Fn() -> Arc<dyn Generator> + Send + Sync + 'static,
+    {
+        self.factories.insert(name.into(), Box::new(factory));
+        self
+    }
+
+    /// A fresh node from the factory registered for `name`, if any.
+    pub fn build(&self, name: &str) -> Option<Arc<dyn Generator>> {
+        let node = (self.factories.get(name)?)();
+        node.set_name(name.to_string());
+        Some(node)
+    }
+}
+
+/// Restores `generator` and its subtree from `snapshot`. Children are matched
+/// in order by type and name, so snapshot children missing from the rebuilt
+/// tree are an error while extra children in the tree are left untouched.
+/// Returns how many generators were restored.
+pub fn restore_tree<'a>(generator: &'a dyn Generator, snapshot: &'a NodeSnapshot) -> BoxFuture<'a, Result<usize>> {
+    Box::pin(async move {
+        generator.base().restore_state(snapshot.state);
+        generator.restore_snapshot(snapshot).await;
+        let mut restored = 1;
+
+        let children = generator.children().await;
+        let mut candidates = children.iter();
+        for saved in &snapshot.children {
+            let child = candidates
+                .find(|child| child.type_name() == saved.kind && child.name() == saved.name)
+                .ok_or_else(|| {
+                    format!(
+                        "checkpoint node {} {} has no match under {}",
+                        saved.kind,
+                        saved.name.as_deref().unwrap_or("(unnamed)"),
+                        generator.base().label(),
+                    )
+                })?;
+            restored += restore_tree(child.as_ref(), saved).await?;
+        }
+        Ok(restored)
+    })
+}
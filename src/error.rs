@@ -0,0 +1,38 @@
+use std::fmt;
+
+/// A failure that can be handed to every party waiting on a flow value, so
+/// unlike a boxed error it is cheap to clone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowError {
+    message: String,
+}
+
+impl FlowError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into() }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for FlowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for FlowError {}
+
+impl From<&str> for FlowError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<String> for FlowError {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
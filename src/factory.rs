@@ -7,10 +7,8 @@ pub trait Named {
 }
 
 impl<T: Generator + ?Sized> Named for Arc<T> {
-    fn named(mut self, name: impl Into<String>) -> Self {
-        if let Some(gen) = Arc::get_mut(&mut self) {
-            gen.set_name(name.into());
-        }
+    fn named(self, name: impl Into<String>) -> Self {
+        self.set_name(name.into());
         self
     }
 }
\ No newline at end of file
@@ -0,0 +1,212 @@
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{set_children_active, ChildList, Generator, GeneratorBase, ParentLink};
+use crate::{flow_now, Logger, Result};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A span of time during which an `ActiveWindow` steps its child.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeWindow {
+    /// From `start` until `end` after the window first steps, on the flow clock.
+    Between { start: Duration, end: Duration },
+    /// The first `open` of every `period`, counted from the first step on
+    /// the flow clock.
+    Every { period: Duration, open: Duration },
+    /// Between two times of day on the UTC wall clock, as offsets from
+    /// midnight. An `end` before `start` wraps past midnight.
+    DailyUtc { start: Duration, end: Duration },
+}
+
+impl TimeWindow {
+    fn contains(&self, since_start: Duration, wall_clock: SystemTime) -> bool {
+        match *self {
+            TimeWindow::Between { start, end } => since_start >= start && since_start < end,
+            TimeWindow::Every { period, open } => {
+                !period.is_zero() && Duration::from_nanos((since_start.as_nanos() % period.as_nanos()) as u64) < open
+            }
+            TimeWindow::DailyUtc { start, end } => {
+                let since_epoch = wall_clock.duration_since(UNIX_EPOCH).unwrap_or_default();
+                let time_of_day = Duration::from_secs(since_epoch.as_secs() % DAY.as_secs());
+                if start <= end {
+                    time_of_day >= start && time_of_day < end
+                } else {
+                    time_of_day >= start || time_of_day < end
+                }
+            }
+        }
+    }
+}
+
+/// Steps its child only while one of its windows is open, and otherwise
+/// idles without completing. The child's own timers keep counting while
+/// the window is shut. Completes, or fails, along with the child.
+pub struct ActiveWindow {
+    base: GeneratorBase,
+    inner: Arc<dyn Generator>,
+    slot: Arc<ChildList>,
+    windows: Vec<TimeWindow>,
+    started: Mutex<Option<Instant>>,
+}
+
+impl ActiveWindow {
+    pub fn new(inner: Arc<dyn Generator>) -> Self {
+        Self::build(GeneratorBase::new(), inner)
+    }
+
+    pub fn with_name(name: impl Into<String>, inner: Arc<dyn Generator>) -> Self {
+        Self::build(GeneratorBase::with_name(name), inner)
+    }
+
+    fn build(base: GeneratorBase, inner: Arc<dyn Generator>) -> Self {
+        let slot = Arc::new(RwLock::new(vec![inner.clone()]));
+        inner.base().set_parent(Some(ParentLink::new(&base, &slot)));
+        Self {
+            base,
+            inner,
+            slot,
+            windows: Vec::new(),
+            started: Mutex::new(None),
+        }
+    }
+
+    pub fn window(mut self, window: TimeWindow) -> Self {
+        self.windows.push(window);
+        self
+    }
+
+    pub fn between(self, start: Duration, end: Duration) -> Self {
+        self.window(TimeWindow::Between { start, end })
+    }
+
+    pub fn every(self, period: Duration, open: Duration) -> Self {
+        self.window(TimeWindow::Every { period, open })
+    }
+
+    pub fn daily_utc(self, start: Duration, end: Duration) -> Self {
+        self.window(TimeWindow::DailyUtc { start, end })
+    }
+
+    pub fn inner(&self) -> &Arc<dyn Generator> {
+        &self.inner
+    }
+
+    pub fn windows(&self) -> &[TimeWindow] {
+        &self.windows
+    }
+
+    /// Whether a window is open now; the flow-clock windows count from the
+    /// first step, so they are all shut before it.
+    pub fn is_open(&self) -> bool {
+        let started = *self.started.lock().unwrap_or_else(|e| e.into_inner());
+        self.open_at(started, flow_now())
+    }
+
+    fn open_at(&self, started: Option<Instant>, now: Instant) -> bool {
+        let wall_clock = SystemTime::now();
+        self.windows.iter().any(|window| match (window, started) {
+            (TimeWindow::DailyUtc { .. }, _) => window.contains(Duration::ZERO, wall_clock),
+            (_, Some(started)) => window.contains(now.saturating_duration_since(started), wall_clock),
+            (_, None) => false,
+        })
+    }
+}
+
+#[async_trait]
+impl Generator for ActiveWindow {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let now = flow_now();
+        let started = *self.started.lock().unwrap_or_else(|e| e.into_inner()).get_or_insert(now);
+        if !self.open_at(Some(started), now) {
+            return Ok(());
+        }
+
+        if self.inner.is_active() && self.inner.is_running() && !self.inner.is_completed() {
+            if let Err(e) = self.inner.traced_step().await {
+                self.logger().error(format!("Windowed generator step failed: {}", e));
+                self.inner.base().step_errored(&e.to_string());
+            }
+        }
+
+        if self.inner.is_failed() {
+            self.base.fail(&format!("{} failed", self.inner.base().label()));
+        } else if self.inner.is_completed() {
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        if !self.inner.reset().await {
+            return false;
+        }
+        *self.started.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.base.reset();
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        self.slot.read().await.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.slot.try_read().ok().map(|slot| slot.clone())
+    }
+}
@@ -0,0 +1,211 @@
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{AsyncFuture, Generator, GeneratorBase};
+use crate::{flow_now, Logger, Result};
+
+/// What an `Aggregator` resolves its future with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aggregated<T> {
+    /// The submissions, in the order they arrived.
+    pub items: Vec<T>,
+    /// Set when the timeout ran out before all the expected submissions.
+    pub partial: bool,
+}
+
+struct Submissions<T> {
+    items: Vec<T>,
+    // Kept once the items are handed over.
+    count: usize,
+    closed: bool,
+}
+
+/// The submit side of an `Aggregator`; clone it into each worker.
+pub struct AggregateHandle<T> {
+    submissions: Arc<Mutex<Submissions<T>>>,
+    expected: usize,
+}
+
+impl<T> AggregateHandle<T> {
+    /// Adds `item` to the results; returns it back once the aggregator has
+    /// all it expects or has timed out.
+    pub fn submit(&self, item: T) -> std::result::Result<(), T> {
+        let mut submissions = self.submissions.lock().unwrap_or_else(|e| e.into_inner());
+        if submissions.closed || submissions.count >= self.expected {
+            return Err(item);
+        }
+        submissions.items.push(item);
+        submissions.count += 1;
+        Ok(())
+    }
+
+    /// Submissions still expected.
+    pub fn remaining(&self) -> usize {
+        let submissions = self.submissions.lock().unwrap_or_else(|e| e.into_inner());
+        self.expected.saturating_sub(submissions.count)
+    }
+}
+
+impl<T> Clone for AggregateHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            submissions: self.submissions.clone(),
+            expected: self.expected,
+        }
+    }
+}
+
+/// Collects `expected` submissions from a `handle`, for joining the results
+/// of dynamically spawned workers, then resolves `result` with them and
+/// completes. With a `timeout`, gives up that long after its first step and
+/// resolves with what it has, flagged `partial`; it still completes rather
+/// than fails, so the flow can decide what a partial result means.
+pub struct Aggregator<T> {
+    base: GeneratorBase,
+    expected: usize,
+    timeout: Option<Duration>,
+    submissions: Arc<Mutex<Submissions<T>>>,
+    result: Arc<AsyncFuture<Aggregated<T>>>,
+    started_at: Mutex<Option<Instant>>,
+}
+
+impl<T: Send + Sync + 'static> Aggregator<T> {
+    pub fn new(expected: usize) -> Self {
+        Self::build(GeneratorBase::new(), expected)
+    }
+
+    pub fn with_name(name: impl Into<String>, expected: usize) -> Self {
+        Self::build(GeneratorBase::with_name(name), expected)
+    }
+
+    fn build(base: GeneratorBase, expected: usize) -> Self {
+        Self {
+            base,
+            expected,
+            timeout: None,
+            submissions: Arc::new(Mutex::new(Submissions {
+                items: Vec::new(),
+                count: 0,
+                closed: false,
+            })),
+            result: Arc::new(AsyncFuture::new()),
+            started_at: Mutex::new(None),
+        }
+    }
+
+    /// Resolves with a partial result if the submissions are still short
+    /// `timeout` after the first step.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn handle(&self) -> AggregateHandle<T> {
+        AggregateHandle {
+            submissions: self.submissions.clone(),
+            expected: self.expected,
+        }
+    }
+
+    /// Resolves once the aggregator completes.
+    pub fn result(&self) -> Arc<AsyncFuture<Aggregated<T>>> {
+        self.result.clone()
+    }
+
+    pub fn expected(&self) -> usize {
+        self.expected
+    }
+
+    pub fn submitted(&self) -> usize {
+        self.submissions.lock().unwrap_or_else(|e| e.into_inner()).count
+    }
+
+    // Closes the submissions and takes them if they are all in or time is up.
+    fn take_due(&self, timed_out: bool) -> Option<Aggregated<T>> {
+        let mut submissions = self.submissions.lock().unwrap_or_else(|e| e.into_inner());
+        let partial = submissions.items.len() < self.expected;
+        if partial && !timed_out {
+            return None;
+        }
+        submissions.closed = true;
+        Some(Aggregated {
+            items: std::mem::take(&mut submissions.items),
+            partial,
+        })
+    }
+}
+
+#[async_trait]
+impl<T: Send + Sync + 'static> Generator for Aggregator<T> {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let now = flow_now();
+        let started_at = *self.started_at.lock().unwrap_or_else(|e| e.into_inner()).get_or_insert(now);
+        let timed_out = self.timeout.is_some_and(|timeout| now.duration_since(started_at) >= timeout);
+        let Some(aggregated) = self.take_due(timed_out) else {
+            return Ok(());
+        };
+
+        if aggregated.partial {
+            self.logger().warn(format!(
+                "Aggregator timed out with {} of {} submissions",
+                aggregated.items.len(),
+                self.expected
+            ));
+        }
+        self.result.set_value(aggregated).await;
+        self.complete();
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+}
@@ -0,0 +1,211 @@
+use std::time::Duration;
+use crate::SeededRng;
+
+/// How long to wait before each retry. Strategies keep their own progress,
+/// so a retrying node owns one and calls `reset` once it succeeds.
+pub trait Backoff: Send {
+    /// The wait before the next retry; each call moves one retry on.
+    fn next_delay(&mut self) -> Duration;
+
+    /// Starts over from the first delay.
+    fn reset(&mut self);
+}
+
+impl<B: Backoff + ?Sized> Backoff for Box<B> {
+    fn next_delay(&mut self) -> Duration {
+        (**self).next_delay()
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+}
+
+/// Adapters available on every `Backoff`.
+pub trait BackoffExt: Backoff + Sized {
+    /// Never waits longer than `max`.
+    fn capped(self, max: Duration) -> Capped<Self> {
+        Capped { inner: self, max }
+    }
+
+    /// Adds up to `fraction` of each delay at random, so retries from many
+    /// nodes spread out instead of landing together.
+    fn jittered(self, fraction: f64) -> Jittered<Self> {
+        Jittered {
+            inner: self,
+            fraction: fraction.clamp(0.0, 1.0),
+            rng: SeededRng::new(SeededRng::entropy_seed()),
+        }
+    }
+}
+
+impl<B: Backoff> BackoffExt for B {}
+
+/// The same delay every time.
+#[derive(Debug, Clone)]
+pub struct ConstantBackoff {
+    delay: Duration,
+}
+
+impl ConstantBackoff {
+    pub fn new(delay: Duration) -> Self {
+        Self { delay }
+    }
+}
+
+impl Backoff for ConstantBackoff {
+    fn next_delay(&mut self) -> Duration {
+        self.delay
+    }
+
+    fn reset(&mut self) {}
+}
+
+/// `base`, then `factor` times longer on each retry, up to `cap`.
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    base: Duration,
+    factor: f64,
+    cap: Duration,
+    retries: u32,
+}
+
+impl ExponentialBackoff {
+    /// Doubles from `base` up to `cap`.
+    pub fn new(base: Duration, cap: Duration) -> Self {
+        Self {
+            base,
+            factor: 2.0,
+            cap,
+            retries: 0,
+        }
+    }
+
+    pub fn factor(mut self, factor: f64) -> Self {
+        self.factor = factor.max(1.0);
+        self
+    }
+}
+
+impl Backoff for ExponentialBackoff {
+    fn next_delay(&mut self) -> Duration {
+        let scale = self.factor.powi(self.retries.min(i32::MAX as u32) as i32);
+        self.retries = self.retries.saturating_add(1);
+        self.base.mul_f64(scale.min(u32::MAX as f64)).min(self.cap)
+    }
+
+    fn reset(&mut self) {
+        self.retries = 0;
+    }
+}
+
+/// `base` times the Fibonacci numbers, 1, 1, 2, 3, 5..., up to `cap`; gentler
+/// than doubling.
+#[derive(Debug, Clone)]
+pub struct FibonacciBackoff {
+    base: Duration,
+    cap: Duration,
+    current: u64,
+    next: u64,
+}
+
+impl FibonacciBackoff {
+    pub fn new(base: Duration, cap: Duration) -> Self {
+        Self {
+            base,
+            cap,
+            current: 1,
+            next: 1,
+        }
+    }
+}
+
+impl Backoff for FibonacciBackoff {
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.base.saturating_mul(self.current.min(u32::MAX as u64) as u32).min(self.cap);
+        (self.current, self.next) = (self.next, self.current.saturating_add(self.next));
+        delay
+    }
+
+    fn reset(&mut self) {
+        (self.current, self.next) = (1, 1);
+    }
+}
+
+/// AWS's "decorrelated jitter": each delay is picked at random between
+/// `base` and three times the last one, up to `cap`, which both grows the
+/// waits and keeps competing retriers apart.
+#[derive(Debug, Clone)]
+pub struct DecorrelatedJitter {
+    base: Duration,
+    cap: Duration,
+    last: Duration,
+    rng: SeededRng,
+}
+
+impl DecorrelatedJitter {
+    pub fn new(base: Duration, cap: Duration) -> Self {
+        Self::with_seed(base, cap, SeededRng::entropy_seed())
+    }
+
+    /// Draws from a fixed seed, for reproducible tests.
+    pub fn with_seed(base: Duration, cap: Duration, seed: u64) -> Self {
+        Self {
+            base,
+            cap,
+            last: base,
+            rng: SeededRng::new(seed),
+        }
+    }
+}
+
+impl Backoff for DecorrelatedJitter {
+    fn next_delay(&mut self) -> Duration {
+        let high = self.last.saturating_mul(3).max(self.base);
+        let spread = (high - self.base).as_nanos().min(u64::MAX as u128) as u64;
+        let offset = if spread == 0 { 0 } else { self.rng.next_u64() % (spread + 1) };
+        self.last = (self.base + Duration::from_nanos(offset)).min(self.cap);
+        self.last
+    }
+
+    fn reset(&mut self) {
+        self.last = self.base;
+    }
+}
+
+/// See `BackoffExt::capped`.
+#[derive(Debug, Clone)]
+pub struct Capped<B> {
+    inner: B,
+    max: Duration,
+}
+
+impl<B: Backoff> Backoff for Capped<B> {
+    fn next_delay(&mut self) -> Duration {
+        self.inner.next_delay().min(self.max)
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// See `BackoffExt::jittered`.
+#[derive(Debug, Clone)]
+pub struct Jittered<B> {
+    inner: B,
+    fraction: f64,
+    rng: SeededRng,
+}
+
+impl<B: Backoff> Backoff for Jittered<B> {
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.inner.next_delay();
+        let unit = (self.rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        delay + delay.mul_f64(self.fraction * unit)
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
@@ -1,20 +1,63 @@
 use async_trait::async_trait;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::RwLock;
+use tokio::time::Instant;
 use uuid::Uuid;
-use crate::flow::{Generator, GeneratorBase};
-use crate::{Logger, Result};
+use crate::flow::{
+    accept_child, check_no_cycle, reset_subtree, set_children_active, BarrierBuilder, Generator, GeneratorBase,
+    ParentLink,
+};
+use crate::{fill_step_order, flow_now, Logger, Result};
 
 pub struct Barrier {
     base: GeneratorBase,
     children: Arc<RwLock<Vec<Arc<dyn Generator>>>>,
+    prune_completed: AtomicBool,
+    pruned: AtomicUsize,
+    reported: Mutex<HashSet<Uuid>>,
+    on_child_complete: Mutex<Option<ChildCompleteHook>>,
+    deadlines: Mutex<HashMap<Uuid, ChildDeadline>>,
+    timed_out: AtomicUsize,
+    order: Mutex<Vec<usize>>,
 }
 
+// Time a child has spent running under its deadline, counted between the
+// barrier's steps while both are active.
+struct ChildDeadline {
+    timeout: Duration,
+    spent: Duration,
+    last_step: Option<Instant>,
+}
+
+impl ChildDeadline {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            spent: Duration::ZERO,
+            last_step: None,
+        }
+    }
+}
+
+/// Called with the finished child, how many children have finished and how
+/// many the barrier has in total.
+pub type ChildCompleteHook = Arc<dyn Fn(&dyn Generator, usize, usize) + Send + Sync>;
+
 impl Barrier {
     pub fn new() -> Self {
         Self {
             base: GeneratorBase::new(),
             children: Arc::new(RwLock::new(Vec::new())),
+            prune_completed: AtomicBool::new(false),
+            pruned: AtomicUsize::new(0),
+            reported: Mutex::new(HashSet::new()),
+            on_child_complete: Mutex::new(None),
+            deadlines: Mutex::new(HashMap::new()),
+            timed_out: AtomicUsize::new(0),
+            order: Mutex::new(Vec::new()),
         }
     }
 
@@ -22,23 +65,205 @@ impl Barrier {
         Self {
             base: GeneratorBase::with_name(name),
             children: Arc::new(RwLock::new(Vec::new())),
+            prune_completed: AtomicBool::new(false),
+            pruned: AtomicUsize::new(0),
+            reported: Mutex::new(HashSet::new()),
+            on_child_complete: Mutex::new(None),
+            deadlines: Mutex::new(HashMap::new()),
+            timed_out: AtomicUsize::new(0),
+            order: Mutex::new(Vec::new()),
         }
     }
 
+    // Used by the builders: fills the child list of a container that has not
+    // been shared yet, so no lock needs to be awaited.
+    pub(crate) fn with_children(self, children: Vec<Arc<dyn Generator>>) -> Self {
+        {
+            let mut existing = self.children.try_write().expect("container is not shared yet");
+            existing.extend(children.iter().cloned());
+        }
+        let link = self.parent_link();
+        for child in &children {
+            child.base().set_parent(Some(link.clone()));
+        }
+        self
+    }
+
+    fn parent_link(&self) -> ParentLink {
+        ParentLink::new(&self.base, &self.children)
+    }
+
     pub async fn add_child(&self, child: Arc<dyn Generator>) {
+        if accept_child(&self.base, &child) {
+            self.push_child(child).await;
+        }
+    }
+
+    /// Like `add_child`, but returns the error instead of logging it when
+    /// `child` is this container or one of its ancestors.
+    pub async fn try_add_child(&self, child: Arc<dyn Generator>) -> Result<()> {
+        check_no_cycle(&self.base, child.as_ref())?;
+        self.push_child(child).await;
+        Ok(())
+    }
+
+    async fn push_child(&self, child: Arc<dyn Generator>) {
+        child.base().set_parent(Some(self.parent_link()));
         let mut children = self.children.write().await;
         children.push(child);
     }
 
+    /// Adds `child`, failing and deactivating it if it is still running
+    /// `timeout` after the barrier first steps it, so one slow child cannot
+    /// hold the barrier forever. Time spent while the barrier or the child is
+    /// deactivated does not count, as with timers.
+    pub async fn add_child_with_timeout(&self, child: Arc<dyn Generator>, timeout: Duration) {
+        let id = child.id();
+        if accept_child(&self.base, &child) {
+            self.deadlines
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(id, ChildDeadline::new(timeout));
+            self.push_child(child).await;
+        }
+    }
+
+    /// Children failed by `add_child_with_timeout` deadlines.
+    pub fn timed_out_count(&self) -> usize {
+        self.timed_out.load(Ordering::Relaxed)
+    }
+
+    pub async fn add_children<I>(&self, children: I)
+    where
+        I: IntoIterator<Item = Arc<dyn Generator>>,
+    {
+        let link = self.parent_link();
+        let mut existing = self.children.write().await;
+        existing.extend(children.into_iter().filter(|child| accept_child(&self.base, child)).inspect(|child| {
+            child.base().set_parent(Some(link.clone()));
+        }));
+    }
+
     pub async fn child_count(&self) -> usize {
         let children = self.children.read().await;
         children.len()
     }
 
+    /// Drops children as soon as they finish instead of holding them until
+    /// the barrier completes, for long-lived barriers fed dynamically.
+    /// `completed_count` still counts them.
+    pub fn set_prune_completed(&self, prune: bool) {
+        self.prune_completed.store(prune, Ordering::Relaxed);
+    }
+
+    pub fn prunes_completed(&self) -> bool {
+        self.prune_completed.load(Ordering::Relaxed)
+    }
+
+    /// Children finished so far, including any already pruned.
+    pub async fn completed_count(&self) -> usize {
+        let children = self.children.read().await;
+        let held = children.iter().filter(|child| child.is_completed()).count();
+        held + self.pruned.load(Ordering::Relaxed)
+    }
+
+    /// Children still running.
+    pub async fn pending_count(&self) -> usize {
+        let children = self.children.read().await;
+        children.iter().filter(|child| !child.is_completed()).count()
+    }
+
+    /// Calls `callback` once for each child that finishes, from the
+    /// barrier's step, for reporting progress like "3/7 downloads finished".
+    pub fn on_child_complete<F>(&self, callback: F)
+    where
+        F: Fn(&dyn Generator, usize, usize) + Send + Sync + 'static,
+    {
+        *self.on_child_complete.lock().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(callback));
+    }
+
+    // Children finish in their own step or from outside, so completions are
+    // noticed by scanning, each reported once.
+    fn report_completions(&self, children: &[Arc<dyn Generator>]) {
+        let hook = self.on_child_complete.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let Some(hook) = hook else {
+            return;
+        };
+        let pruned = self.pruned.load(Ordering::Relaxed);
+        let total = children.len() + pruned;
+        let mut reported = self.reported.lock().unwrap_or_else(|e| e.into_inner());
+        let mut completed = pruned + reported.len();
+        for child in children.iter().filter(|child| child.is_completed()) {
+            if reported.insert(child.id()) {
+                completed += 1;
+                hook(child.as_ref(), completed, total);
+            }
+        }
+    }
+
+    fn expire_deadlines(&self, children: &[Arc<dyn Generator>]) {
+        let mut deadlines = self.deadlines.lock().unwrap_or_else(|e| e.into_inner());
+        if deadlines.is_empty() {
+            return;
+        }
+        let now = flow_now();
+        for child in children {
+            let Some(deadline) = deadlines.get_mut(&child.id()) else {
+                continue;
+            };
+            if child.is_completed() {
+                continue;
+            }
+            if !child.is_active() {
+                deadline.last_step = None;
+                continue;
+            }
+            if let Some(last_step) = deadline.last_step.replace(now) {
+                deadline.spent += now.saturating_duration_since(last_step);
+            }
+            if deadline.spent >= deadline.timeout {
+                let reason = format!("timed out after {:?}", deadline.timeout);
+                self.logger().warn(format!("Barrier child {} {}", child.base().label(), reason));
+                child.deactivate();
+                child.base().fail(&reason);
+                self.timed_out.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    // The time until the next step is not counted against any deadline.
+    fn pause_deadlines(&self) {
+        for deadline in self.deadlines.lock().unwrap_or_else(|e| e.into_inner()).values_mut() {
+            deadline.last_step = None;
+        }
+    }
+
     async fn all_children_completed(&self) -> bool {
         let children = self.children.read().await;
         children.iter().all(|child| child.is_completed())
     }
+
+    async fn remove_completed(&self) {
+        let mut children = self.children.write().await;
+        let before = children.len();
+        children.retain(|child| {
+            let keep = !child.is_completed();
+            if !keep {
+                child.base().set_parent(None);
+            }
+            keep
+        });
+        self.pruned.fetch_add(before - children.len(), Ordering::Relaxed);
+        let held = |id: &Uuid| children.iter().any(|child| child.id() == *id);
+        self.reported.lock().unwrap_or_else(|e| e.into_inner()).retain(held);
+        self.deadlines.lock().unwrap_or_else(|e| e.into_inner()).retain(|id, _| held(id));
+    }
+}
+
+impl Barrier {
+    pub fn builder() -> BarrierBuilder {
+        BarrierBuilder::new()
+    }
 }
 
 impl Default for Barrier {
@@ -53,11 +278,11 @@ impl Generator for Barrier {
         self.base.id()
     }
 
-    fn name(&self) -> Option<&str> {
+    fn name(&self) -> Option<String> {
         self.base.name()
     }
 
-    fn set_name(&mut self, name: String) {
+    fn set_name(&self, name: String) {
         self.base.set_name(name);
     }
 
@@ -75,10 +300,13 @@ impl Generator for Barrier {
 
     fn activate(&self) {
         self.base.activate();
+        set_children_active(self, true);
     }
 
     fn deactivate(&self) {
         self.base.deactivate();
+        set_children_active(self, false);
+        self.pause_deadlines();
     }
 
     fn complete(&self) {
@@ -90,19 +318,33 @@ impl Generator for Barrier {
             return Ok(());
         }
 
+        self.base.start();
+
         let children = self.children.read().await;
         if children.is_empty() {
             self.complete();
             return Ok(());
         }
 
-        for child in children.iter() {
+        self.expire_deadlines(&children);
+        let mut order = std::mem::take(&mut *self.order.lock().unwrap_or_else(|e| e.into_inner()));
+        fill_step_order(&mut order, children.len());
+        for &index in &order {
+            let child = &children[index];
             if child.is_active() && child.is_running() && !child.is_completed() {
-                if let Err(e) = child.step().await {
+                if let Err(e) = child.traced_step().await {
                     self.logger().error(format!("Child step failed in barrier: {}", e));
+                    child.base().step_errored(&e.to_string());
                 }
             }
         }
+        *self.order.lock().unwrap_or_else(|e| e.into_inner()) = order;
+        self.report_completions(&children);
+        drop(children);
+
+        if self.prunes_completed() {
+            self.remove_completed().await;
+        }
 
         if self.all_children_completed().await {
             self.complete();
@@ -114,4 +356,28 @@ impl Generator for Barrier {
     fn logger(&self) -> &Logger {
         self.base.logger()
     }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        self.pruned.store(0, Ordering::Relaxed);
+        self.timed_out.store(0, Ordering::Relaxed);
+        self.reported.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        for deadline in self.deadlines.lock().unwrap_or_else(|e| e.into_inner()).values_mut() {
+            *deadline = ChildDeadline::new(deadline.timeout);
+        }
+        reset_subtree(self).await;
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        let children = self.children.read().await;
+        children.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.children.try_read().ok().map(|children| children.clone())
+    }
 }
\ No newline at end of file
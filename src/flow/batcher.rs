@@ -0,0 +1,227 @@
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{set_children_active, ChildList, Generator, GeneratorBase, ParentLink};
+use crate::{flow_now, Logger, Result};
+
+struct BatchState<T> {
+    items: Vec<T>,
+    // When a step first saw the oldest queued item, on the flow clock, so a
+    // paused or slowed layer ages batches with it.
+    oldest: Option<Instant>,
+    closed: bool,
+}
+
+/// The push side of a `Batcher`; clone it freely across producers.
+pub struct BatchHandle<T> {
+    state: Arc<Mutex<BatchState<T>>>,
+}
+
+impl<T> BatchHandle<T> {
+    /// Queues `item` for the next batch; returns it back once closed.
+    pub fn push(&self, item: T) -> std::result::Result<(), T> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.closed {
+            return Err(item);
+        }
+        state.items.push(item);
+        Ok(())
+    }
+
+    /// No more items; whatever is queued is flushed as a final batch.
+    pub fn close(&self) {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).closed = true;
+    }
+}
+
+impl<T> Clone for BatchHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+type FlushFactory<T> = Box<dyn Fn(Vec<T>) -> Arc<dyn Generator> + Send + Sync>;
+
+/// Accumulates pushed items and flushes them once `max_size` are queued or
+/// the oldest has waited `max_age`, whichever comes first. Each flush builds
+/// a subtree from the batch and runs it as a child. Completes once the
+/// handle is closed and every flush has finished.
+pub struct Batcher<T> {
+    base: GeneratorBase,
+    state: Arc<Mutex<BatchState<T>>>,
+    max_size: usize,
+    max_age: Duration,
+    flush: FlushFactory<T>,
+    flushes: Arc<ChildList>,
+    flushed_batches: AtomicUsize,
+}
+
+impl<T: Send + 'static> Batcher<T> {
+    pub fn new<F>(max_size: usize, max_age: Duration, flush: F) -> Self
+    where
+        F: Fn(Vec<T>) -> Arc<dyn Generator> + Send + Sync + 'static,
+    {
+        Self {
+            base: GeneratorBase::new(),
+            state: Arc::new(Mutex::new(BatchState {
+                items: Vec::new(),
+                oldest: None,
+                closed: false,
+            })),
+            max_size: max_size.max(1),
+            max_age,
+            flush: Box::new(flush),
+            flushes: Arc::new(RwLock::new(Vec::new())),
+            flushed_batches: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn with_name<F>(name: impl Into<String>, max_size: usize, max_age: Duration, flush: F) -> Self
+    where
+        F: Fn(Vec<T>) -> Arc<dyn Generator> + Send + Sync + 'static,
+    {
+        let batcher = Self::new(max_size, max_age, flush);
+        batcher.base.set_name(name.into());
+        batcher
+    }
+
+    pub fn handle(&self) -> BatchHandle<T> {
+        BatchHandle {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Items waiting for the next flush.
+    pub fn pending(&self) -> usize {
+        self.lock_state().items.len()
+    }
+
+    pub fn flushed_batches(&self) -> usize {
+        self.flushed_batches.load(Ordering::Relaxed)
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, BatchState<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Cuts the batches that are due; full ones first, then an aged or final
+    // partial batch.
+    fn due_batches(&self) -> (Vec<Vec<T>>, bool) {
+        let now = flow_now();
+        let mut state = self.lock_state();
+        if !state.items.is_empty() {
+            state.oldest.get_or_insert(now);
+        }
+        let mut batches = Vec::new();
+        while state.items.len() >= self.max_size {
+            let rest = state.items.split_off(self.max_size);
+            batches.push(std::mem::replace(&mut state.items, rest));
+        }
+        let aged = state.oldest.is_some_and(|oldest| now.saturating_duration_since(oldest) >= self.max_age);
+        if !state.items.is_empty() && (aged || state.closed) {
+            batches.push(std::mem::take(&mut state.items));
+        }
+        if state.items.is_empty() {
+            state.oldest = None;
+        } else if !batches.is_empty() {
+            state.oldest = Some(now);
+        }
+        (batches, state.closed)
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> Generator for Batcher<T> {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let (batches, closed) = self.due_batches();
+        let mut flushes = self.flushes.write().await;
+        for batch in batches {
+            let flush = (self.flush)(batch);
+            flush.base().set_parent(Some(ParentLink::new(&self.base, &self.flushes)));
+            flushes.push(flush);
+            self.flushed_batches.fetch_add(1, Ordering::Relaxed);
+        }
+
+        for flush in flushes.iter() {
+            if flush.is_active() && flush.is_running() && !flush.is_completed() {
+                if let Err(e) = flush.traced_step().await {
+                    self.logger().error(format!("Batch flush failed: {}", e));
+                    flush.base().step_errored(&e.to_string());
+                }
+            }
+        }
+        flushes.retain(|flush| !flush.is_completed());
+
+        if closed && flushes.is_empty() && self.pending() == 0 {
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        self.flushes.read().await.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.flushes.try_read().ok().map(|flushes| flushes.clone())
+    }
+}
@@ -0,0 +1,102 @@
+use std::sync::{Arc, Mutex};
+use crate::flow::{AsyncFuture, Generator, Promise};
+
+/// Carries a value from one kernel to another. The producing side sends, or
+/// forwards a future or a completion; the consuming kernel adds the nodes
+/// from `future` to its tree, and they complete as soon as the value lands,
+/// without a trigger polling shared state. Clones share the same bridge and
+/// can be moved to other threads and runtimes.
+pub struct Bridge<T> {
+    inner: Arc<BridgeState<T>>,
+}
+
+struct BridgeState<T> {
+    value: Mutex<Option<T>>,
+    waiting: Mutex<Vec<Promise<T>>>,
+}
+
+impl<T: Clone + Send + Sync + 'static> Bridge<T> {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(BridgeState {
+                value: Mutex::new(None),
+                waiting: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Resolves every future handed out so far, and any handed out later.
+    /// A second send replaces the value for later futures only.
+    pub async fn send(&self, value: T) {
+        *self.inner.value.lock().unwrap_or_else(|e| e.into_inner()) = Some(value.clone());
+        let waiting = std::mem::take(&mut *self.inner.waiting.lock().unwrap_or_else(|e| e.into_inner()));
+        for promise in waiting {
+            promise.set_value(value.clone()).await;
+        }
+    }
+
+    pub fn value(&self) -> Option<T> {
+        self.inner.value.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn is_sent(&self) -> bool {
+        self.value().is_some()
+    }
+
+    /// A node for the consuming kernel that completes with the bridged value.
+    pub async fn future(&self) -> Arc<AsyncFuture<T>> {
+        self.register(AsyncFuture::pair()).await
+    }
+
+    pub async fn named_future(&self, name: impl Into<String>) -> Arc<AsyncFuture<T>> {
+        self.register(AsyncFuture::named_pair(name)).await
+    }
+
+    async fn register(&self, (promise, future): (Promise<T>, Arc<AsyncFuture<T>>)) -> Arc<AsyncFuture<T>> {
+        let sent = {
+            let value = self.inner.value.lock().unwrap_or_else(|e| e.into_inner());
+            if value.is_none() {
+                self.inner.waiting.lock().unwrap_or_else(|e| e.into_inner()).push(promise.clone());
+            }
+            value.clone()
+        };
+        if let Some(value) = sent {
+            promise.set_value(value).await;
+        }
+        future
+    }
+
+    /// Sends `source`'s value once it is set. The relay runs on a spawned
+    /// task, so this must be called within a tokio runtime.
+    pub fn forward(&self, source: &Arc<AsyncFuture<T>>) {
+        let (bridge, source) = (self.clone(), source.clone());
+        tokio::spawn(async move {
+            let value = source.wait().await;
+            bridge.send(value).await;
+        });
+    }
+
+    /// Sends `value` once `generator` completes or fails, for bridging an
+    /// event such as "level loaded" from one kernel into another.
+    pub fn forward_completion(&self, generator: Arc<dyn Generator>, value: T) {
+        let bridge = self.clone();
+        tokio::spawn(async move {
+            generator.wait_completed().await;
+            bridge.send(value).await;
+        });
+    }
+}
+
+impl<T> Clone for Bridge<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Default for Bridge<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
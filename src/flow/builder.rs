@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use std::time::Duration;
+use crate::flow::{Barrier, Generator, Sequence, Timer, Trigger};
+
+/// Chainable construction of a `Sequence` without awaiting each `add_child`.
+#[derive(Default)]
+pub struct SequenceBuilder {
+    name: Option<String>,
+    children: Vec<Arc<dyn Generator>>,
+}
+
+impl SequenceBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_name(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn then(mut self, generator: Arc<dyn Generator>) -> Self {
+        self.children.push(generator);
+        self
+    }
+
+    pub fn parallel(self, build: impl FnOnce(BarrierBuilder) -> BarrierBuilder) -> Self {
+        self.then(build(BarrierBuilder::new()).build())
+    }
+
+    pub fn sequence(self, build: impl FnOnce(SequenceBuilder) -> SequenceBuilder) -> Self {
+        self.then(build(SequenceBuilder::new()).build())
+    }
+
+    pub fn timer(self, duration: Duration) -> Self {
+        self.then(Arc::new(Timer::new(duration)))
+    }
+
+    pub fn trigger<F>(self, condition: F) -> Self
+    where
+        F: Fn() -> bool + Send + Sync + 'static,
+    {
+        self.then(Arc::new(Trigger::new(condition)))
+    }
+
+    pub fn build(self) -> Arc<Sequence> {
+        let sequence = match self.name {
+            Some(name) => Sequence::with_name(name),
+            None => Sequence::new(),
+        };
+        Arc::new(sequence.with_children(self.children))
+    }
+}
+
+/// Chainable construction of a `Barrier`; every child runs in parallel.
+#[derive(Default)]
+pub struct BarrierBuilder {
+    name: Option<String>,
+    children: Vec<Arc<dyn Generator>>,
+}
+
+impl BarrierBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_name(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn then(mut self, generator: Arc<dyn Generator>) -> Self {
+        self.children.push(generator);
+        self
+    }
+
+    pub fn parallel(self, build: impl FnOnce(BarrierBuilder) -> BarrierBuilder) -> Self {
+        self.then(build(BarrierBuilder::new()).build())
+    }
+
+    pub fn sequence(self, build: impl FnOnce(SequenceBuilder) -> SequenceBuilder) -> Self {
+        self.then(build(SequenceBuilder::new()).build())
+    }
+
+    pub fn timer(self, duration: Duration) -> Self {
+        self.then(Arc::new(Timer::new(duration)))
+    }
+
+    pub fn trigger<F>(self, condition: F) -> Self
+    where
+        F: Fn() -> bool + Send + Sync + 'static,
+    {
+        self.then(Arc::new(Trigger::new(condition)))
+    }
+
+    pub fn build(self) -> Arc<Barrier> {
+        let barrier = match self.name {
+            Some(name) => Barrier::with_name(name),
+            None => Barrier::new(),
+        };
+        Arc::new(barrier.with_children(self.children))
+    }
+}
@@ -0,0 +1,221 @@
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{set_children_active, AsyncFuture, ChildList, Generator, GeneratorBase, ParentLink, Promise};
+use crate::{flow_now, Logger, Result};
+
+type Producer<T> = Box<dyn Fn(Promise<T>) -> Arc<dyn Generator> + Send + Sync>;
+
+/// Memoizes the value a child flow produces. While the cached value is
+/// younger than `ttl`, a pass completes straight away; otherwise `produce`
+/// builds a fresh child from a promise, the child runs, and its value
+/// replaces the cached one in `value`. The cache outlives `reset`, so a
+/// looped flow only pays for the child once per `ttl`. Fails, keeping the
+/// old value, if the child fails or finishes without keeping its promise.
+pub struct Cached<T> {
+    base: GeneratorBase,
+    ttl: Duration,
+    produce: Producer<T>,
+    value: Arc<AsyncFuture<T>>,
+    stored_at: Mutex<Option<Instant>>,
+    refresh: Arc<ChildList>,
+    fresh: Mutex<Option<Arc<AsyncFuture<T>>>>,
+    hits: AtomicU32,
+    refreshes: AtomicU32,
+}
+
+impl<T: Clone + Send + Sync + 'static> Cached<T> {
+    pub fn new<F>(ttl: Duration, produce: F) -> Self
+    where
+        F: Fn(Promise<T>) -> Arc<dyn Generator> + Send + Sync + 'static,
+    {
+        Self::build(GeneratorBase::new(), ttl, Box::new(produce))
+    }
+
+    pub fn with_name<F>(name: impl Into<String>, ttl: Duration, produce: F) -> Self
+    where
+        F: Fn(Promise<T>) -> Arc<dyn Generator> + Send + Sync + 'static,
+    {
+        Self::build(GeneratorBase::with_name(name), ttl, Box::new(produce))
+    }
+
+    fn build(base: GeneratorBase, ttl: Duration, produce: Producer<T>) -> Self {
+        Self {
+            base,
+            ttl,
+            produce,
+            value: Arc::new(AsyncFuture::new()),
+            stored_at: Mutex::new(None),
+            refresh: Arc::new(RwLock::new(Vec::new())),
+            fresh: Mutex::new(None),
+            hits: AtomicU32::new(0),
+            refreshes: AtomicU32::new(0),
+        }
+    }
+
+    /// The latest value; it resolves on the first refresh and then keeps
+    /// the newest one.
+    pub fn value(&self) -> Arc<AsyncFuture<T>> {
+        self.value.clone()
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// How old the cached value is, if there is one.
+    pub fn age(&self) -> Option<Duration> {
+        let stored_at = *self.stored_at.lock().unwrap_or_else(|e| e.into_inner());
+        stored_at.map(|at| flow_now().saturating_duration_since(at))
+    }
+
+    pub fn is_fresh(&self) -> bool {
+        self.age().is_some_and(|age| age < self.ttl)
+    }
+
+    /// Makes the next pass refresh regardless of age. The stale value stays
+    /// readable until then.
+    pub fn invalidate(&self) {
+        *self.stored_at.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Passes served from the cache.
+    pub fn hits(&self) -> u32 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Times the child has been built to refresh the value.
+    pub fn refreshes(&self) -> u32 {
+        self.refreshes.load(Ordering::Relaxed)
+    }
+
+    // The refreshing child, built now if the cache has gone stale.
+    async fn refreshing_child(&self) -> Option<Arc<dyn Generator>> {
+        let mut refresh = self.refresh.write().await;
+        if let Some(child) = refresh.first() {
+            return Some(child.clone());
+        }
+        if self.is_fresh() {
+            return None;
+        }
+
+        let (promise, fresh) = AsyncFuture::pair();
+        let child = (self.produce)(promise);
+        child.base().set_parent(Some(ParentLink::new(&self.base, &self.refresh)));
+        refresh.push(child.clone());
+        *self.fresh.lock().unwrap_or_else(|e| e.into_inner()) = Some(fresh);
+        self.refreshes.fetch_add(1, Ordering::Relaxed);
+        Some(child)
+    }
+
+    fn take_fresh_value(&self) -> Option<T> {
+        let fresh = self.fresh.lock().unwrap_or_else(|e| e.into_inner()).take();
+        fresh.and_then(|fresh| fresh.try_get())
+    }
+}
+
+#[async_trait]
+impl<T: Clone + Send + Sync + 'static> Generator for Cached<T> {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let Some(child) = self.refreshing_child().await else {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            self.complete();
+            return Ok(());
+        };
+
+        if child.is_active() && child.is_running() && !child.is_completed() {
+            if let Err(e) = child.traced_step().await {
+                self.logger().error(format!("Cached value producer step failed: {}", e));
+                child.base().step_errored(&e.to_string());
+            }
+        }
+        if !child.is_completed() {
+            return Ok(());
+        }
+
+        self.refresh.write().await.clear();
+        match self.take_fresh_value() {
+            _ if child.is_failed() => self.base.fail(&format!("{} failed", child.base().label())),
+            Some(value) => {
+                self.value.set_value(value).await;
+                *self.stored_at.lock().unwrap_or_else(|e| e.into_inner()) = Some(flow_now());
+                self.complete();
+            }
+            None => self.base.fail(&format!("{} finished without a value", child.base().label())),
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    // Keeps the cached value; a refresh still under way is dropped.
+    async fn reset(&self) -> bool {
+        self.refresh.write().await.clear();
+        self.fresh.lock().unwrap_or_else(|e| e.into_inner()).take();
+        self.base.reset();
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        self.refresh.read().await.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.refresh.try_read().ok().map(|refresh| refresh.clone())
+    }
+}
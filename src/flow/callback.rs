@@ -0,0 +1,91 @@
+use futures::future::BoxFuture;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::Instrument;
+use crate::flow::Callback;
+
+pub type AsyncCallback = Arc<dyn Fn() -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// A callback fired by timing components, either run inline or spawned.
+#[derive(Clone)]
+pub enum FlowCallback {
+    Sync(Callback),
+    Async(AsyncCallback),
+}
+
+impl FlowCallback {
+    pub fn from_sync<F>(callback: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        FlowCallback::Sync(Arc::new(callback))
+    }
+
+    pub fn from_async<F, Fut>(callback: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        FlowCallback::Async(Arc::new(move || Box::pin(callback())))
+    }
+
+    /// Sync callbacks run inline; async callbacks are spawned so the step
+    /// that fired them never waits on their completion.
+    pub fn invoke(&self) {
+        match self {
+            FlowCallback::Sync(callback) => callback(),
+            FlowCallback::Async(callback) => {
+                tokio::spawn(callback().in_current_span());
+            }
+        }
+    }
+}
+
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+/// Identifies a registered callback so it can later be removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CallbackHandle(u64);
+
+#[derive(Clone, Default)]
+pub struct CallbackList {
+    callbacks: Vec<(CallbackHandle, FlowCallback)>,
+}
+
+impl CallbackList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, callback: FlowCallback) -> CallbackHandle {
+        let handle = CallbackHandle(NEXT_HANDLE.fetch_add(1, Ordering::Relaxed));
+        self.callbacks.push((handle, callback));
+        handle
+    }
+
+    pub fn remove(&mut self, handle: CallbackHandle) -> bool {
+        let before = self.callbacks.len();
+        self.callbacks.retain(|(h, _)| *h != handle);
+        self.callbacks.len() != before
+    }
+
+    pub fn clear(&mut self) {
+        self.callbacks.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.callbacks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.callbacks.is_empty()
+    }
+
+    /// Invokes every callback in registration order.
+    pub fn invoke_all(&self) {
+        for (_, callback) in &self.callbacks {
+            callback.invoke();
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tokio::sync::mpsc::error::{TryRecvError, TrySendError};
+use tokio::sync::mpsc::{Receiver, Sender};
+use uuid::Uuid;
+use crate::flow::{Generator, GeneratorBase};
+use crate::{Logger, Result};
+
+pub(crate) const DEFAULT_BATCH_LIMIT: usize = 64;
+
+pub(crate) type ItemHandler<T> = Box<dyn Fn(T) + Send + Sync>;
+
+/// Feeds items sent by external code into the flow. Each step hands at most
+/// `batch_limit` waiting items to the handler without blocking, so a bounded
+/// channel pushes back on senders while the flow is busy. Completes once
+/// every sender is dropped and the channel is drained.
+pub struct ChannelSource<T> {
+    base: GeneratorBase,
+    receiver: Mutex<Receiver<T>>,
+    handler: ItemHandler<T>,
+    batch_limit: usize,
+}
+
+impl<T: Send + 'static> ChannelSource<T> {
+    pub fn new<F>(receiver: Receiver<T>, handler: F) -> Self
+    where
+        F: Fn(T) + Send + Sync + 'static,
+    {
+        Self {
+            base: GeneratorBase::new(),
+            receiver: Mutex::new(receiver),
+            handler: Box::new(handler),
+            batch_limit: DEFAULT_BATCH_LIMIT,
+        }
+    }
+
+    pub fn with_name<F>(name: impl Into<String>, receiver: Receiver<T>, handler: F) -> Self
+    where
+        F: Fn(T) + Send + Sync + 'static,
+    {
+        let source = Self::new(receiver, handler);
+        source.base.set_name(name.into());
+        source
+    }
+
+    /// Caps how many items one step consumes; at least one.
+    pub fn with_batch_limit(mut self, limit: usize) -> Self {
+        self.batch_limit = limit.max(1);
+        self
+    }
+
+    // Drains up to the batch limit; true once the channel is closed and empty.
+    fn drain(&self) -> bool {
+        let mut receiver = self.receiver.lock().unwrap_or_else(|e| e.into_inner());
+        for _ in 0..self.batch_limit {
+            match receiver.try_recv() {
+                Ok(item) => (self.handler)(item),
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => return true,
+            }
+        }
+        false
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> Generator for ChannelSource<T> {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        if self.drain() {
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+}
+
+/// Pushes results from the flow out to external code. Values passed to
+/// `send` are buffered and forwarded as the channel has room; when it is full
+/// they wait for a later step instead of blocking the kernel. Completes once
+/// closed and flushed, and fails if the receiver goes away first.
+pub struct ChannelSink<T> {
+    base: GeneratorBase,
+    sender: Mutex<Option<Sender<T>>>,
+    buffer: Mutex<VecDeque<T>>,
+    closed: AtomicBool,
+}
+
+impl<T: Send + 'static> ChannelSink<T> {
+    pub fn new(sender: Sender<T>) -> Self {
+        Self {
+            base: GeneratorBase::new(),
+            sender: Mutex::new(Some(sender)),
+            buffer: Mutex::new(VecDeque::new()),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_name(name: impl Into<String>, sender: Sender<T>) -> Self {
+        let sink = Self::new(sender);
+        sink.base.set_name(name.into());
+        sink
+    }
+
+    /// Queues `value` for the next step; returns it back once closed.
+    pub fn send(&self, value: T) -> std::result::Result<(), T> {
+        if self.is_closed() {
+            return Err(value);
+        }
+        self.buffer.lock().unwrap_or_else(|e| e.into_inner()).push_back(value);
+        Ok(())
+    }
+
+    /// No more values will be sent; the sender is dropped once flushed.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Values still waiting for room in the channel.
+    pub fn pending(&self) -> usize {
+        self.buffer.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    // Forwards buffered values until the channel is full. Err if the
+    // receiver has been dropped.
+    fn flush(&self) -> std::result::Result<(), ()> {
+        let sender = self.sender.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(sender) = sender.as_ref() else {
+            return Ok(());
+        };
+        let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        while let Some(value) = buffer.pop_front() {
+            match sender.try_send(value) {
+                Ok(()) => {}
+                Err(TrySendError::Full(value)) => {
+                    buffer.push_front(value);
+                    return Ok(());
+                }
+                Err(TrySendError::Closed(value)) => {
+                    buffer.push_front(value);
+                    return Err(());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> Generator for ChannelSink<T> {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        if self.flush().is_err() {
+            self.base.fail("channel receiver dropped");
+        } else if self.is_closed() && self.pending() == 0 {
+            self.sender.lock().unwrap_or_else(|e| e.into_inner()).take();
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+}
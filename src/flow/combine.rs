@@ -0,0 +1,190 @@
+//! Shorthands for ad-hoc coordination over a handful of generators, without
+//! naming and wiring the containers by hand.
+
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+use crate::flow::{reset_subtree, set_children_active, Barrier, ChildList, Generator, GeneratorBase, ParentLink};
+use crate::{fill_step_order, Logger, Result};
+
+/// Races `children`; see `FirstOf`.
+pub fn first_of<I>(children: I) -> Arc<FirstOf>
+where
+    I: IntoIterator<Item = Arc<dyn Generator>>,
+{
+    Arc::new(FirstOf::new(children))
+}
+
+/// A `Barrier` over `children`, completing once they all have.
+pub fn all_of<I>(children: I) -> Arc<Barrier>
+where
+    I: IntoIterator<Item = Arc<dyn Generator>>,
+{
+    Arc::new(Barrier::new().with_children(children.into_iter().collect()))
+}
+
+/// Steps its children side by side and completes as soon as one of them
+/// completes successfully, cancelling and deactivating the rest. Failed
+/// children drop out of the race; it fails only once all of them have.
+pub struct FirstOf {
+    base: GeneratorBase,
+    children: Arc<ChildList>,
+    winner: Mutex<Option<usize>>,
+    order: Mutex<Vec<usize>>,
+}
+
+impl FirstOf {
+    pub fn new<I>(children: I) -> Self
+    where
+        I: IntoIterator<Item = Arc<dyn Generator>>,
+    {
+        Self::build(GeneratorBase::new(), children)
+    }
+
+    pub fn with_name<I>(name: impl Into<String>, children: I) -> Self
+    where
+        I: IntoIterator<Item = Arc<dyn Generator>>,
+    {
+        Self::build(GeneratorBase::with_name(name), children)
+    }
+
+    fn build<I>(base: GeneratorBase, children: I) -> Self
+    where
+        I: IntoIterator<Item = Arc<dyn Generator>>,
+    {
+        let children: Vec<_> = children.into_iter().collect();
+        let list = Arc::new(RwLock::new(children.clone()));
+        for child in &children {
+            child.base().set_parent(Some(ParentLink::new(&base, &list)));
+        }
+        Self {
+            base,
+            children: list,
+            winner: Mutex::new(None),
+            order: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Index of the child that won, once one has.
+    pub fn winner_index(&self) -> Option<usize> {
+        *self.winner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn winner(&self) -> Option<Arc<dyn Generator>> {
+        let index = self.winner_index()?;
+        self.try_children()?.get(index).cloned()
+    }
+
+    // Stops every child but the winner.
+    fn cancel_losers(&self, children: &[Arc<dyn Generator>], winner: usize) {
+        for (index, child) in children.iter().enumerate() {
+            if index != winner && !child.is_completed() {
+                child.cancel();
+                child.deactivate();
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Generator for FirstOf {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let children = self.children.read().await;
+        if children.is_empty() {
+            self.base.fail("no children to race");
+            return Ok(());
+        }
+
+        let mut order = std::mem::take(&mut *self.order.lock().unwrap_or_else(|e| e.into_inner()));
+        fill_step_order(&mut order, children.len());
+        for &index in &order {
+            let child = &children[index];
+            if child.is_active() && child.is_running() && !child.is_completed() {
+                if let Err(e) = child.traced_step().await {
+                    self.logger().error(format!("Child step failed in first_of: {}", e));
+                    child.base().step_errored(&e.to_string());
+                }
+            }
+        }
+        *self.order.lock().unwrap_or_else(|e| e.into_inner()) = order;
+
+        // Ties go to the earlier child.
+        if let Some(winner) = children.iter().position(|child| child.is_completed() && !child.is_failed()) {
+            *self.winner.lock().unwrap_or_else(|e| e.into_inner()) = Some(winner);
+            self.cancel_losers(&children, winner);
+            self.complete();
+        } else if children.iter().all(|child| child.is_failed()) {
+            self.base.fail(&format!("all {} children failed", children.len()));
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        self.winner.lock().unwrap_or_else(|e| e.into_inner()).take();
+        reset_subtree(self).await;
+        set_children_active(self, self.is_active());
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        self.children.read().await.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.children.try_read().ok().map(|children| children.clone())
+    }
+}
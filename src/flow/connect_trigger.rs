@@ -0,0 +1,280 @@
+use async_trait::async_trait;
+use std::io;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{Backoff, Generator, GeneratorBase, RetryBudget};
+use crate::{Logger, Result};
+
+const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+enum Target {
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl Target {
+    // Connects and immediately hangs up again.
+    async fn probe(&self) -> io::Result<()> {
+        match self {
+            Self::Tcp(addr) => TcpStream::connect(addr).await.map(drop),
+            #[cfg(unix)]
+            Self::Unix(path) => tokio::net::UnixStream::connect(path).await.map(drop),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Tcp(addr) => addr.clone(),
+            #[cfg(unix)]
+            Self::Unix(path) => path.display().to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Attempts {
+    connected: AtomicBool,
+    in_flight: AtomicBool,
+    count: AtomicU32,
+}
+
+/// Completes once a TCP address or Unix socket accepts a connection, trying
+/// every `retry_interval`, for startup flows that wait on a dependency
+/// service. With a `timeout`, fails if the service is still down that long
+/// after the first step. Attempts run on spawned tasks and the connection is
+/// closed straight away.
+pub struct ConnectTrigger {
+    base: GeneratorBase,
+    target: Arc<Target>,
+    retry_interval: Duration,
+    timeout: Option<Duration>,
+    retry_budget: Option<RetryBudget>,
+    backoff: Option<Mutex<Box<dyn Backoff>>>,
+    attempts: Arc<Attempts>,
+    probe: Mutex<Option<JoinHandle<()>>>,
+    // When the first step ran, when the last attempt started, and how long
+    // the backoff has it wait before the next.
+    started_at: Mutex<Option<Instant>>,
+    last_attempt: Mutex<Option<Instant>>,
+    backoff_wait: Mutex<Duration>,
+}
+
+impl ConnectTrigger {
+    /// Waits for `addr`, such as `"localhost:5432"`, to accept TCP connections.
+    pub fn tcp(addr: impl Into<String>) -> Self {
+        Self::build(Target::Tcp(addr.into()))
+    }
+
+    /// Waits for the Unix socket at `path` to accept connections.
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        Self::build(Target::Unix(path.into()))
+    }
+
+    fn build(target: Target) -> Self {
+        Self {
+            base: GeneratorBase::new(),
+            target: Arc::new(target),
+            retry_interval: DEFAULT_RETRY_INTERVAL,
+            timeout: None,
+            retry_budget: None,
+            backoff: None,
+            attempts: Arc::new(Attempts::default()),
+            probe: Mutex::new(None),
+            started_at: Mutex::new(None),
+            last_attempt: Mutex::new(None),
+            backoff_wait: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Time between attempts, and the longest one attempt may take. Defaults
+    /// to 100ms.
+    pub fn retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    /// Fails the trigger if nothing connects within `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Spends a retry from `budget` for every attempt after the first, and
+    /// waits out another interval when it is spent.
+    pub fn retry_budget(mut self, budget: RetryBudget) -> Self {
+        self.retry_budget = Some(budget);
+        self
+    }
+
+    /// Spaces the attempts by `backoff` instead of the fixed interval, which
+    /// then only limits how long one attempt may take.
+    pub fn backoff(mut self, backoff: impl Backoff + 'static) -> Self {
+        self.backoff = Some(Mutex::new(Box::new(backoff)));
+        self
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.attempts.connected.load(Ordering::Relaxed)
+    }
+
+    /// Finished connection attempts, successful or not.
+    pub fn attempts(&self) -> u32 {
+        self.attempts.count.load(Ordering::Relaxed)
+    }
+
+    fn wait(&self) -> Duration {
+        match self.backoff {
+            Some(_) => *self.backoff_wait.lock().unwrap_or_else(|e| e.into_inner()),
+            None => self.retry_interval,
+        }
+    }
+
+    // Starts an attempt if the wait has passed and none is in flight.
+    fn attempt(&self, now: Instant) {
+        let mut last_attempt = self.last_attempt.lock().unwrap_or_else(|e| e.into_inner());
+        if last_attempt.is_some_and(|at| now.duration_since(at) < self.wait()) {
+            return;
+        }
+        if self.attempts.in_flight.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        *last_attempt = Some(now);
+        if let Some(budget) = self.retry_budget.as_ref().filter(|_| self.attempts() > 0) {
+            if !budget.try_spend() {
+                self.logger().verbose_with(4, || {
+                    format!("Retry budget {} spent, not retrying {}", budget.name(), self.target.describe())
+                });
+                self.attempts.in_flight.store(false, Ordering::Relaxed);
+                return;
+            }
+        }
+        if let Some(backoff) = &self.backoff {
+            let delay = backoff.lock().unwrap_or_else(|e| e.into_inner()).next_delay();
+            *self.backoff_wait.lock().unwrap_or_else(|e| e.into_inner()) = delay;
+        }
+
+        let (target, attempts, logger) = (self.target.clone(), self.attempts.clone(), self.logger().clone());
+        let limit = self.retry_interval;
+        let probe = tokio::spawn(async move {
+            match tokio::time::timeout(limit, target.probe()).await {
+                Ok(Ok(())) => attempts.connected.store(true, Ordering::Relaxed),
+                Ok(Err(e)) => logger.verbose_with(4, || format!("{} not ready: {}", target.describe(), e)),
+                Err(_) => logger.verbose_with(4, || format!("{} not ready: timed out", target.describe())),
+            }
+            attempts.count.fetch_add(1, Ordering::Relaxed);
+            attempts.in_flight.store(false, Ordering::Relaxed);
+        });
+        *self.probe.lock().unwrap_or_else(|e| e.into_inner()) = Some(probe);
+    }
+
+    // Stops an attempt still under way, so it cannot report after a reset.
+    fn abort_probe(&self) {
+        if let Some(probe) = self.probe.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            probe.abort();
+        }
+        self.attempts.in_flight.store(false, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+impl Generator for ConnectTrigger {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        if self.is_connected() {
+            self.logger().verbose(2, format!("{} is accepting connections", self.target.describe()));
+            self.complete();
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let started_at = *self.started_at.lock().unwrap_or_else(|e| e.into_inner()).get_or_insert(now);
+        if self.timeout.is_some_and(|timeout| now.duration_since(started_at) >= timeout) {
+            self.base.fail(&format!(
+                "{} not connectable after {} attempts",
+                self.target.describe(),
+                self.attempts()
+            ));
+            return Ok(());
+        }
+
+        self.attempt(now);
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        self.base.reset();
+        self.abort_probe();
+        self.attempts.connected.store(false, Ordering::Relaxed);
+        self.attempts.count.store(0, Ordering::Relaxed);
+        *self.started_at.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        *self.last_attempt.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        if let Some(backoff) = &self.backoff {
+            backoff.lock().unwrap_or_else(|e| e.into_inner()).reset();
+        }
+        true
+    }
+}
+
+impl Drop for ConnectTrigger {
+    fn drop(&mut self) {
+        self.abort_probe();
+    }
+}
@@ -1,15 +1,60 @@
 use async_trait::async_trait;
+use futures::future::BoxFuture;
 use std::future::Future;
 use std::sync::Arc;
-use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
+use tracing::Instrument;
 use uuid::Uuid;
 use crate::flow::{Generator, GeneratorBase};
+use crate::resources::{self, current_resources, Resources};
 use crate::{Logger, Result};
 
+type LazyTask = Box<dyn FnOnce(FlowCtx) -> BoxFuture<'static, Result<()>> + Send>;
+
+/// Handed to a lazily-spawned coroutine when it first steps.
+#[derive(Clone)]
+pub struct FlowCtx {
+    id: Uuid,
+    name: Option<String>,
+    logger: Logger,
+    resources: Resources,
+}
+
+impl FlowCtx {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn logger(&self) -> &Logger {
+        &self.logger
+    }
+
+    /// The `T` registered on the kernel that first stepped this coroutine.
+    pub fn resource<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.resources.get()
+    }
+
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+}
+
+pub(crate) fn task_span(base: &GeneratorBase) -> tracing::Span {
+    tracing::debug_span!(
+        "coroutine",
+        node = %base.name().unwrap_or_default(),
+        id = %base.id(),
+    )
+}
+
 pub struct AsyncCoroutine {
     base: GeneratorBase,
-    handle: Arc<Mutex<Option<JoinHandle<Result<()>>>>>,
+    handle: std::sync::Mutex<Option<JoinHandle<Result<()>>>>,
+    pending: std::sync::Mutex<Option<LazyTask>>,
 }
 
 impl AsyncCoroutine {
@@ -17,10 +62,12 @@ impl AsyncCoroutine {
     where
         F: Future<Output = Result<()>> + Send + 'static,
     {
-        let handle = tokio::spawn(future);
+        let base = GeneratorBase::new();
+        let handle = tokio::spawn(future.instrument(task_span(&base)));
         Self {
-            base: GeneratorBase::new(),
-            handle: Arc::new(Mutex::new(Some(handle))),
+            base,
+            handle: std::sync::Mutex::new(Some(handle)),
+            pending: std::sync::Mutex::new(None),
         }
     }
 
@@ -28,21 +75,43 @@ impl AsyncCoroutine {
     where
         F: Future<Output = Result<()>> + Send + 'static,
     {
-        let handle = tokio::spawn(future);
+        let base = GeneratorBase::with_name(name);
+        let handle = tokio::spawn(future.instrument(task_span(&base)));
+        Self {
+            base,
+            handle: std::sync::Mutex::new(Some(handle)),
+            pending: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Defers spawning until the coroutine is first stepped by its parent.
+    pub fn lazy<F, Fut>(name: impl Into<String>, task: F) -> Self
+    where
+        F: FnOnce(FlowCtx) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
         Self {
             base: GeneratorBase::with_name(name),
-            handle: Arc::new(Mutex::new(Some(handle))),
+            handle: std::sync::Mutex::new(None),
+            pending: std::sync::Mutex::new(Some(Box::new(move |ctx| Box::pin(task(ctx))))),
         }
     }
 
-    async fn is_handle_finished(&self) -> bool {
-        let handle_lock = self.handle.lock().await;
-        if let Some(ref handle) = *handle_lock {
-            handle.is_finished()
-        } else {
-            true
+    fn spawn_pending(&self) {
+        let task = self.pending.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(task) = task {
+            let resources = current_resources().unwrap_or_default();
+            let ctx = FlowCtx {
+                id: self.base.id(),
+                name: self.base.name(),
+                logger: self.base.logger().clone(),
+                resources: resources.clone(),
+            };
+            let task = resources::scope(resources, task(ctx)).instrument(task_span(&self.base));
+            *self.handle.lock().unwrap_or_else(|e| e.into_inner()) = Some(tokio::spawn(task));
         }
     }
+
 }
 
 #[async_trait]
@@ -51,11 +120,11 @@ impl Generator for AsyncCoroutine {
         self.base.id()
     }
 
-    fn name(&self) -> Option<&str> {
+    fn name(&self) -> Option<String> {
         self.base.name()
     }
 
-    fn set_name(&mut self, name: String) {
+    fn set_name(&self, name: String) {
         self.base.set_name(name);
     }
 
@@ -88,25 +157,22 @@ impl Generator for AsyncCoroutine {
             return Ok(());
         }
 
-        if let Some(ref name) = self.base.name() {
-            self.logger().verbose(4, format!("Stepping coroutine: {}", name));
-        }
+        self.base.start();
 
-        if self.is_handle_finished().await {
-            let mut handle_lock = self.handle.lock().await;
-            if let Some(handle) = handle_lock.take() {
-                match handle.await {
-                    Ok(result) => {
-                        if let Err(e) = result {
-                            self.logger().error(format!("Coroutine failed: {}", e));
-                        }
-                    }
-                    Err(e) => {
-                        self.logger().error(format!("Coroutine join failed: {}", e));
-                    }
-                }
+        self.logger().verbose_with(4, || format!("Stepping coroutine: {}", self.base.label()));
+
+        self.spawn_pending();
+
+        let finished = {
+            let mut handle = self.handle.lock().unwrap_or_else(|e| e.into_inner());
+            match handle.as_ref() {
+                Some(running) if !running.is_finished() => return Ok(()),
+                _ => handle.take(),
             }
-            self.complete();
+        };
+        match finished {
+            Some(handle) => settle(&self.base, "Coroutine", handle).await,
+            None => self.complete(),
         }
 
         Ok(())
@@ -115,6 +181,19 @@ impl Generator for AsyncCoroutine {
     fn logger(&self) -> &Logger {
         self.base.logger()
     }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    // A lazy body not yet started is dropped, so the coroutine fails as
+    // cancelled rather than completing empty-handed on its next step.
+    fn cancel(&self) {
+        if self.pending.lock().unwrap_or_else(|e| e.into_inner()).take().is_some() {
+            self.base.cancel();
+        }
+        abort(&self.base, &self.handle);
+    }
 }
 
 pub struct SyncCoroutine<T> {
@@ -146,11 +225,11 @@ impl<T: Send + Sync + 'static> Generator for SyncCoroutine<T> {
         self.base.id()
     }
 
-    fn name(&self) -> Option<&str> {
+    fn name(&self) -> Option<String> {
         self.base.name()
     }
 
-    fn set_name(&mut self, name: String) {
+    fn set_name(&self, name: String) {
         self.base.set_name(name);
     }
 
@@ -183,6 +262,8 @@ impl<T: Send + Sync + 'static> Generator for SyncCoroutine<T> {
             return Ok(());
         }
 
+        self.base.start();
+
         if let Some(ref step_fn) = self.step_fn {
             let result = step_fn();
             if result.is_none() {
@@ -196,4 +277,257 @@ impl<T: Send + Sync + 'static> Generator for SyncCoroutine<T> {
     fn logger(&self) -> &Logger {
         self.base.logger()
     }
-}
\ No newline at end of file
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        self.base.reset();
+        true
+    }
+}
+
+type BlockingTask = Box<dyn FnOnce() -> Result<()> + Send>;
+
+/// Runs CPU-bound or blocking synchronous work on tokio's blocking thread
+/// pool, so it never stalls the step loop. The work starts when the
+/// coroutine is first stepped.
+pub struct BlockingCoroutine {
+    base: GeneratorBase,
+    task: std::sync::Mutex<Option<BlockingTask>>,
+    handle: std::sync::Mutex<Option<JoinHandle<Result<()>>>>,
+}
+
+impl BlockingCoroutine {
+    pub fn new<F>(work: F) -> Self
+    where
+        F: FnOnce() -> Result<()> + Send + 'static,
+    {
+        Self {
+            base: GeneratorBase::new(),
+            task: std::sync::Mutex::new(Some(Box::new(work))),
+            handle: std::sync::Mutex::new(None),
+        }
+    }
+
+    pub fn with_name<F>(name: impl Into<String>, work: F) -> Self
+    where
+        F: FnOnce() -> Result<()> + Send + 'static,
+    {
+        let coroutine = Self::new(work);
+        coroutine.base.set_name(name.into());
+        coroutine
+    }
+
+    fn spawn_pending(&self) {
+        let task = self.task.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(task) = task {
+            let span = task_span(&self.base);
+            let handle = tokio::task::spawn_blocking(move || span.in_scope(task));
+            *self.handle.lock().unwrap_or_else(|e| e.into_inner()) = Some(handle);
+        }
+    }
+}
+
+fn take_finished(slot: &std::sync::Mutex<Option<JoinHandle<Result<()>>>>) -> Option<JoinHandle<Result<()>>> {
+    let mut handle = slot.lock().unwrap_or_else(|e| e.into_inner());
+    match handle.as_ref() {
+        Some(running) if running.is_finished() => handle.take(),
+        _ => None,
+    }
+}
+
+fn abort(base: &GeneratorBase, slot: &std::sync::Mutex<Option<JoinHandle<Result<()>>>>) {
+    if let Some(handle) = slot.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        handle.abort();
+        base.cancel();
+    }
+}
+
+// Completes `base` from a finished task, failing it if the task errored.
+async fn settle(base: &GeneratorBase, kind: &str, handle: JoinHandle<Result<()>>) {
+    match handle.await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            base.logger().error(format!("{} failed: {}", kind, e));
+            base.fail(&e.to_string());
+        }
+        Err(e) => {
+            base.logger().error(format!("{} join failed: {}", kind, e));
+            base.fail(&e.to_string());
+        }
+    }
+    base.complete();
+}
+
+#[async_trait]
+impl Generator for BlockingCoroutine {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+        self.spawn_pending();
+
+        if let Some(handle) = take_finished(&self.handle) {
+            settle(&self.base, "Blocking coroutine", handle).await;
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    // A blocking task cannot be interrupted, so only one not yet started is
+    // dropped.
+    fn cancel(&self) {
+        if self.task.lock().unwrap_or_else(|e| e.into_inner()).take().is_some() {
+            self.base.cancel();
+        }
+    }
+}
+
+/// A coroutine whose future need not be `Send`, such as one holding `Rc`
+/// handles. It runs on a `tokio::task::LocalSet`; build it with
+/// `LocalKernel::coroutine`, or with `new` from inside a `LocalSet`.
+pub struct LocalCoroutine {
+    base: GeneratorBase,
+    handle: std::sync::Mutex<Option<JoinHandle<Result<()>>>>,
+}
+
+impl LocalCoroutine {
+    /// Spawns `future` on the current `LocalSet`; panics outside of one.
+    pub fn new<F>(future: F) -> Self
+    where
+        F: Future<Output = Result<()>> + 'static,
+    {
+        let base = GeneratorBase::new();
+        let handle = tokio::task::spawn_local(future.instrument(task_span(&base)));
+        Self::from_handle(base, handle)
+    }
+
+    pub fn with_name<F>(name: impl Into<String>, future: F) -> Self
+    where
+        F: Future<Output = Result<()>> + 'static,
+    {
+        let base = GeneratorBase::with_name(name);
+        let handle = tokio::task::spawn_local(future.instrument(task_span(&base)));
+        Self::from_handle(base, handle)
+    }
+
+    pub(crate) fn from_handle(base: GeneratorBase, handle: JoinHandle<Result<()>>) -> Self {
+        Self {
+            base,
+            handle: std::sync::Mutex::new(Some(handle)),
+        }
+    }
+}
+
+#[async_trait]
+impl Generator for LocalCoroutine {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        if let Some(handle) = take_finished(&self.handle) {
+            settle(&self.base, "Local coroutine", handle).await;
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    fn cancel(&self) {
+        abort(&self.base, &self.handle);
+    }
+}
@@ -0,0 +1,230 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+use crate::flow::{accept_child, reset_subtree, set_children_active, ChildList, Generator, GeneratorBase, ParentLink};
+use crate::{Logger, Result};
+
+/// Runs tasks as soon as the tasks they depend on have completed, in
+/// parallel, for diamond-shaped flows that nested sequences and barriers
+/// cannot express. If a task fails, the tasks depending on it fail without
+/// running; the rest carry on. Completes once every task has finished.
+pub struct Dag {
+    base: GeneratorBase,
+    children: Arc<ChildList>,
+    // Added since the last step, moved into `children` when it runs, so
+    // `add` can stay synchronous for chaining.
+    pending: Mutex<Vec<Arc<dyn Generator>>>,
+    dependencies: Mutex<HashMap<Uuid, Vec<Uuid>>>,
+}
+
+/// Returned by `Dag::add` to declare what the new task waits for.
+pub struct DagTask<'a> {
+    dag: &'a Dag,
+    id: Uuid,
+}
+
+impl DagTask<'_> {
+    /// Holds the task back until `dependency`, another task of the same
+    /// dag, completes.
+    pub fn after<G: Generator + ?Sized>(self, dependency: &Arc<G>) -> Self {
+        self.after_id(dependency.id())
+    }
+
+    pub fn after_id(self, dependency: Uuid) -> Self {
+        let mut dependencies = self.dag.dependencies.lock().unwrap_or_else(|e| e.into_inner());
+        dependencies.entry(self.id).or_default().push(dependency);
+        drop(dependencies);
+        self
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+// Where a task stands, judged from its dependencies.
+enum Readiness {
+    Ready,
+    Waiting,
+    Blocked(String),
+}
+
+impl Dag {
+    pub fn new() -> Self {
+        Self::build(GeneratorBase::new())
+    }
+
+    pub fn with_name(name: impl Into<String>) -> Self {
+        Self::build(GeneratorBase::with_name(name))
+    }
+
+    fn build(base: GeneratorBase) -> Self {
+        Self {
+            base,
+            children: Arc::new(RwLock::new(Vec::new())),
+            pending: Mutex::new(Vec::new()),
+            dependencies: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Adds `task`, to run once everything named with `after` has completed,
+    /// or straight away if nothing is.
+    pub fn add(&self, task: Arc<dyn Generator>) -> DagTask<'_> {
+        let id = task.id();
+        if accept_child(&self.base, &task) {
+            task.base().set_parent(Some(ParentLink::new(&self.base, &self.children)));
+            self.pending.lock().unwrap_or_else(|e| e.into_inner()).push(task);
+        }
+        DagTask { dag: self, id }
+    }
+
+    /// The ids `task` waits for.
+    pub fn dependencies_of(&self, task: Uuid) -> Vec<Uuid> {
+        let dependencies = self.dependencies.lock().unwrap_or_else(|e| e.into_inner());
+        dependencies.get(&task).cloned().unwrap_or_default()
+    }
+
+    async fn take_pending(&self) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        if !pending.is_empty() {
+            self.children.write().await.extend(pending);
+        }
+    }
+
+    fn readiness(&self, task: &dyn Generator, by_id: &HashMap<Uuid, &Arc<dyn Generator>>) -> Readiness {
+        let dependencies = self.dependencies.lock().unwrap_or_else(|e| e.into_inner());
+        let mut ready = true;
+        for dependency in dependencies.get(&task.id()).into_iter().flatten() {
+            match by_id.get(dependency) {
+                None => return Readiness::Blocked(format!("depends on {}, which is not in the dag", dependency)),
+                Some(dependency) if dependency.is_failed() => {
+                    return Readiness::Blocked(format!("dependency {} failed", dependency.base().label()))
+                }
+                Some(dependency) => ready &= dependency.is_completed(),
+            }
+        }
+        if ready {
+            Readiness::Ready
+        } else {
+            Readiness::Waiting
+        }
+    }
+}
+
+impl Default for Dag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Generator for Dag {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+        self.take_pending().await;
+
+        let children = self.children.read().await;
+        let by_id: HashMap<Uuid, &Arc<dyn Generator>> = children.iter().map(|child| (child.id(), child)).collect();
+        let mut progressed = false;
+        for task in children.iter().filter(|task| !task.is_completed()) {
+            match self.readiness(task.as_ref(), &by_id) {
+                Readiness::Waiting => {}
+                Readiness::Blocked(reason) => {
+                    self.logger().warn(format!("Dag task {} {}", task.base().label(), reason));
+                    task.base().fail(&reason);
+                    progressed = true;
+                }
+                Readiness::Ready => {
+                    progressed = true;
+                    if task.is_active() && task.is_running() {
+                        if let Err(e) = task.traced_step().await {
+                            self.logger().error(format!("Task step failed in dag: {}", e));
+                            task.base().step_errored(&e.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        if children.iter().all(|task| task.is_completed()) {
+            self.complete();
+        } else if !progressed {
+            // Every unfinished task waits on another unfinished task.
+            let reason = "dependency cycle between its tasks";
+            self.logger().error(format!("Dag {}", reason));
+            self.base.fail(reason);
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        self.take_pending().await;
+        reset_subtree(self).await;
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        let mut children = self.children.read().await.clone();
+        children.extend(self.pending.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned());
+        children
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        let mut children = self.children.try_read().ok()?.clone();
+        children.extend(self.pending.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned());
+        Some(children)
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{GeneratorBase, LifecycleObserver};
+use crate::FlowError;
+
+/// A lifecycle event published on `AsyncKernel::subscribe`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlowEvent {
+    NodeAdded { id: Uuid, name: Option<String>, parent: Option<Uuid> },
+    NodeStarted { id: Uuid, name: Option<String> },
+    NodeCompleted { id: Uuid, name: Option<String> },
+    NodeFailed { id: Uuid, name: Option<String>, reason: String },
+    KernelBreak,
+    /// Nothing in the tree changed state for `idle`.
+    Stalled { idle: Duration, running: Vec<StalledNode> },
+}
+
+/// A generator still running when the kernel reported a stall.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StalledNode {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub kind: String,
+    /// For triggers, the condition that never fired.
+    pub condition: Option<String>,
+}
+
+/// Forwards lifecycle events to a broadcast channel. Events are dropped
+/// while nobody is subscribed.
+pub struct EventBroadcaster {
+    sender: broadcast::Sender<FlowEvent>,
+}
+
+impl EventBroadcaster {
+    pub fn new(sender: broadcast::Sender<FlowEvent>) -> Self {
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: FlowEvent) {
+        let _ = self.sender.send(event);
+    }
+}
+
+impl LifecycleObserver for EventBroadcaster {
+    fn on_added(&self, generator: &GeneratorBase) {
+        self.publish(FlowEvent::NodeAdded {
+            id: generator.id(),
+            name: generator.name(),
+            parent: generator.parent_id(),
+        });
+    }
+
+    fn on_start(&self, generator: &GeneratorBase) {
+        self.publish(FlowEvent::NodeStarted { id: generator.id(), name: generator.name() });
+    }
+
+    fn on_complete(&self, generator: &GeneratorBase) {
+        self.publish(FlowEvent::NodeCompleted { id: generator.id(), name: generator.name() });
+    }
+
+    fn on_fail(&self, generator: &GeneratorBase, reason: &str) {
+        self.publish(FlowEvent::NodeFailed {
+            id: generator.id(),
+            name: generator.name(),
+            reason: reason.to_string(),
+        });
+    }
+}
+
+/// A failure somewhere in a kernel's tree, from `AsyncKernel::take_errors`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeError {
+    pub id: Uuid,
+    /// The failing generator's node path, e.g. `Root/Stage/Upload`.
+    pub path: String,
+    pub error: FlowError,
+    pub at: Instant,
+}
+
+/// Called with the node path and error of every failure in a kernel's tree.
+pub type ErrorHandler = Arc<dyn Fn(&str, &FlowError) + Send + Sync>;
+
+/// How many errors a kernel keeps between `take_errors` calls by default.
+pub const DEFAULT_ERROR_CAPACITY: usize = 1024;
+
+// Keeps the latest failures and step errors below the generator it
+// observes, up to its capacity, and hands each to the error handler, if
+// any. Cancellations are not errors.
+pub(crate) struct ErrorLog {
+    errors: Mutex<VecDeque<NodeError>>,
+    capacity: AtomicUsize,
+    dropped: AtomicU64,
+    handler: RwLock<Option<ErrorHandler>>,
+}
+
+impl Default for ErrorLog {
+    fn default() -> Self {
+        Self {
+            errors: Mutex::new(VecDeque::new()),
+            capacity: AtomicUsize::new(DEFAULT_ERROR_CAPACITY),
+            dropped: AtomicU64::new(0),
+            handler: RwLock::new(None),
+        }
+    }
+}
+
+impl ErrorLog {
+    pub(crate) fn take(&self) -> Vec<NodeError> {
+        std::mem::take(&mut *self.errors.lock().unwrap_or_else(|e| e.into_inner())).into()
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        let mut errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        self.trim(&mut errors, capacity);
+    }
+
+    // Drops the oldest errors beyond `capacity`, counting them.
+    fn trim(&self, errors: &mut VecDeque<NodeError>, capacity: usize) {
+        while errors.len() > capacity {
+            errors.pop_front();
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn set_handler(&self, handler: Option<ErrorHandler>) {
+        *self.handler.write().unwrap_or_else(|e| e.into_inner()) = handler;
+    }
+
+    fn push(&self, generator: &GeneratorBase, error: &str) {
+        let error = NodeError {
+            id: generator.id(),
+            path: generator.logger().node_path(),
+            error: FlowError::new(error),
+            at: Instant::now(),
+        };
+        // Cloned out so the handler can itself install a new handler.
+        let handler = self.handler.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(handler) = handler {
+            handler(&error.path, &error.error);
+        }
+        let mut errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        errors.push_back(error);
+        self.trim(&mut errors, self.capacity.load(Ordering::Relaxed));
+    }
+}
+
+impl LifecycleObserver for ErrorLog {
+    fn on_fail(&self, generator: &GeneratorBase, reason: &str) {
+        if !generator.is_cancelled() {
+            self.push(generator, reason);
+        }
+    }
+
+    fn on_step_error(&self, generator: &GeneratorBase, error: &str) {
+        self.push(generator, error);
+    }
+}
@@ -0,0 +1,222 @@
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{set_children_active, ChildList, Generator, GeneratorBase, ParentLink};
+use crate::{flow_now, Logger, Result};
+
+/// Which subtree a `Fallback` is running, or ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FallbackPath {
+    Primary,
+    Fallback,
+}
+
+/// Runs `primary`, and if it hasn't completed after running for `deadline`,
+/// cancels it and runs `fallback` instead. Only time spent active counts
+/// toward the deadline. Completes, or fails, along
+/// with whichever subtree it ends up on; a primary failing before the
+/// deadline fails the node without trying the fallback. `path` tells which
+/// one it took.
+pub struct Fallback {
+    base: GeneratorBase,
+    primary: Arc<dyn Generator>,
+    fallback: Arc<dyn Generator>,
+    deadline: Duration,
+    // Holds whichever of the two is running.
+    slot: Arc<ChildList>,
+    path: Mutex<FallbackPath>,
+    elapsed: Mutex<ActiveTime>,
+}
+
+// Flow time the primary has spent active, charged between steps.
+#[derive(Default)]
+struct ActiveTime {
+    spent: Duration,
+    last_step: Option<Instant>,
+}
+
+impl Fallback {
+    pub fn new(primary: Arc<dyn Generator>, fallback: Arc<dyn Generator>, deadline: Duration) -> Self {
+        Self::build(GeneratorBase::new(), primary, fallback, deadline)
+    }
+
+    pub fn with_name(
+        name: impl Into<String>,
+        primary: Arc<dyn Generator>,
+        fallback: Arc<dyn Generator>,
+        deadline: Duration,
+    ) -> Self {
+        Self::build(GeneratorBase::with_name(name), primary, fallback, deadline)
+    }
+
+    fn build(base: GeneratorBase, primary: Arc<dyn Generator>, fallback: Arc<dyn Generator>, deadline: Duration) -> Self {
+        let slot = Arc::new(RwLock::new(vec![primary.clone()]));
+        primary.base().set_parent(Some(ParentLink::new(&base, &slot)));
+        fallback.base().set_parent(Some(ParentLink::new(&base, &slot)));
+        Self {
+            base,
+            primary,
+            fallback,
+            deadline,
+            slot,
+            path: Mutex::new(FallbackPath::Primary),
+            elapsed: Mutex::new(ActiveTime::default()),
+        }
+    }
+
+    pub fn primary(&self) -> &Arc<dyn Generator> {
+        &self.primary
+    }
+
+    pub fn fallback(&self) -> &Arc<dyn Generator> {
+        &self.fallback
+    }
+
+    pub fn deadline(&self) -> Duration {
+        self.deadline
+    }
+
+    pub fn path(&self) -> FallbackPath {
+        *self.path.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn took_fallback(&self) -> bool {
+        self.path() == FallbackPath::Fallback
+    }
+
+    fn running(&self) -> &Arc<dyn Generator> {
+        match self.path() {
+            FallbackPath::Primary => &self.primary,
+            FallbackPath::Fallback => &self.fallback,
+        }
+    }
+
+    // Adds the time since the last step while `active`, and returns the total.
+    fn charge(&self, active: bool) -> Duration {
+        let now = flow_now();
+        let mut elapsed = self.elapsed.lock().unwrap_or_else(|e| e.into_inner());
+        if !active {
+            elapsed.last_step = None;
+        } else if let Some(last_step) = elapsed.last_step.replace(now) {
+            elapsed.spent += now.saturating_duration_since(last_step);
+        }
+        elapsed.spent
+    }
+
+    // Gives up on the primary and puts the fallback in its place.
+    async fn switch_to_fallback(&self) {
+        self.logger().warn(format!(
+            "{} missed its {:?} deadline, running {}",
+            self.primary.base().label(),
+            self.deadline,
+            self.fallback.base().label()
+        ));
+        self.primary.cancel();
+        self.primary.deactivate();
+        *self.slot.write().await = vec![self.fallback.clone()];
+        *self.path.lock().unwrap_or_else(|e| e.into_inner()) = FallbackPath::Fallback;
+    }
+}
+
+#[async_trait]
+impl Generator for Fallback {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+        self.elapsed.lock().unwrap_or_else(|e| e.into_inner()).last_step = None;
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let child = self.running().clone();
+        let spent = self.charge(child.is_active());
+        if child.is_active() && child.is_running() && !child.is_completed() {
+            if let Err(e) = child.traced_step().await {
+                self.logger().error(format!("Fallback child step failed: {}", e));
+                child.base().step_errored(&e.to_string());
+            }
+        }
+
+        if child.is_failed() {
+            self.base.fail(&format!("{} failed", child.base().label()));
+        } else if child.is_completed() {
+            self.complete();
+        } else if !self.took_fallback() && spent >= self.deadline {
+            self.switch_to_fallback().await;
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        if self.took_fallback() && !self.fallback.reset().await {
+            return false;
+        }
+        if !self.primary.reset().await {
+            return false;
+        }
+        self.primary.activate();
+        *self.slot.write().await = vec![self.primary.clone()];
+        *self.path.lock().unwrap_or_else(|e| e.into_inner()) = FallbackPath::Primary;
+        *self.elapsed.lock().unwrap_or_else(|e| e.into_inner()) = ActiveTime::default();
+        self.base.reset();
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        self.slot.read().await.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.slot.try_read().ok().map(|slot| slot.clone())
+    }
+}
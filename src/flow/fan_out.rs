@@ -0,0 +1,330 @@
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{self, Receiver, UnboundedSender};
+use tokio::sync::RwLock;
+use tracing::Instrument;
+use uuid::Uuid;
+use crate::flow::{set_children_active, task_span, AsyncFuture, ChildList, Generator, GeneratorBase, ParentLink};
+use crate::{Logger, Result};
+
+enum FanOutSource<T> {
+    Channel(Receiver<T>),
+    Future(Arc<AsyncFuture<T>>),
+    Drained,
+}
+
+impl<T: Clone + Send + Sync + 'static> FanOutSource<T> {
+    // Everything available right now.
+    fn receive(&mut self, logger: &Logger) -> Vec<T> {
+        let mut values = Vec::new();
+        match self {
+            FanOutSource::Channel(receiver) => loop {
+                match receiver.try_recv() {
+                    Ok(value) => values.push(value),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        *self = FanOutSource::Drained;
+                        break;
+                    }
+                }
+            },
+            FanOutSource::Future(future) => {
+                if future.is_failed() {
+                    logger.warn(format!("Fan-out source {} failed", future.base().label()));
+                    *self = FanOutSource::Drained;
+                } else if let Some(value) = future.is_ready().then(|| future.try_get()).flatten() {
+                    values.push(value);
+                    *self = FanOutSource::Drained;
+                }
+            }
+            FanOutSource::Drained => {}
+        }
+        values
+    }
+}
+
+type FlowFactory<T> = Box<dyn Fn(T) -> Arc<dyn Generator> + Send + Sync>;
+
+enum Consumer<T> {
+    // Drained by a task of its own, started on the first step.
+    Callback {
+        queue: Option<UnboundedSender<T>>,
+        drain: Option<BoxFuture<'static, ()>>,
+        backlog: Arc<AtomicUsize>,
+    },
+    // One flow at a time, stepped as a child of the fan-out.
+    Flow {
+        factory: FlowFactory<T>,
+        queue: VecDeque<T>,
+        running: Option<Arc<dyn Generator>>,
+    },
+}
+
+impl<T> Consumer<T> {
+    fn backlog(&self) -> usize {
+        match self {
+            Consumer::Callback { backlog, .. } => backlog.load(Ordering::Relaxed),
+            Consumer::Flow { queue, running, .. } => queue.len() + usize::from(running.is_some()),
+        }
+    }
+}
+
+/// Forwards every value from a channel or future to each registered
+/// consumer, an async callback or a flow built per value. Each consumer
+/// has a queue of its own, so a slow one falls behind without holding up
+/// the rest. Completes once the source is drained and every consumer has
+/// caught up. A failing consumer is logged and counted but does not fail
+/// the fan-out. Values arriving before a consumer registers are not
+/// replayed to it.
+pub struct FanOut<T> {
+    base: GeneratorBase,
+    source: Mutex<FanOutSource<T>>,
+    consumers: Mutex<Vec<Consumer<T>>>,
+    flows: Arc<ChildList>,
+    received: AtomicUsize,
+    failed: Arc<AtomicUsize>,
+}
+
+impl<T: Clone + Send + Sync + 'static> FanOut<T> {
+    /// Values sent on `receiver`; drained once every sender is dropped.
+    pub fn from_channel(receiver: Receiver<T>) -> Self {
+        Self::build(FanOutSource::Channel(receiver))
+    }
+
+    /// The single value `future` resolves to.
+    pub fn from_future(future: Arc<AsyncFuture<T>>) -> Self {
+        Self::build(FanOutSource::Future(future))
+    }
+
+    fn build(source: FanOutSource<T>) -> Self {
+        Self {
+            base: GeneratorBase::new(),
+            source: Mutex::new(source),
+            consumers: Mutex::new(Vec::new()),
+            flows: Arc::new(RwLock::new(Vec::new())),
+            received: AtomicUsize::new(0),
+            failed: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Registers `consumer`, called with each value in turn on a task of its
+    /// own. Returns its index for `backlog`.
+    pub fn add_consumer<F, Fut>(&self, consumer: F) -> usize
+    where
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let (queue, mut values) = mpsc::unbounded_channel();
+        let backlog = Arc::new(AtomicUsize::new(0));
+        let (remaining, failed, logger) = (backlog.clone(), self.failed.clone(), self.logger().clone());
+        let drain = Box::pin(async move {
+            while let Some(value) = values.recv().await {
+                if let Err(e) = consumer(value).await {
+                    failed.fetch_add(1, Ordering::Relaxed);
+                    logger.error(format!("Fan-out consumer failed: {}", e));
+                }
+                remaining.fetch_sub(1, Ordering::Relaxed);
+            }
+        });
+        self.push_consumer(Consumer::Callback {
+            queue: Some(queue),
+            drain: Some(drain),
+            backlog,
+        })
+    }
+
+    /// Registers a consumer that runs the flow `factory` builds for each
+    /// value, one flow at a time. Returns its index for `backlog`.
+    pub fn add_flow<F>(&self, factory: F) -> usize
+    where
+        F: Fn(T) -> Arc<dyn Generator> + Send + Sync + 'static,
+    {
+        self.push_consumer(Consumer::Flow {
+            factory: Box::new(factory),
+            queue: VecDeque::new(),
+            running: None,
+        })
+    }
+
+    fn push_consumer(&self, consumer: Consumer<T>) -> usize {
+        let mut consumers = self.lock_consumers();
+        consumers.push(consumer);
+        consumers.len() - 1
+    }
+
+    pub fn consumers(&self) -> usize {
+        self.lock_consumers().len()
+    }
+
+    /// Values received from the source so far.
+    pub fn received(&self) -> usize {
+        self.received.load(Ordering::Relaxed)
+    }
+
+    /// Values `consumer` has yet to finish with, including the one in hand.
+    pub fn backlog(&self, consumer: usize) -> usize {
+        self.lock_consumers().get(consumer).map_or(0, Consumer::backlog)
+    }
+
+    /// Deliveries a consumer failed on, across all consumers.
+    pub fn failed(&self) -> usize {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    fn lock_consumers(&self) -> std::sync::MutexGuard<'_, Vec<Consumer<T>>> {
+        self.consumers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Queues the values for every consumer, and hangs up on the callback
+    // tasks once the source is drained.
+    fn deliver(&self, consumers: &mut [Consumer<T>]) -> bool {
+        let (values, drained) = {
+            let mut source = self.source.lock().unwrap_or_else(|e| e.into_inner());
+            let values = source.receive(self.logger());
+            (values, matches!(*source, FanOutSource::Drained))
+        };
+        self.received.fetch_add(values.len(), Ordering::Relaxed);
+
+        for consumer in consumers.iter_mut() {
+            match consumer {
+                Consumer::Callback { queue, drain, backlog } => {
+                    if let Some(drain) = drain.take() {
+                        tokio::spawn(drain.instrument(task_span(&self.base)));
+                    }
+                    if let Some(sender) = queue {
+                        for value in &values {
+                            backlog.fetch_add(1, Ordering::Relaxed);
+                            if sender.send(value.clone()).is_err() {
+                                backlog.fetch_sub(1, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                    if drained {
+                        *queue = None;
+                    }
+                }
+                Consumer::Flow { queue, .. } => queue.extend(values.iter().cloned()),
+            }
+        }
+        drained
+    }
+
+    // Retires finished flows and starts the next queued one per consumer.
+    fn dispatch_flows(&self, consumers: &mut [Consumer<T>], flows: &mut Vec<Arc<dyn Generator>>) {
+        for consumer in consumers.iter_mut() {
+            let Consumer::Flow { factory, queue, running } = consumer else {
+                continue;
+            };
+            if let Some(flow) = running.take_if(|flow| flow.is_completed()) {
+                if flow.is_failed() {
+                    self.failed.fetch_add(1, Ordering::Relaxed);
+                    self.logger().error(format!("Fan-out flow {} failed", flow.base().label()));
+                }
+            }
+            if running.is_none() {
+                if let Some(value) = queue.pop_front() {
+                    let flow = factory(value);
+                    flow.base().set_parent(Some(ParentLink::new(&self.base, &self.flows)));
+                    flows.push(flow.clone());
+                    *running = Some(flow);
+                }
+            }
+        }
+        flows.retain(|flow| !flow.is_completed());
+    }
+}
+
+#[async_trait]
+impl<T: Clone + Send + Sync + 'static> Generator for FanOut<T> {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let mut flows = self.flows.write().await;
+        let drained = {
+            let mut consumers = self.lock_consumers();
+            let drained = self.deliver(&mut consumers);
+            self.dispatch_flows(&mut consumers, &mut flows);
+            drained
+        };
+
+        for flow in flows.iter() {
+            if flow.is_active() && flow.is_running() && !flow.is_completed() {
+                if let Err(e) = flow.traced_step().await {
+                    self.logger().error(format!("Fan-out flow step failed: {}", e));
+                    flow.base().step_errored(&e.to_string());
+                }
+            }
+        }
+
+        let mut consumers = self.lock_consumers();
+        self.dispatch_flows(&mut consumers, &mut flows);
+        if drained && consumers.iter().all(|consumer| consumer.backlog() == 0) {
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        self.flows.read().await.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.flows.try_read().ok().map(|flows| flows.clone())
+    }
+}
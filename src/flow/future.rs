@@ -1,43 +1,129 @@
 use async_trait::async_trait;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
-use std::task::{Context, Poll};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 use tokio::sync::{RwLock, Notify};
 use uuid::Uuid;
 use crate::flow::{Generator, GeneratorBase};
-use crate::{Logger, Result};
+use crate::{FlowError, Logger, Result};
 
 pub struct AsyncFuture<T> {
     base: GeneratorBase,
     inner: Arc<RwLock<Option<T>>>,
     notify: Arc<Notify>,
+    wakers: Arc<Mutex<Vec<Waker>>>,
+    resolver: Option<Resolver<T>>,
+    set_count: AtomicUsize,
+    error: Mutex<Option<FlowError>>,
+}
+
+type Resolver<T> = Box<dyn Fn() -> Option<std::result::Result<T, FlowError>> + Send + Sync>;
+
+/// The write side of an `AsyncFuture`; consumers get the read-only node.
+pub struct Promise<T> {
+    future: Arc<AsyncFuture<T>>,
+}
+
+impl<T: Send + Sync + 'static> Promise<T> {
+    pub async fn set_value(&self, value: T) {
+        self.future.set_value(value).await;
+    }
+
+    /// Fails the future, releasing consumers blocked in `wait_result`.
+    pub fn set_error(&self, error: impl Into<FlowError>) {
+        self.future.set_error(error.into());
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.future.is_ready()
+    }
+
+    pub fn future_id(&self) -> Uuid {
+        self.future.id()
+    }
+}
+
+impl<T> Clone for Promise<T> {
+    fn clone(&self) -> Self {
+        Self {
+            future: self.future.clone(),
+        }
+    }
 }
 
 impl<T: Send + Sync + 'static> AsyncFuture<T> {
-    pub fn new() -> Self {
+    /// A promise for the producer and the future node for consumers.
+    pub fn pair() -> (Promise<T>, Arc<Self>) {
+        let future = Arc::new(Self::new());
+        (Promise { future: future.clone() }, future)
+    }
+
+    pub fn named_pair(name: impl Into<String>) -> (Promise<T>, Arc<Self>) {
+        let future = Arc::new(Self::with_name(name));
+        (Promise { future: future.clone() }, future)
+    }
+
+    pub(crate) fn new() -> Self {
         Self {
             base: GeneratorBase::new(),
             inner: Arc::new(RwLock::new(None)),
             notify: Arc::new(Notify::new()),
+            wakers: Arc::new(Mutex::new(Vec::new())),
+            resolver: None,
+            set_count: AtomicUsize::new(0),
+            error: Mutex::new(None),
         }
     }
 
-    pub fn with_name(name: impl Into<String>) -> Self {
+    pub(crate) fn with_name(name: impl Into<String>) -> Self {
         Self {
             base: GeneratorBase::with_name(name),
             inner: Arc::new(RwLock::new(None)),
             notify: Arc::new(Notify::new()),
+            wakers: Arc::new(Mutex::new(Vec::new())),
+            resolver: None,
+            set_count: AtomicUsize::new(0),
+            error: Mutex::new(None),
         }
     }
 
-    pub async fn set_value(&self, value: T) {
+    // A future that sets itself, when stepped, once `resolver` yields a
+    // value or an error.
+    fn resolved_by<F>(resolver: F) -> Self
+    where
+        F: Fn() -> Option<std::result::Result<T, FlowError>> + Send + Sync + 'static,
+    {
+        Self {
+            resolver: Some(Box::new(resolver)),
+            ..Self::new()
+        }
+    }
+
+    pub(crate) async fn set_value(&self, value: T) {
         let mut inner = self.inner.write().await;
         *inner = Some(value);
+        drop(inner);
+        self.set_count.fetch_add(1, Ordering::Relaxed);
         self.notify.notify_waiters();
+        self.wake_pollers();
         self.complete();
     }
 
+    pub(crate) fn set_error(&self, error: FlowError) {
+        self.base.fail(error.message());
+        *self.error.lock().unwrap_or_else(|e| e.into_inner()) = Some(error);
+        self.notify.notify_waiters();
+        self.wake_pollers();
+    }
+
+    /// The error the producer failed with, if any.
+    pub fn error(&self) -> Option<FlowError> {
+        self.error.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
     pub async fn get_value(&self) -> Option<T> 
     where
         T: Clone,
@@ -56,25 +142,147 @@ impl<T: Send + Sync + 'static> AsyncFuture<T> {
         T: Clone,
     {
         loop {
-            {
-                let inner = self.inner.read().await;
-                if let Some(ref value) = *inner {
-                    return value.clone();
-                }
+            // Registered before the check, so a value set in between still
+            // wakes this waiter.
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(value) = self.inner.read().await.clone() {
+                return value;
+            }
+
+            notified.await;
+        }
+    }
+
+    /// Waits for the value, or for the error if the producer fails. `wait`
+    /// only ever returns a value, so prefer this when the producer can fail.
+    pub async fn wait_result(&self) -> Result<T>
+    where
+        T: Clone,
+    {
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(value) = self.inner.read().await.clone() {
+                return Ok(value);
+            }
+            if let Some(error) = self.error() {
+                return Err(error.into());
             }
-            
-            self.notify.notified().await;
+
+            notified.await;
         }
     }
 
+    /// Like `wait`, but gives up with `None` once `timeout` passes.
+    pub async fn wait_timeout(&self, timeout: Duration) -> Option<T>
+    where
+        T: Clone,
+    {
+        tokio::time::timeout(timeout, self.wait()).await.ok()
+    }
+
+    /// The value if it has been set, without waiting. Also `None` in the
+    /// brief window while `set_value` holds the lock.
+    pub fn try_get(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.inner.try_read().ok().and_then(|inner| inner.clone())
+    }
+
+    /// A future holding `transform` of this one's value, or failing with
+    /// this one's error. The relay runs on a spawned task, so this must be
+    /// called within a tokio runtime.
+    pub fn map<U, F>(self: &Arc<Self>, transform: F) -> Arc<AsyncFuture<U>>
+    where
+        T: Clone,
+        U: Send + Sync + 'static,
+        F: FnOnce(T) -> U + Send + 'static,
+    {
+        let source = self.clone();
+        let output = Arc::new(AsyncFuture::new());
+        let target = output.clone();
+        tokio::spawn(async move {
+            match source.wait_result().await {
+                Ok(value) => target.set_value(transform(value)).await,
+                Err(e) => target.set_error(FlowError::new(e.to_string())),
+            }
+        });
+        output
+    }
+
+    /// Chains a stage that itself produces a future. Fails if either this
+    /// future or the chained one does.
+    pub fn and_then<U, F>(self: &Arc<Self>, next: F) -> Arc<AsyncFuture<U>>
+    where
+        T: Clone,
+        U: Clone + Send + Sync + 'static,
+        F: FnOnce(T) -> Arc<AsyncFuture<U>> + Send + 'static,
+    {
+        let source = self.clone();
+        let output = Arc::new(AsyncFuture::new());
+        let target = output.clone();
+        tokio::spawn(async move {
+            let chained = match source.wait_result().await {
+                Ok(value) => next(value).wait_result().await,
+                Err(e) => Err(e),
+            };
+            match chained {
+                Ok(value) => target.set_value(value).await,
+                Err(e) => target.set_error(FlowError::new(e.to_string())),
+            }
+        });
+        output
+    }
+
+    /// A future holding both values once both are set, or failing as soon
+    /// as either input fails.
+    pub fn zip<U>(self: &Arc<Self>, other: &Arc<AsyncFuture<U>>) -> Arc<AsyncFuture<(T, U)>>
+    where
+        T: Clone,
+        U: Clone + Send + Sync + 'static,
+    {
+        let (first, second) = (self.clone(), other.clone());
+        let output = Arc::new(AsyncFuture::new());
+        let target = output.clone();
+        tokio::spawn(async move {
+            match tokio::try_join!(first.wait_result(), second.wait_result()) {
+                Ok(pair) => target.set_value(pair).await,
+                Err(e) => target.set_error(FlowError::new(e.to_string())),
+            }
+        });
+        output
+    }
+
     pub fn is_ready(&self) -> bool {
         self.is_completed()
     }
-}
 
-impl<T: Send + Sync + 'static> Default for AsyncFuture<T> {
-    fn default() -> Self {
-        Self::new()
+    /// Clears the value and completion so the future can be set again in the
+    /// next round of a looping flow. Waiters keep waiting for the next value.
+    pub async fn reset(&self) {
+        let mut inner = self.inner.write().await;
+        *inner = None;
+        drop(inner);
+        *self.error.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.base.reset();
+    }
+
+    /// How many times a value has been set, across resets.
+    pub fn set_count(&self) -> usize {
+        self.set_count.load(Ordering::Relaxed)
+    }
+
+    fn wake_pollers(&self) {
+        let wakers = std::mem::take(&mut *self.wakers.lock().unwrap_or_else(|e| e.into_inner()));
+        for waker in wakers {
+            waker.wake();
+        }
     }
 }
 
@@ -84,11 +292,11 @@ impl<T: Send + Sync + 'static> Generator for AsyncFuture<T> {
         self.base.id()
     }
 
-    fn name(&self) -> Option<&str> {
+    fn name(&self) -> Option<String> {
         self.base.name()
     }
 
-    fn set_name(&mut self, name: String) {
+    fn set_name(&self, name: String) {
         self.base.set_name(name);
     }
 
@@ -121,9 +329,17 @@ impl<T: Send + Sync + 'static> Generator for AsyncFuture<T> {
             return Ok(());
         }
 
-        let inner = self.inner.read().await;
-        if inner.is_some() {
+        self.base.start();
+
+        let ready = self.inner.read().await.is_some();
+        if ready {
             self.complete();
+        } else {
+            match self.resolver.as_ref().and_then(|resolve| resolve()) {
+                Some(Ok(value)) => self.set_value(value).await,
+                Some(Err(error)) => self.set_error(error),
+                None => {}
+            }
         }
 
         Ok(())
@@ -132,27 +348,89 @@ impl<T: Send + Sync + 'static> Generator for AsyncFuture<T> {
     fn logger(&self) -> &Logger {
         self.base.logger()
     }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        AsyncFuture::reset(self).await;
+        true
+    }
+}
+
+/// A future, resolved when stepped in a flow, holding every input's value
+/// in order once all of them are set. Fails with the first error found as
+/// soon as any input fails.
+pub fn join_all<T>(futures: Vec<Arc<AsyncFuture<T>>>) -> Arc<AsyncFuture<Vec<T>>>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    Arc::new(AsyncFuture::resolved_by(move || {
+        if let Some(error) = futures.iter().find_map(|future| future.error()) {
+            return Some(Err(error));
+        }
+        futures.iter().map(|future| future.try_get()).collect::<Option<_>>().map(Ok)
+    }))
+}
+
+/// A future, resolved when stepped in a flow, holding the index and value of
+/// the first input found set. Inputs are checked in order each step. Fails
+/// once every input has failed.
+pub fn select_any<T>(futures: Vec<Arc<AsyncFuture<T>>>) -> Arc<AsyncFuture<(usize, T)>>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    Arc::new(AsyncFuture::resolved_by(move || {
+        let first = futures
+            .iter()
+            .enumerate()
+            .find_map(|(index, future)| future.try_get().map(|value| (index, value)));
+        match first {
+            Some(found) => Some(Ok(found)),
+            None if !futures.is_empty() && futures.iter().all(|future| future.error().is_some()) => {
+                Some(Err(FlowError::new(format!("all {} inputs failed", futures.len()))))
+            }
+            None => None,
+        }
+    }))
+}
+
+impl<T: Clone> AsyncFuture<T> {
+    // The waker is registered before the value is checked, so a `set_value`
+    // racing with `poll` always either is seen or wakes the task.
+    fn poll_value(&self, cx: &mut Context<'_>) -> Poll<T> {
+        {
+            let mut wakers = self.wakers.lock().unwrap_or_else(|e| e.into_inner());
+            if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+        }
+
+        match self.inner.try_read() {
+            Ok(inner) => match inner.as_ref() {
+                Some(value) => Poll::Ready(value.clone()),
+                None => Poll::Pending,
+            },
+            // A writer holds the lock and wakes the registered wakers when done.
+            Err(_) => Poll::Pending,
+        }
+    }
 }
 
 impl<T: Send + Sync + 'static + Clone> Future for AsyncFuture<T> {
     type Output = T;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let notify = self.notify.clone();
-        let inner = self.inner.clone();
-        
-        let waker = cx.waker().clone();
-        tokio::spawn(async move {
-            notify.notified().await;
-            waker.wake();
-        });
-        
-        match futures::executor::block_on(async {
-            let inner = inner.read().await;
-            inner.clone()
-        }) {
-            Some(value) => Poll::Ready(value),
-            None => Poll::Pending,
-        }
+        self.poll_value(cx)
     }
-}
\ No newline at end of file
+}
+
+/// Lets a shared node be awaited directly: `(&*future).await`.
+impl<T: Send + Sync + 'static + Clone> Future for &AsyncFuture<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.poll_value(cx)
+    }
+}
@@ -1,13 +1,230 @@
 use async_trait::async_trait;
-use std::sync::atomic::{AtomicBool, Ordering};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, Weak};
+use tracing::Instrument;
 use uuid::Uuid;
+use crate::flow::{render_tree, snapshot, to_mermaid, validate, walk, ChildHandle, ChildSlab, FlowVisitor, NodeSnapshot};
 use crate::Logger;
 
+pub type Callback = Arc<dyn Fn() + Send + Sync>;
+pub type FailCallback = Arc<dyn Fn(&str) + Send + Sync>;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GeneratorState {
+    Inactive,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl fmt::Display for GeneratorState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            GeneratorState::Inactive => "inactive",
+            GeneratorState::Running => "running",
+            GeneratorState::Completed => "completed",
+            GeneratorState::Failed => "failed",
+        };
+        f.write_str(text)
+    }
+}
+
+pub type ChildList = tokio::sync::RwLock<Vec<Arc<dyn Generator>>>;
+pub(crate) type SlabList = tokio::sync::RwLock<ChildSlab>;
+type ParentSlot = RwLock<Option<ParentLink>>;
+type ObserverList = RwLock<Vec<Arc<dyn LifecycleObserver>>>;
+
+/// Receives lifecycle events from every generator below the one it is
+/// registered on, including generators added after registration.
+pub trait LifecycleObserver: Send + Sync {
+    fn on_added(&self, _generator: &GeneratorBase) {}
+    fn on_start(&self, _generator: &GeneratorBase) {}
+    fn on_complete(&self, _generator: &GeneratorBase) {}
+    fn on_fail(&self, _generator: &GeneratorBase, _reason: &str) {}
+    fn on_triggered(&self, _generator: &GeneratorBase) {}
+    /// `step` returned an error; the generator itself carries on unless it
+    /// also failed.
+    fn on_step_error(&self, _generator: &GeneratorBase, _error: &str) {}
+
+    /// Decides whether a `Trigger` fires this step in place of its own
+    /// condition; `FlowReplayer` uses this to reproduce recorded firings.
+    fn override_trigger(&self, _generator: &GeneratorBase) -> Option<bool> {
+        None
+    }
+}
+
+/// A child's reference back to the container that holds it.
+#[derive(Clone)]
+pub struct ParentLink {
+    id: Uuid,
+    children: Siblings,
+    cursor: Option<Weak<tokio::sync::RwLock<usize>>>,
+    ancestry: Weak<ParentSlot>,
+    observers: Weak<ObserverList>,
+    logger: Logger,
+}
+
+// Where a child sits in its parent: a plain list, or a `Node`'s slab under
+// the child's handle.
+#[derive(Clone)]
+enum Siblings {
+    List(Weak<ChildList>),
+    Slab(Weak<SlabList>, ChildHandle),
+}
+
+impl Siblings {
+    fn parent_alive(&self) -> bool {
+        match self {
+            Siblings::List(children) => children.strong_count() > 0,
+            Siblings::Slab(children, _) => children.strong_count() > 0,
+        }
+    }
+}
+
+impl ParentLink {
+    pub fn new(parent: &GeneratorBase, children: &Arc<ChildList>) -> Self {
+        Self::linked(parent, Siblings::List(Arc::downgrade(children)))
+    }
+
+    pub(crate) fn in_slab(parent: &GeneratorBase, children: &Arc<SlabList>, handle: ChildHandle) -> Self {
+        Self::linked(parent, Siblings::Slab(Arc::downgrade(children), handle))
+    }
+
+    fn linked(parent: &GeneratorBase, children: Siblings) -> Self {
+        Self {
+            id: parent.id(),
+            children,
+            cursor: None,
+            ancestry: Arc::downgrade(&parent.parent),
+            observers: Arc::downgrade(&parent.observers),
+            logger: parent.logger.clone(),
+        }
+    }
+
+    /// Lets detaching children keep a sequence's current index in place.
+    pub fn with_cursor(mut self, cursor: &Arc<tokio::sync::RwLock<usize>>) -> Self {
+        self.cursor = Some(Arc::downgrade(cursor));
+        self
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+/// Errors if adding `child` under `parent` would make a generator its own
+/// ancestor, which would step forever.
+pub fn check_no_cycle(parent: &GeneratorBase, child: &dyn Generator) -> crate::Result<()> {
+    if child.id() == parent.id() || parent.ancestors().contains(&child.id()) {
+        return Err(format!(
+            "adding {} under {} would create a cycle",
+            child.base().label(),
+            parent.label(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+// The usual container reset: every child that supports it, then the container.
+pub(crate) async fn reset_subtree(parent: &dyn Generator) {
+    for child in parent.children().await {
+        child.reset().await;
+    }
+    parent.base().reset();
+}
+
+// Containers hand activation changes down, so a deactivated subtree is
+// suspended as a whole and its timers stop accruing time.
+pub(crate) fn set_children_active(parent: &dyn Generator, active: bool) {
+    for child in parent.try_children().unwrap_or_default() {
+        if active {
+            child.activate();
+        } else {
+            child.deactivate();
+        }
+    }
+}
+
+// Used by the infallible container methods: logs and rejects a cyclic insert.
+pub(crate) fn accept_child(parent: &GeneratorBase, child: &Arc<dyn Generator>) -> bool {
+    match check_no_cycle(parent, child.as_ref()) {
+        Ok(()) => {
+            child.logger().set_kind(child.type_name());
+            true
+        }
+        Err(e) => {
+            parent.logger().error(format!("Rejected child: {}", e));
+            false
+        }
+    }
+}
+
+pub type GeneratorPredicate<'a> = dyn Fn(&Arc<dyn Generator>) -> bool + Send + Sync + 'a;
+
+/// Lets an `Arc<dyn Generator>` be turned back into its concrete type.
+/// Implemented for every generator; see `dyn Generator::downcast_ref` and
+/// `downcast_arc`.
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+    fn into_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+}
+
+impl<T: Generator + 'static> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
+impl dyn Generator {
+    pub fn is<T: Generator + 'static>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+
+    pub fn downcast_ref<T: Generator + 'static>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+}
+
+/// The generator as a `T`, if that is its concrete type.
+pub fn downcast_arc<T: Generator + 'static>(generator: Arc<dyn Generator>) -> Option<Arc<T>> {
+    generator.into_any_arc().downcast().ok()
+}
+
+/// `Generator::find_typed` for a generator known only as `dyn Generator`.
+pub async fn find_typed<T: Generator + 'static>(root: &dyn Generator, name: &str) -> Option<Arc<T>> {
+    let found = root.find(&|child| child.name().as_deref() == Some(name) && child.is::<T>()).await?;
+    downcast_arc(found)
+}
+
+/// `Generator::find_all_typed` for a generator known only as `dyn Generator`.
+pub async fn find_all_typed<T: Generator + 'static>(root: &dyn Generator) -> Vec<Arc<T>> {
+    let mut found = Vec::new();
+    let mut stack = root.children().await;
+    stack.reverse();
+    while let Some(child) = stack.pop() {
+        let mut grandchildren = child.children().await;
+        grandchildren.reverse();
+        stack.extend(grandchildren);
+        if child.is::<T>() {
+            found.extend(downcast_arc(child));
+        }
+    }
+    found
+}
+
 #[async_trait]
-pub trait Generator: Send + Sync {
+pub trait Generator: AsAny + Send + Sync {
     fn id(&self) -> Uuid;
-    fn name(&self) -> Option<&str>;
-    fn set_name(&mut self, name: String);
+    fn name(&self) -> Option<String>;
+    fn set_name(&self, name: String);
     fn is_active(&self) -> bool;
     fn is_running(&self) -> bool;
     fn is_completed(&self) -> bool;
@@ -16,14 +233,276 @@ pub trait Generator: Send + Sync {
     fn complete(&self);
     async fn step(&self) -> crate::Result<()>;
     fn logger(&self) -> &Logger;
+    fn base(&self) -> &GeneratorBase;
+
+    fn is_failed(&self) -> bool {
+        self.base().is_failed()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.base().is_cancelled()
+    }
+
+    /// The concrete type's short name, e.g. `Sequence` or `AsyncFuture`.
+    fn type_name(&self) -> &'static str {
+        let full = std::any::type_name::<Self>();
+        let path = full.split('<').next().unwrap_or(full);
+        path.rsplit("::").next().unwrap_or(path)
+    }
+
+    /// The `tracing` span that a parent enters while stepping this generator.
+    fn span(&self) -> tracing::Span {
+        tracing::debug_span!(
+            "flow.step",
+            path = %self.logger().node_path(),
+            node = %self.name().unwrap_or_default(),
+            kind = self.type_name(),
+            id = %self.id(),
+        )
+    }
+
+    /// `step` run inside this generator's span, and timed when the kernel is
+    /// profiling; containers step children this way.
+    async fn traced_step(&self) -> crate::Result<()> {
+        self.logger().set_kind(self.type_name());
+        let span = self.span();
+        match crate::profile::current() {
+            None => self.step().instrument(span).await,
+            Some(profiler) => {
+                let started = std::time::Instant::now();
+                let result = self.step().instrument(span).await;
+                profiler.record(self.logger().node_path(), self.type_name(), started.elapsed());
+                result
+            }
+        }
+    }
+
+    fn state(&self) -> GeneratorState {
+        if self.is_failed() {
+            GeneratorState::Failed
+        } else if self.is_completed() {
+            GeneratorState::Completed
+        } else if !self.is_active() {
+            GeneratorState::Inactive
+        } else {
+            GeneratorState::Running
+        }
+    }
+
+    fn fail(&self, reason: &str) {
+        self.base().fail(reason);
+    }
+
+    /// Sets the verbosity of this generator's logger; its descendants inherit
+    /// it unless they set their own.
+    fn set_log_verbosity(&self, verbosity: u8) {
+        self.logger().set_verbosity(verbosity);
+    }
+
+    fn log_verbosity(&self) -> u8 {
+        self.logger().verbosity()
+    }
+
+    /// Resolves once this generator completes or fails, for code outside the
+    /// kernel that just wants to await a subtree finishing.
+    async fn wait_completed(&self) {
+        self.base().wait_completed().await
+    }
+
+    fn parent_id(&self) -> Option<Uuid> {
+        self.base().parent_id()
+    }
+
+    fn ancestors(&self) -> Vec<Uuid> {
+        self.base().ancestors()
+    }
+
+    async fn detach(&self) -> bool {
+        self.base().detach().await
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        Vec::new()
+    }
+
+    /// Non-blocking variant of `children`; `None` if the list is locked.
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        Some(Vec::new())
+    }
+
+    /// Depth-first, pre-order search of the subtree below this generator.
+    async fn find(&self, predicate: &GeneratorPredicate<'_>) -> Option<Arc<dyn Generator>> {
+        let mut stack = self.children().await;
+        stack.reverse();
+        while let Some(child) = stack.pop() {
+            if predicate(&child) {
+                return Some(child);
+            }
+            let mut grandchildren = child.children().await;
+            grandchildren.reverse();
+            stack.extend(grandchildren);
+        }
+        None
+    }
+
+    async fn find_by_name(&self, name: &str) -> Option<Arc<dyn Generator>> {
+        self.find(&|child| child.name().as_deref() == Some(name)).await
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Option<Arc<dyn Generator>> {
+        self.find(&|child| child.id() == id).await
+    }
+
+    /// The first generator below this one named `name` that is a `T`, as a
+    /// `T`: `node.find_typed::<Timer>("Timeout")`.
+    async fn find_typed<T: Generator + 'static>(&self, name: &str) -> Option<Arc<T>>
+    where
+        Self: Sized,
+    {
+        find_typed(self, name).await
+    }
+
+    /// Every generator below this one that is a `T`, in search order.
+    async fn find_all_typed<T: Generator + 'static>(&self) -> Vec<Arc<T>>
+    where
+        Self: Sized,
+    {
+        find_all_typed(self).await
+    }
+
+    async fn visit(&self, visitor: &mut dyn FlowVisitor)
+    where
+        Self: Sized,
+    {
+        walk(self, visitor).await;
+    }
+
+    async fn render_tree(&self) -> String
+    where
+        Self: Sized,
+    {
+        render_tree(self).await
+    }
+
+    async fn to_mermaid(&self) -> String
+    where
+        Self: Sized,
+    {
+        to_mermaid(self).await
+    }
+
+    /// Adds type-specific details, such as timer or sequence progress.
+    async fn fill_snapshot(&self, _snapshot: &mut NodeSnapshot) {}
+
+    /// Reapplies the details `fill_snapshot` captured, when resuming from a
+    /// checkpoint.
+    async fn restore_snapshot(&self, _snapshot: &NodeSnapshot) {}
+
+    async fn snapshot(&self) -> NodeSnapshot
+    where
+        Self: Sized,
+    {
+        snapshot(self).await
+    }
+
+    /// Stops work this subtree runs outside the flow, such as coroutine
+    /// tasks, so it does not carry on after the flow is abandoned. Used by
+    /// `AsyncKernel::break_flow`.
+    fn cancel(&self) {
+        for child in self.try_children().unwrap_or_default() {
+            child.cancel();
+        }
+    }
+
+    /// Rewinds the generator so it can run again, as the body of a loop or
+    /// retry: containers rewind and reset their children, timers restart and
+    /// triggers re-arm. Returns false, leaving it untouched, for generators
+    /// that cannot run twice, such as coroutines whose future has been
+    /// consumed or channels that have been closed.
+    async fn reset(&self) -> bool {
+        false
+    }
+
+    /// Removes finished generators from the `Node`s in this subtree and
+    /// returns how many were removed. Other containers keep their completed
+    /// children, which they need for ordering, and are only searched.
+    async fn prune(&self) -> usize {
+        let mut removed = 0;
+        for child in self.children().await {
+            removed += child.prune().await;
+        }
+        removed
+    }
+
+    /// Checks the subtree for cycles and for generators reachable twice.
+    async fn validate(&self) -> crate::Result<()>
+    where
+        Self: Sized,
+    {
+        validate(self).await
+    }
+}
+
+/// How new generators get their ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdMode {
+    /// Random v4 UUIDs, unique across processes.
+    #[default]
+    Random,
+    /// Ids from a process-wide counter: far cheaper for flows that create
+    /// thousands of short-lived generators, but only unique within the process.
+    Sequential,
+}
+
+static SEQUENTIAL_IDS: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Sets how generators created from now on get their ids.
+pub fn set_id_mode(mode: IdMode) {
+    SEQUENTIAL_IDS.store(mode == IdMode::Sequential, Ordering::Relaxed);
+}
+
+pub fn id_mode() -> IdMode {
+    if SEQUENTIAL_IDS.load(Ordering::Relaxed) {
+        IdMode::Sequential
+    } else {
+        IdMode::Random
+    }
+}
+
+// Sequential ids keep the top half zero, which no v4 UUID has.
+fn next_id() -> Uuid {
+    match id_mode() {
+        IdMode::Random => Uuid::new_v4(),
+        IdMode::Sequential => Uuid::from_u64_pair(0, NEXT_ID.fetch_add(1, Ordering::Relaxed)),
+    }
+}
+
+/// The short form of `id` used in labels: the first eight hex digits, or
+/// `#n` for a sequential id.
+pub fn short_id(id: Uuid) -> String {
+    match id.as_u64_pair() {
+        (0, n) => format!("#{}", n),
+        _ => id.to_string()[..8].to_string(),
+    }
 }
 
 pub struct GeneratorBase {
     id: Uuid,
-    name: Option<String>,
+    name: RwLock<Option<String>>,
     active: AtomicBool,
     running: AtomicBool,
     completed: AtomicBool,
+    started: AtomicBool,
+    failed: AtomicBool,
+    cancelled: AtomicBool,
+    priority: AtomicI32,
+    on_start: RwLock<Option<Callback>>,
+    on_complete: RwLock<Option<Callback>>,
+    on_fail: RwLock<Option<FailCallback>>,
+    parent: Arc<ParentSlot>,
+    observers: Arc<ObserverList>,
+    finished: tokio::sync::Notify,
     logger: Logger,
 }
 
@@ -31,10 +510,20 @@ impl Clone for GeneratorBase {
     fn clone(&self) -> Self {
         Self {
             id: self.id,
-            name: self.name.clone(),
+            name: RwLock::new(read_hook(&self.name)),
             active: AtomicBool::new(self.active.load(Ordering::Relaxed)),
             running: AtomicBool::new(self.running.load(Ordering::Relaxed)),
             completed: AtomicBool::new(self.completed.load(Ordering::Relaxed)),
+            started: AtomicBool::new(self.started.load(Ordering::Relaxed)),
+            failed: AtomicBool::new(self.failed.load(Ordering::Relaxed)),
+            cancelled: AtomicBool::new(self.cancelled.load(Ordering::Relaxed)),
+            priority: AtomicI32::new(self.priority.load(Ordering::Relaxed)),
+            on_start: RwLock::new(read_hook(&self.on_start)),
+            on_complete: RwLock::new(read_hook(&self.on_complete)),
+            on_fail: RwLock::new(read_hook(&self.on_fail)),
+            parent: Arc::new(RwLock::new(read_hook(&self.parent))),
+            observers: Arc::new(RwLock::new(read_list(&self.observers))),
+            finished: tokio::sync::Notify::new(),
             logger: self.logger.clone(),
         }
     }
@@ -42,19 +531,31 @@ impl Clone for GeneratorBase {
 
 impl GeneratorBase {
     pub fn new() -> Self {
-        Self {
-            id: Uuid::new_v4(),
-            name: None,
+        let base = Self {
+            id: next_id(),
+            name: RwLock::new(None),
             active: AtomicBool::new(true),
             running: AtomicBool::new(true),
             completed: AtomicBool::new(false),
+            started: AtomicBool::new(false),
+            failed: AtomicBool::new(false),
+            cancelled: AtomicBool::new(false),
+            priority: AtomicI32::new(0),
+            on_start: RwLock::new(None),
+            on_complete: RwLock::new(None),
+            on_fail: RwLock::new(None),
+            parent: Arc::new(RwLock::new(None)),
+            observers: Arc::new(RwLock::new(Vec::new())),
+            finished: tokio::sync::Notify::new(),
             logger: Logger::default(),
-        }
+        };
+        base.logger.set_node(base.id);
+        base
     }
 
     pub fn with_name(name: impl Into<String>) -> Self {
-        let mut base = Self::new();
-        base.name = Some(name.into());
+        let base = Self::new();
+        base.set_name(name.into());
         base
     }
 
@@ -62,12 +563,19 @@ impl GeneratorBase {
         self.id
     }
 
-    pub fn name(&self) -> Option<&str> {
-        self.name.as_deref()
+    pub fn name(&self) -> Option<String> {
+        read_hook(&self.name)
+    }
+
+    /// The name, or a short form of the id when unnamed.
+    pub fn label(&self) -> String {
+        self.name().unwrap_or_else(|| short_id(self.id))
     }
 
-    pub fn set_name(&mut self, name: String) {
-        self.name = Some(name);
+    /// Names can change at any time, even after the generator is shared.
+    pub fn set_name(&self, name: String) {
+        self.logger.set_node_name(name.clone());
+        write_hook(&self.name, name);
     }
 
     pub fn is_active(&self) -> bool {
@@ -82,6 +590,28 @@ impl GeneratorBase {
         self.completed.load(Ordering::Relaxed)
     }
 
+    pub fn is_started(&self) -> bool {
+        self.started.load(Ordering::Relaxed)
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Failed through `cancel` rather than by going wrong.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Scheduling priority within a `Node`; higher steps first. Defaults to 0.
+    pub fn priority(&self) -> i32 {
+        self.priority.load(Ordering::Relaxed)
+    }
+
+    pub fn set_priority(&self, priority: i32) {
+        self.priority.store(priority, Ordering::Relaxed);
+    }
+
     pub fn activate(&self) {
         self.active.store(true, Ordering::Relaxed);
     }
@@ -90,12 +620,240 @@ impl GeneratorBase {
         self.active.store(false, Ordering::Relaxed);
     }
 
+    /// Marks the generator as started, firing `on_start` the first time only.
+    pub fn start(&self) {
+        if !self.started.swap(true, Ordering::Relaxed) {
+            if let Some(callback) = read_hook(&self.on_start) {
+                callback();
+            }
+            self.notify(|observer| observer.on_start(self));
+        }
+    }
+
     pub fn complete(&self) {
-        self.completed.store(true, Ordering::Relaxed);
         self.running.store(false, Ordering::Relaxed);
+        if !self.completed.swap(true, Ordering::Relaxed) {
+            if let Some(callback) = read_hook(&self.on_complete) {
+                callback();
+            }
+            self.notify(|observer| observer.on_complete(self));
+            self.finished.notify_waiters();
+        }
+    }
+
+    /// Returns the generator to its fresh, running state so it can run again.
+    /// Lifecycle callbacks and observers fire again on the next round.
+    pub fn reset(&self) {
+        self.completed.store(false, Ordering::Relaxed);
+        self.failed.store(false, Ordering::Relaxed);
+        self.cancelled.store(false, Ordering::Relaxed);
+        self.started.store(false, Ordering::Relaxed);
+        self.running.store(true, Ordering::Relaxed);
+    }
+
+    /// Puts the generator straight into `state` without firing callbacks or
+    /// observers, for resuming from a checkpoint.
+    pub fn restore_state(&self, state: GeneratorState) {
+        match state {
+            GeneratorState::Running => {}
+            GeneratorState::Inactive => self.deactivate(),
+            GeneratorState::Completed | GeneratorState::Failed => {
+                self.started.store(true, Ordering::Relaxed);
+                self.running.store(false, Ordering::Relaxed);
+                self.failed.store(state == GeneratorState::Failed, Ordering::Relaxed);
+                self.completed.store(true, Ordering::Relaxed);
+                self.finished.notify_waiters();
+            }
+        }
+    }
+
+    /// Completes the generator as failed. `on_fail` fires instead of `on_complete`.
+    pub fn fail(&self, reason: &str) {
+        self.finish_failed(reason, false);
+    }
+
+    /// Fails the generator with reason "cancelled" unless it has already
+    /// finished. Cancellation is control flow, not an error, so the kernel's
+    /// error log and handler skip it.
+    pub fn cancel(&self) {
+        self.finish_failed("cancelled", true);
+    }
+
+    fn finish_failed(&self, reason: &str, cancelled: bool) {
+        self.running.store(false, Ordering::Relaxed);
+        if !self.completed.swap(true, Ordering::Relaxed) {
+            self.failed.store(true, Ordering::Relaxed);
+            self.cancelled.store(cancelled, Ordering::Relaxed);
+            if let Some(callback) = read_hook(&self.on_fail) {
+                callback(reason);
+            }
+            self.notify(|observer| observer.on_fail(self, reason));
+            self.finished.notify_waiters();
+        }
+    }
+
+    // Containers call this when a child's step returns an error.
+    pub(crate) fn step_errored(&self, error: &str) {
+        self.notify(|observer| observer.on_step_error(self, error));
+    }
+
+    pub async fn wait_completed(&self) {
+        loop {
+            let notified = self.finished.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.is_completed() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    pub fn set_on_start<F>(&self, callback: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        write_hook(&self.on_start, Arc::new(callback));
+    }
+
+    pub fn set_on_complete<F>(&self, callback: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        write_hook(&self.on_complete, Arc::new(callback));
+    }
+
+    pub fn set_on_fail<F>(&self, callback: F)
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        write_hook(&self.on_fail, Arc::new(callback));
+    }
+
+    /// Observes every generator below this one; see `LifecycleObserver`.
+    pub fn add_observer(&self, observer: Arc<dyn LifecycleObserver>) {
+        self.observers.write().unwrap_or_else(|e| e.into_inner()).push(observer);
+    }
+
+    pub fn remove_observer(&self, observer: &Arc<dyn LifecycleObserver>) -> bool {
+        let mut observers = self.observers.write().unwrap_or_else(|e| e.into_inner());
+        let before = observers.len();
+        observers.retain(|o| !Arc::ptr_eq(o, observer));
+        observers.len() != before
+    }
+
+    // Observers registered on the live parent chain, nearest ancestor first.
+    fn observers_above(&self) -> Vec<Arc<dyn LifecycleObserver>> {
+        let mut found = Vec::new();
+        let mut link = read_hook(&self.parent);
+        while let Some(current) = link {
+            if !current.children.parent_alive() {
+                break;
+            }
+            if let Some(observers) = current.observers.upgrade() {
+                found.extend(read_list(&observers));
+            }
+            link = current.ancestry.upgrade().and_then(|slot| read_hook(&slot));
+        }
+        found
+    }
+
+    pub(crate) fn notify(&self, event: impl Fn(&dyn LifecycleObserver)) {
+        for observer in self.observers_above() {
+            event(observer.as_ref());
+        }
+    }
+
+    /// The first answer any observer above this generator gives to `query`.
+    pub(crate) fn ask_observers<T>(&self, query: impl Fn(&dyn LifecycleObserver) -> Option<T>) -> Option<T> {
+        self.observers_above().iter().find_map(|observer| query(observer.as_ref()))
+    }
+
+    pub fn set_parent(&self, link: Option<ParentLink>) {
+        let added = link.is_some();
+        self.logger.inherit_from(link.as_ref().map(|link| &link.logger));
+        *self.parent.write().unwrap_or_else(|e| e.into_inner()) = link;
+        if added {
+            self.notify(|observer| observer.on_added(self));
+        }
+    }
+
+    pub fn parent_id(&self) -> Option<Uuid> {
+        read_hook(&self.parent)
+            .filter(|link| link.children.parent_alive())
+            .map(|link| link.id)
+    }
+
+    /// Ids of the containers above this generator, nearest first.
+    pub fn ancestors(&self) -> Vec<Uuid> {
+        let mut ancestors = Vec::new();
+        let mut link = read_hook(&self.parent);
+        while let Some(current) = link {
+            if !current.children.parent_alive() {
+                break;
+            }
+            ancestors.push(current.id);
+            link = current.ancestry.upgrade().and_then(|slot| read_hook(&slot));
+        }
+        ancestors
+    }
+
+    /// Removes this generator from its parent's children. Must not be awaited
+    /// from within the parent's own `step`, which holds the children lock.
+    pub async fn detach(&self) -> bool {
+        let Some(link) = read_hook(&self.parent) else {
+            return false;
+        };
+        self.set_parent(None);
+
+        let children = match link.children {
+            Siblings::List(children) => children,
+            Siblings::Slab(children, handle) => {
+                let Some(children) = children.upgrade() else {
+                    return false;
+                };
+                return children.write().await.remove(handle).is_some();
+            }
+        };
+        let Some(children) = children.upgrade() else {
+            return false;
+        };
+        let mut children = children.write().await;
+        let Some(pos) = children.iter().position(|c| c.id() == self.id) else {
+            return false;
+        };
+        children.remove(pos);
+
+        if let Some(cursor) = link.cursor.and_then(|cursor| cursor.upgrade()) {
+            let mut cursor = cursor.write().await;
+            if pos < *cursor {
+                *cursor -= 1;
+            }
+        }
+        true
     }
 
     pub fn logger(&self) -> &Logger {
         &self.logger
     }
-}
\ No newline at end of file
+}
+
+impl Default for GeneratorBase {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Hooks are cloned out of the lock before being invoked so a callback may
+// safely replace hooks on the generator that is calling it.
+fn read_hook<T: Clone>(hook: &RwLock<Option<T>>) -> Option<T> {
+    hook.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn read_list<T: Clone>(list: &RwLock<Vec<T>>) -> Vec<T> {
+    list.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn write_hook<T>(hook: &RwLock<Option<T>>, callback: T) {
+    *hook.write().unwrap_or_else(|e| e.into_inner()) = Some(callback);
+}
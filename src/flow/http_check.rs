@@ -0,0 +1,244 @@
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{Generator, GeneratorBase};
+use crate::{Logger, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+    /// No check has finished yet.
+    Unknown,
+    Healthy,
+    Unhealthy,
+}
+
+/// When an `HttpCheck` stops polling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckPolicy {
+    /// Polls for as long as it runs, only reporting health.
+    Monitor,
+    /// Completes on the first healthy response, for waiting on a service.
+    UntilHealthy,
+    /// Fails after this many unhealthy responses in a row.
+    FailAfter(u32),
+}
+
+#[derive(Default)]
+struct Probe {
+    health: AtomicU8,
+    failures: AtomicU32,
+    checks: AtomicU32,
+    in_flight: AtomicBool,
+}
+
+impl Probe {
+    fn health(&self) -> Health {
+        match self.health.load(Ordering::Relaxed) {
+            1 => Health::Healthy,
+            2 => Health::Unhealthy,
+            _ => Health::Unknown,
+        }
+    }
+
+    fn record(&self, healthy: bool) {
+        if healthy {
+            self.failures.store(0, Ordering::Relaxed);
+            self.health.store(1, Ordering::Relaxed);
+        } else {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+            self.health.store(2, Ordering::Relaxed);
+        }
+        self.checks.fetch_add(1, Ordering::Relaxed);
+        self.in_flight.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Polls `url` every `interval`, counting a 2xx response as healthy and any
+/// other status, timeout or connection error as unhealthy. Requests run on
+/// spawned tasks, so a slow endpoint never holds up the kernel. `condition`
+/// exposes the health to triggers elsewhere in the flow; `policy` decides
+/// whether the check completes or fails. Behind the `http` feature.
+pub struct HttpCheck {
+    base: GeneratorBase,
+    url: String,
+    interval: Duration,
+    timeout: Duration,
+    policy: CheckPolicy,
+    client: reqwest::Client,
+    probe: Arc<Probe>,
+    last_check: Mutex<Option<Instant>>,
+}
+
+impl HttpCheck {
+    pub fn new(url: impl Into<String>, interval: Duration) -> Self {
+        Self::build(GeneratorBase::new(), url.into(), interval)
+    }
+
+    pub fn with_name(name: impl Into<String>, url: impl Into<String>, interval: Duration) -> Self {
+        Self::build(GeneratorBase::with_name(name), url.into(), interval)
+    }
+
+    fn build(base: GeneratorBase, url: String, interval: Duration) -> Self {
+        Self {
+            base,
+            url,
+            interval,
+            timeout: interval,
+            policy: CheckPolicy::Monitor,
+            client: reqwest::Client::new(),
+            probe: Arc::new(Probe::default()),
+            last_check: Mutex::new(None),
+        }
+    }
+
+    /// How long a request may take before it counts as unhealthy. Defaults
+    /// to the polling interval.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn policy(mut self, policy: CheckPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn health(&self) -> Health {
+        self.probe.health()
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.health() == Health::Healthy
+    }
+
+    /// Unhealthy responses since the last healthy one.
+    pub fn consecutive_failures(&self) -> u32 {
+        self.probe.failures.load(Ordering::Relaxed)
+    }
+
+    /// Finished checks, healthy or not.
+    pub fn checks(&self) -> u32 {
+        self.probe.checks.load(Ordering::Relaxed)
+    }
+
+    /// A condition that holds while the last check was healthy, for
+    /// `Trigger::new` and friends.
+    pub fn condition(&self) -> impl Fn() -> bool + Send + Sync + 'static {
+        let probe = self.probe.clone();
+        move || probe.health() == Health::Healthy
+    }
+
+    // Starts a request if the interval has passed and none is in flight.
+    fn poll(&self) {
+        let now = Instant::now();
+        let mut last_check = self.last_check.lock().unwrap_or_else(|e| e.into_inner());
+        if last_check.is_some_and(|at| now.duration_since(at) < self.interval) {
+            return;
+        }
+        if self.probe.in_flight.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        *last_check = Some(now);
+
+        let request = self.client.get(&self.url).timeout(self.timeout).send();
+        let (probe, logger, url) = (self.probe.clone(), self.logger().clone(), self.url.clone());
+        tokio::spawn(async move {
+            let healthy = match request.await {
+                Ok(response) => {
+                    logger.verbose_with(4, || format!("{} answered {}", url, response.status()));
+                    response.status().is_success()
+                }
+                Err(e) => {
+                    logger.verbose_with(2, || format!("{} unreachable: {}", url, e));
+                    false
+                }
+            };
+            probe.record(healthy);
+        });
+    }
+}
+
+#[async_trait]
+impl Generator for HttpCheck {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        match self.policy {
+            CheckPolicy::UntilHealthy if self.is_healthy() => {
+                self.complete();
+                return Ok(());
+            }
+            CheckPolicy::FailAfter(limit) if self.consecutive_failures() >= limit => {
+                self.base.fail(&format!("{} unhealthy for {} checks", self.url, limit));
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        self.poll();
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        self.base.reset();
+        *self.last_check.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.probe.failures.store(0, Ordering::Relaxed);
+        self.probe.health.store(0, Ordering::Relaxed);
+        true
+    }
+}
@@ -0,0 +1,201 @@
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+use crate::flow::{CallbackHandle, CallbackList, FlowCallback, Generator, GeneratorBase};
+use crate::{Logger, Result};
+
+/// Reads a number every step and fires its callbacks when it crosses
+/// `on_threshold`, then stays quiet until it has come back past
+/// `off_threshold`, so a reading hovering around one threshold does not
+/// fire over and over. An `off_threshold` above `on_threshold` watches for
+/// falling readings instead, such as free disk space. Never completes on
+/// its own.
+pub struct HysteresisTrigger {
+    base: GeneratorBase,
+    read: Box<dyn Fn() -> f64 + Send + Sync>,
+    on_threshold: f64,
+    off_threshold: f64,
+    triggered_callbacks: RwLock<CallbackList>,
+    armed: AtomicBool,
+    fires: AtomicU32,
+    last_value: AtomicU64,
+}
+
+impl HysteresisTrigger {
+    pub fn new<F>(read: F, on_threshold: f64, off_threshold: f64) -> Self
+    where
+        F: Fn() -> f64 + Send + Sync + 'static,
+    {
+        Self::build(GeneratorBase::new(), Box::new(read), on_threshold, off_threshold)
+    }
+
+    pub fn with_name<F>(name: impl Into<String>, read: F, on_threshold: f64, off_threshold: f64) -> Self
+    where
+        F: Fn() -> f64 + Send + Sync + 'static,
+    {
+        Self::build(GeneratorBase::with_name(name), Box::new(read), on_threshold, off_threshold)
+    }
+
+    fn build(
+        base: GeneratorBase,
+        read: Box<dyn Fn() -> f64 + Send + Sync>,
+        on_threshold: f64,
+        off_threshold: f64,
+    ) -> Self {
+        Self {
+            base,
+            read,
+            on_threshold,
+            off_threshold,
+            triggered_callbacks: RwLock::new(CallbackList::new()),
+            armed: AtomicBool::new(true),
+            fires: AtomicU32::new(0),
+            last_value: AtomicU64::new(f64::NAN.to_bits()),
+        }
+    }
+
+    pub async fn add_triggered_callback<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.triggered_callbacks.write().await.add(FlowCallback::from_sync(callback))
+    }
+
+    pub async fn add_triggered_callback_async<F, Fut>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.triggered_callbacks.write().await.add(FlowCallback::from_async(callback))
+    }
+
+    pub async fn remove_triggered_callback(&self, handle: CallbackHandle) -> bool {
+        self.triggered_callbacks.write().await.remove(handle)
+    }
+
+    pub fn on_threshold(&self) -> f64 {
+        self.on_threshold
+    }
+
+    pub fn off_threshold(&self) -> f64 {
+        self.off_threshold
+    }
+
+    /// Whether the next crossing of `on_threshold` fires.
+    pub fn is_armed(&self) -> bool {
+        self.armed.load(Ordering::Relaxed)
+    }
+
+    pub fn fires(&self) -> u32 {
+        self.fires.load(Ordering::Relaxed)
+    }
+
+    /// The reading from the last step; NaN before the first.
+    pub fn last_value(&self) -> f64 {
+        f64::from_bits(self.last_value.load(Ordering::Relaxed))
+    }
+
+    fn rising(&self) -> bool {
+        self.off_threshold <= self.on_threshold
+    }
+
+    fn crossed_on(&self, value: f64) -> bool {
+        if self.rising() {
+            value >= self.on_threshold
+        } else {
+            value <= self.on_threshold
+        }
+    }
+
+    fn crossed_off(&self, value: f64) -> bool {
+        if self.rising() {
+            value < self.off_threshold
+        } else {
+            value > self.off_threshold
+        }
+    }
+}
+
+#[async_trait]
+impl Generator for HysteresisTrigger {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let value = (self.read)();
+        self.last_value.store(value.to_bits(), Ordering::Relaxed);
+        if !self.is_armed() {
+            if self.crossed_off(value) {
+                self.armed.store(true, Ordering::Relaxed);
+            }
+            return Ok(());
+        }
+
+        if self.crossed_on(value) {
+            self.armed.store(false, Ordering::Relaxed);
+            self.fires.fetch_add(1, Ordering::Relaxed);
+            self.logger().verbose(2, format!("Reading {} crossed {}", value, self.on_threshold));
+            self.triggered_callbacks.read().await.invoke_all();
+            self.base.notify(|observer| observer.on_triggered(&self.base));
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        self.armed.store(true, Ordering::Relaxed);
+        self.fires.store(0, Ordering::Relaxed);
+        self.last_value.store(f64::NAN.to_bits(), Ordering::Relaxed);
+        self.base.reset();
+        true
+    }
+}
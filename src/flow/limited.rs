@@ -0,0 +1,195 @@
+use async_trait::async_trait;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, RwLock, Semaphore};
+use uuid::Uuid;
+use crate::flow::{
+    accept_child, check_no_cycle, reset_subtree, set_children_active, Generator, GeneratorBase, ParentLink,
+};
+use crate::{Logger, Result};
+
+/// Runs its children in parallel like a `Barrier`, but at most
+/// `max_concurrent` at a time: a child holds a permit from its first step
+/// until it completes, and the rest queue in insertion order. Only lazily
+/// started children, such as `AsyncCoroutine::lazy`, are actually held back;
+/// eagerly spawned work is already running.
+pub struct Limited {
+    base: GeneratorBase,
+    children: Arc<RwLock<Vec<Arc<dyn Generator>>>>,
+    max_concurrent: usize,
+    slots: Arc<Semaphore>,
+    permits: Mutex<HashMap<Uuid, OwnedSemaphorePermit>>,
+}
+
+impl Limited {
+    pub fn new(max_concurrent: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            base: GeneratorBase::new(),
+            children: Arc::new(RwLock::new(Vec::new())),
+            max_concurrent,
+            slots: Arc::new(Semaphore::new(max_concurrent)),
+            permits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_name(name: impl Into<String>, max_concurrent: usize) -> Self {
+        let limited = Self::new(max_concurrent);
+        limited.base.set_name(name.into());
+        limited
+    }
+
+    fn parent_link(&self) -> ParentLink {
+        ParentLink::new(&self.base, &self.children)
+    }
+
+    pub async fn add_child(&self, child: Arc<dyn Generator>) {
+        if accept_child(&self.base, &child) {
+            self.push_child(child).await;
+        }
+    }
+
+    /// Like `add_child`, but returns the error instead of logging it when
+    /// `child` is this container or one of its ancestors.
+    pub async fn try_add_child(&self, child: Arc<dyn Generator>) -> Result<()> {
+        check_no_cycle(&self.base, child.as_ref())?;
+        self.push_child(child).await;
+        Ok(())
+    }
+
+    async fn push_child(&self, child: Arc<dyn Generator>) {
+        child.base().set_parent(Some(self.parent_link()));
+        let mut children = self.children.write().await;
+        children.push(child);
+    }
+
+    pub async fn add_children<I>(&self, children: I)
+    where
+        I: IntoIterator<Item = Arc<dyn Generator>>,
+    {
+        let link = self.parent_link();
+        let mut existing = self.children.write().await;
+        existing.extend(children.into_iter().filter(|child| accept_child(&self.base, child)).inspect(|child| {
+            child.base().set_parent(Some(link.clone()));
+        }));
+    }
+
+    pub async fn child_count(&self) -> usize {
+        let children = self.children.read().await;
+        children.len()
+    }
+
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    /// Children currently holding a permit.
+    pub async fn running_count(&self) -> usize {
+        self.permits.lock().await.len()
+    }
+}
+
+#[async_trait]
+impl Generator for Limited {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let children = self.children.read().await;
+        let mut permits = self.permits.lock().await;
+
+        // Finished children hand their permit to the next in the queue.
+        permits.retain(|id, _| children.iter().any(|child| child.id() == *id && !child.is_completed()));
+
+        for child in children.iter() {
+            if child.is_completed() || !child.is_active() || !child.is_running() {
+                continue;
+            }
+            if let Entry::Vacant(slot) = permits.entry(child.id()) {
+                match self.slots.clone().try_acquire_owned() {
+                    Ok(permit) => {
+                        slot.insert(permit);
+                    }
+                    Err(_) => break,
+                }
+            }
+            if let Err(e) = child.traced_step().await {
+                self.logger().error(format!("Child step failed in limited: {}", e));
+                child.base().step_errored(&e.to_string());
+            }
+        }
+
+        permits.retain(|id, _| children.iter().any(|child| child.id() == *id && !child.is_completed()));
+
+        if children.iter().all(|child| child.is_completed()) {
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        self.permits.lock().await.clear();
+        reset_subtree(self).await;
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        let children = self.children.read().await;
+        children.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.children.try_read().ok().map(|children| children.clone())
+    }
+}
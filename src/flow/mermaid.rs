@@ -0,0 +1,92 @@
+use std::fmt::Write;
+use crate::flow::{label, walk, FlowVisitor, Generator, GeneratorState, VisitControl, VisitInfo};
+
+struct Entry {
+    id: String,
+    title: String,
+    kind: &'static str,
+    state: GeneratorState,
+    parent: Option<usize>,
+}
+
+struct MermaidCollector {
+    entries: Vec<Entry>,
+    open: Vec<usize>,
+}
+
+impl FlowVisitor for MermaidCollector {
+    fn enter(&mut self, generator: &dyn Generator, _info: &VisitInfo) -> VisitControl {
+        self.entries.push(Entry {
+            id: format!("n{}", generator.id().simple()),
+            title: label(generator),
+            kind: generator.type_name(),
+            state: generator.state(),
+            parent: self.open.last().copied(),
+        });
+        self.open.push(self.entries.len() - 1);
+        VisitControl::Continue
+    }
+
+    fn leave(&mut self, _generator: &dyn Generator, _info: &VisitInfo) {
+        self.open.pop();
+    }
+}
+
+/// Renders `root` as a Mermaid flowchart. Composites become subgraphs
+/// annotated with how many of their children have completed, and the
+/// children of a `Sequence` are chained in execution order.
+pub async fn to_mermaid(root: &dyn Generator) -> String {
+    let mut collector = MermaidCollector { entries: Vec::new(), open: Vec::new() };
+    walk(root, &mut collector).await;
+    let entries = collector.entries;
+
+    let mut out = String::from("flowchart TD\n");
+    if !entries.is_empty() {
+        write_entry(&entries, 0, 1, &mut out);
+    }
+
+    for state in [GeneratorState::Running, GeneratorState::Completed, GeneratorState::Failed, GeneratorState::Inactive] {
+        let ids: Vec<_> = entries.iter().filter(|e| e.state == state).map(|e| e.id.as_str()).collect();
+        if !ids.is_empty() {
+            let _ = writeln!(out, "    class {} {}", ids.join(","), state);
+        }
+    }
+    out.push_str("    classDef running fill:#fff3e0\n");
+    out.push_str("    classDef completed fill:#e8f5e8\n");
+    out.push_str("    classDef failed fill:#ffcdd2\n");
+    out.push_str("    classDef inactive fill:#f5f5f5\n");
+    out
+}
+
+fn write_entry(entries: &[Entry], index: usize, depth: usize, out: &mut String) {
+    let entry = &entries[index];
+    let indent = "    ".repeat(depth);
+    let children: Vec<usize> = (index + 1..entries.len())
+        .filter(|&i| entries[i].parent == Some(index))
+        .collect();
+
+    if children.is_empty() {
+        let _ = writeln!(out, "{}{}[\"{} [{}] {}\"]", indent, entry.id, escape(&entry.title), entry.kind, entry.state);
+        return;
+    }
+
+    let done = children.iter().filter(|&&i| entries[i].state == GeneratorState::Completed).count();
+    let _ = writeln!(
+        out,
+        "{}subgraph {}[\"{} [{}] {} {}/{}\"]",
+        indent, entry.id, escape(&entry.title), entry.kind, entry.state, done, children.len()
+    );
+    for &child in &children {
+        write_entry(entries, child, depth + 1, out);
+    }
+    if entry.kind == "Sequence" {
+        for pair in children.windows(2) {
+            let _ = writeln!(out, "{}    {} --> {}", indent, entries[pair[0]].id, entries[pair[1]].id);
+        }
+    }
+    let _ = writeln!(out, "{}end", indent);
+}
+
+fn escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
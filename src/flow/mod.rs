@@ -1,17 +1,99 @@
 pub mod generator;
+pub mod callback;
 pub mod coroutine;
 pub mod sequence;
 pub mod barrier;
+pub mod combine;
+pub mod dag;
+pub mod limited;
+pub mod worker_pool;
+pub mod pipeline;
+pub mod fan_out;
+pub mod aggregator;
+pub mod batcher;
+pub mod window;
+pub mod watchdog;
+pub mod fallback;
+pub mod supervisor;
+pub mod with_resource;
+pub mod once;
+pub mod cached;
+pub mod pause_gate;
+pub mod active_window;
 pub mod trigger;
+pub mod sampled_trigger;
+pub mod hysteresis_trigger;
+pub mod connect_trigger;
+pub mod retry_budget;
+pub mod backoff;
 pub mod timer;
 pub mod future;
+pub mod stream;
+pub mod channel;
+pub mod stream_node;
+pub mod bridge;
 pub mod node;
+pub mod slab;
+pub mod visitor;
+pub mod render;
+pub mod mermaid;
+pub mod snapshot;
+pub mod validate;
+pub mod builder;
+pub mod events;
+#[cfg(feature = "otel")]
+pub mod otel;
+#[cfg(feature = "remote")]
+pub mod remote;
+#[cfg(feature = "http")]
+pub mod http_check;
 
 pub use generator::*;
+pub use callback::*;
 pub use coroutine::*;
 pub use sequence::*;
 pub use barrier::*;
+pub use combine::FirstOf;
+pub use dag::*;
+pub use limited::*;
+pub use worker_pool::*;
+pub use pipeline::*;
+pub use fan_out::*;
+pub use aggregator::*;
+pub use batcher::*;
+pub use window::*;
+pub use watchdog::*;
+pub use fallback::*;
+pub use supervisor::*;
+pub use with_resource::*;
+pub use once::*;
+pub use cached::*;
+pub use pause_gate::*;
+pub use active_window::*;
 pub use trigger::*;
+pub use sampled_trigger::*;
+pub use hysteresis_trigger::*;
+pub use connect_trigger::*;
+pub use retry_budget::*;
+pub use backoff::*;
 pub use timer::*;
 pub use future::*;
-pub use node::*;
\ No newline at end of file
+pub use stream::*;
+pub use channel::*;
+pub use stream_node::*;
+pub use bridge::*;
+pub use node::*;
+pub use slab::*;
+pub use visitor::*;
+pub use render::*;
+pub use mermaid::*;
+pub use snapshot::*;
+pub use validate::*;
+pub use builder::*;
+pub use events::*;
+#[cfg(feature = "otel")]
+pub use otel::*;
+#[cfg(feature = "remote")]
+pub use remote::*;
+#[cfg(feature = "http")]
+pub use http_check::*;
\ No newline at end of file
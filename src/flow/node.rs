@@ -1,52 +1,265 @@
 use async_trait::async_trait;
-use std::sync::Arc;
+use std::fmt;
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use uuid::Uuid;
-use crate::flow::{Generator, GeneratorBase};
-use crate::{Logger, Result};
+use crate::flow::{
+    accept_child, check_no_cycle, render_tree_now, reset_subtree, set_children_active, ChildHandle, ChildSlab,
+    Generator, GeneratorBase, ParentLink, SlabList,
+};
+use crate::{fill_step_order, Logger, Result};
+
+/// How many children a `Node` steps per tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StepMode {
+    /// Every child, every tick.
+    #[default]
+    All,
+    /// At most this many runnable children per tick, taking turns in child
+    /// order. Cuts per-tick cost on very wide trees at the price of latency;
+    /// priorities and the frame budget do not apply.
+    RoundRobin(usize),
+}
 
 pub struct Node {
     base: GeneratorBase,
-    children: Arc<RwLock<Vec<Arc<dyn Generator>>>>,
+    children: Arc<SlabList>,
+    frame_budget: Mutex<Option<Duration>>,
+    step_mode: Mutex<StepMode>,
+    next_turn: AtomicUsize,
+    prune_completed: AtomicBool,
+    // Reused by every step for the order children are stepped in.
+    order: Mutex<Vec<usize>>,
 }
 
 impl Node {
     pub fn new() -> Self {
         Self {
             base: GeneratorBase::new(),
-            children: Arc::new(RwLock::new(Vec::new())),
+            children: Arc::new(RwLock::new(ChildSlab::new())),
+            frame_budget: Mutex::new(None),
+            step_mode: Mutex::new(StepMode::All),
+            next_turn: AtomicUsize::new(0),
+            prune_completed: AtomicBool::new(false),
+            order: Mutex::new(Vec::new()),
         }
     }
 
     pub fn with_name(name: impl Into<String>) -> Self {
         Self {
             base: GeneratorBase::with_name(name),
-            children: Arc::new(RwLock::new(Vec::new())),
+            children: Arc::new(RwLock::new(ChildSlab::new())),
+            frame_budget: Mutex::new(None),
+            step_mode: Mutex::new(StepMode::All),
+            next_turn: AtomicUsize::new(0),
+            prune_completed: AtomicBool::new(false),
+            order: Mutex::new(Vec::new()),
         }
     }
 
-    pub async fn add_child(&self, child: Arc<dyn Generator>) {
-        let mut children = self.children.write().await;
-        children.push(child);
+    fn parent_link(&self, handle: ChildHandle) -> ParentLink {
+        ParentLink::in_slab(&self.base, &self.children, handle)
     }
 
-    pub async fn remove_child(&self, id: Uuid) -> bool {
+    /// Adds `child`, returning a handle for removing it again in constant
+    /// time, or `None` if it was rejected for making a cycle.
+    pub async fn add_child(&self, child: Arc<dyn Generator>) -> Option<ChildHandle> {
+        if accept_child(&self.base, &child) {
+            return Some(self.push_child(child).await);
+        }
+        None
+    }
+
+    /// Like `add_child`, but returns the error instead of logging it when
+    /// `child` is this container or one of its ancestors.
+    pub async fn try_add_child(&self, child: Arc<dyn Generator>) -> Result<ChildHandle> {
+        check_no_cycle(&self.base, child.as_ref())?;
+        Ok(self.push_child(child).await)
+    }
+
+    async fn push_child(&self, child: Arc<dyn Generator>) -> ChildHandle {
+        let handle = self.children.write().await.push(child.clone());
+        child.base().set_parent(Some(self.parent_link(handle)));
+        handle
+    }
+
+    pub async fn add_children<I>(&self, children: I)
+    where
+        I: IntoIterator<Item = Arc<dyn Generator>>,
+    {
+        let mut existing = self.children.write().await;
+        for child in children.into_iter().filter(|child| accept_child(&self.base, child)) {
+            let handle = existing.push(child.clone());
+            child.base().set_parent(Some(self.parent_link(handle)));
+        }
+    }
+
+    /// Adds `child` stepping ahead of any lower-priority siblings.
+    pub async fn add_child_with_priority(&self, child: Arc<dyn Generator>, priority: i32) -> Option<ChildHandle> {
+        child.base().set_priority(priority);
+        self.add_child(child).await
+    }
+
+    /// Once a tick has spent `budget` stepping children, the rest are deferred
+    /// to the next tick, except those sharing the highest priority present,
+    /// which always step. `None`, the default, steps every child every tick.
+    pub fn set_frame_budget(&self, budget: Option<Duration>) {
+        *self.frame_budget.lock().unwrap_or_else(|e| e.into_inner()) = budget;
+    }
+
+    pub fn frame_budget(&self) -> Option<Duration> {
+        *self.frame_budget.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set_step_mode(&self, mode: StepMode) {
+        *self.step_mode.lock().unwrap_or_else(|e| e.into_inner()) = mode;
+    }
+
+    pub fn step_mode(&self) -> StepMode {
+        *self.step_mode.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Inserts `child` at `index`; indices past the end append.
+    pub async fn insert_at(&self, index: usize, child: Arc<dyn Generator>) -> Option<ChildHandle> {
+        if !accept_child(&self.base, &child) {
+            return None;
+        }
+        let handle = self.children.write().await.insert(index, child.clone());
+        child.base().set_parent(Some(self.parent_link(handle)));
+        Some(handle)
+    }
+
+    pub async fn insert_before(&self, id: Uuid, child: Arc<dyn Generator>) -> bool {
+        if !accept_child(&self.base, &child) {
+            return false;
+        }
         let mut children = self.children.write().await;
-        if let Some(pos) = children.iter().position(|c| c.id() == id) {
-            children.remove(pos);
+        let position = children.iter().position(|c| c.id() == id);
+        if let Some(pos) = position {
+            let handle = children.insert(pos, child.clone());
+            child.base().set_parent(Some(self.parent_link(handle)));
             return true;
         }
         false
     }
 
+    pub async fn replace_child(&self, id: Uuid, child: Arc<dyn Generator>) -> bool {
+        if !accept_child(&self.base, &child) {
+            return false;
+        }
+        let mut children = self.children.write().await;
+        let Some(handle) = children.handle_of(id) else {
+            return false;
+        };
+        if let Some(old) = children.replace(handle, child.clone()) {
+            old.base().set_parent(None);
+        }
+        child.base().set_parent(Some(self.parent_link(handle)));
+        true
+    }
+
+    /// Removes the child with `id`, searching for it; prefer `remove_handle`
+    /// when the handle from `add_child` is at hand.
+    pub async fn remove_child(&self, id: Uuid) -> bool {
+        let mut children = self.children.write().await;
+        match children.handle_of(id).and_then(|handle| children.remove(handle)) {
+            Some(child) => {
+                child.base().set_parent(None);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the child `handle` refers to in constant time. False if it has
+    /// already gone.
+    pub async fn remove_handle(&self, handle: ChildHandle) -> bool {
+        let removed = self.children.write().await.remove(handle);
+        match removed {
+            Some(child) => {
+                child.base().set_parent(None);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The child `handle` refers to, if it is still here.
+    pub async fn child(&self, handle: ChildHandle) -> Option<Arc<dyn Generator>> {
+        self.children.read().await.get(handle).cloned()
+    }
+
     pub async fn child_count(&self) -> usize {
         let children = self.children.read().await;
         children.len()
     }
 
     pub async fn clear_completed(&self) {
+        self.remove_completed().await;
+    }
+
+    /// Opts this node into dropping finished children after each of its own
+    /// steps, so long-lived nested nodes do not accumulate spent timers.
+    /// The kernel's root always does this.
+    pub fn set_prune_completed(&self, prune: bool) {
+        self.prune_completed.store(prune, Ordering::Relaxed);
+    }
+
+    pub fn prunes_completed(&self) -> bool {
+        self.prune_completed.load(Ordering::Relaxed)
+    }
+
+    /// Names of the completed children, read in place under the lock.
+    pub(crate) async fn completed_names(&self) -> Vec<String> {
+        let children = self.children.read().await;
+        children
+            .iter()
+            .filter(|child| child.is_completed())
+            .filter_map(|child| child.name())
+            .collect()
+    }
+
+    async fn remove_completed(&self) -> usize {
         let mut children = self.children.write().await;
-        children.retain(|child| !child.is_completed());
+        let before = children.len();
+        if children.iter().any(|child| child.is_completed()) {
+            children.retain(|child| {
+                let keep = !child.is_completed();
+                if !keep {
+                    child.base().set_parent(None);
+                }
+                keep
+            });
+        }
+        before - children.len()
+    }
+}
+
+impl Node {
+    async fn step_round_robin(&self, children: &ChildSlab, per_tick: usize) {
+        let len = children.slot_count();
+        let start = self.next_turn.load(Ordering::Relaxed) % len;
+        let mut stepped = 0;
+        for offset in 0..len {
+            if stepped == per_tick {
+                break;
+            }
+            let index = (start + offset) % len;
+            let Some(child) = children.slot(index) else {
+                continue;
+            };
+            if child.is_active() && child.is_running() && !child.is_completed() {
+                if let Err(e) = child.traced_step().await {
+                    self.logger().error(format!("Child step failed: {}", e));
+                    child.base().step_errored(&e.to_string());
+                }
+                stepped += 1;
+                self.next_turn.store(index + 1, Ordering::Relaxed);
+            }
+        }
     }
 }
 
@@ -56,17 +269,23 @@ impl Default for Node {
     }
 }
 
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&render_tree_now(self))
+    }
+}
+
 #[async_trait]
 impl Generator for Node {
     fn id(&self) -> Uuid {
         self.base.id()
     }
 
-    fn name(&self) -> Option<&str> {
+    fn name(&self) -> Option<String> {
         self.base.name()
     }
 
-    fn set_name(&mut self, name: String) {
+    fn set_name(&self, name: String) {
         self.base.set_name(name);
     }
 
@@ -84,10 +303,12 @@ impl Generator for Node {
 
     fn activate(&self) {
         self.base.activate();
+        set_children_active(self, true);
     }
 
     fn deactivate(&self) {
         self.base.deactivate();
+        set_children_active(self, false);
     }
 
     fn complete(&self) {
@@ -99,22 +320,60 @@ impl Generator for Node {
             return Ok(());
         }
 
+        self.base.start();
+
         let children = self.children.read().await;
         if children.is_empty() {
             return Ok(());
         }
 
-        if children.len() > 0 {
-            self.logger().verbose(4, format!("Stepping node with {} children", children.len()));
+        self.logger().verbose_with(4, || format!("Stepping node with {} children", children.len()));
+
+        if let StepMode::RoundRobin(per_tick) = self.step_mode() {
+            self.step_round_robin(&children, per_tick.max(1)).await;
+            drop(children);
+            if self.prunes_completed() {
+                self.remove_completed().await;
+            }
+            return Ok(());
         }
 
-        for child in children.iter() {
+        // Orders slot indices, holes included; holes sort last and are skipped.
+        let mut order = std::mem::take(&mut *self.order.lock().unwrap_or_else(|e| e.into_inner()));
+        fill_step_order(&mut order, children.slot_count());
+        let priority = |index: usize| children.slot(index).map_or(i32::MIN, |child| child.base().priority());
+        let first_priority = children.iter().next().map_or(0, |child| child.base().priority());
+        let mixed = children.iter().any(|child| child.base().priority() != first_priority);
+        let top_priority = if mixed {
+            order.sort_by_key(|&index| Reverse(priority(index)));
+            priority(order[0])
+        } else {
+            first_priority
+        };
+        let budget = self.frame_budget();
+        let started = Instant::now();
+
+        for &index in &order {
+            let Some(child) = children.slot(index) else {
+                continue;
+            };
+            let over_budget = budget.is_some_and(|budget| started.elapsed() >= budget);
+            if over_budget && child.base().priority() < top_priority {
+                continue;
+            }
             if child.is_active() && child.is_running() && !child.is_completed() {
-                if let Err(e) = child.step().await {
+                if let Err(e) = child.traced_step().await {
                     self.logger().error(format!("Child step failed: {}", e));
+                    child.base().step_errored(&e.to_string());
                 }
             }
         }
+        drop(children);
+        *self.order.lock().unwrap_or_else(|e| e.into_inner()) = order;
+
+        if self.prunes_completed() {
+            self.remove_completed().await;
+        }
 
         Ok(())
     }
@@ -122,4 +381,30 @@ impl Generator for Node {
     fn logger(&self) -> &Logger {
         self.base.logger()
     }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        reset_subtree(self).await;
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        let children = self.children.read().await;
+        children.to_vec()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.children.try_read().ok().map(|children| children.to_vec())
+    }
+
+    async fn prune(&self) -> usize {
+        let mut removed = self.remove_completed().await;
+        for child in self.children().await {
+            removed += child.prune().await;
+        }
+        removed
+    }
 }
\ No newline at end of file
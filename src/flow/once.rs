@@ -0,0 +1,196 @@
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+use crate::flow::{set_children_active, ChildList, Generator, GeneratorBase, ParentLink};
+use crate::{current_resources, Logger, Result};
+
+// Keys of the keyed `Once` nodes that have run, kept in the kernel's
+// resources so rebuilt trees see them; nodes stepped outside a kernel share
+// a process-wide set.
+#[derive(Default)]
+struct OnceRecord {
+    done: Mutex<HashSet<String>>,
+}
+
+static UNSCOPED_RECORD: LazyLock<OnceRecord> = LazyLock::new(OnceRecord::default);
+
+fn with_record<R>(action: impl FnOnce(&OnceRecord) -> R) -> R {
+    match current_resources() {
+        Some(resources) => action(&resources.get_or_insert_with(OnceRecord::default)),
+        None => action(&UNSCOPED_RECORD),
+    }
+}
+
+/// Runs its child the first time through and completes straight away on
+/// later passes, for one-time initialization inside looped or rebuilt
+/// flows. The run counts once the child completes; if it fails, `Once`
+/// fails with it and the next pass tries again. `new` remembers the run in
+/// the node itself, surviving `reset`; `keyed` remembers it in the kernel
+/// under a key, so a freshly built tree with the same key skips it too.
+pub struct Once {
+    base: GeneratorBase,
+    inner: Arc<dyn Generator>,
+    slot: Arc<ChildList>,
+    key: Option<String>,
+    done: AtomicBool,
+    skipped: AtomicBool,
+}
+
+impl Once {
+    pub fn new(inner: Arc<dyn Generator>) -> Self {
+        Self::build(GeneratorBase::new(), None, inner)
+    }
+
+    pub fn keyed(key: impl Into<String>, inner: Arc<dyn Generator>) -> Self {
+        Self::build(GeneratorBase::new(), Some(key.into()), inner)
+    }
+
+    pub fn with_name(name: impl Into<String>, inner: Arc<dyn Generator>) -> Self {
+        Self::build(GeneratorBase::with_name(name), None, inner)
+    }
+
+    fn build(base: GeneratorBase, key: Option<String>, inner: Arc<dyn Generator>) -> Self {
+        let slot = Arc::new(RwLock::new(vec![inner.clone()]));
+        inner.base().set_parent(Some(ParentLink::new(&base, &slot)));
+        Self {
+            base,
+            inner,
+            slot,
+            key,
+            done: AtomicBool::new(false),
+            skipped: AtomicBool::new(false),
+        }
+    }
+
+    pub fn inner(&self) -> &Arc<dyn Generator> {
+        &self.inner
+    }
+
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// Whether the child has run to completion, here or, for a keyed node,
+    /// under the same key in this kernel.
+    pub fn has_run(&self) -> bool {
+        if self.done.load(Ordering::Relaxed) {
+            return true;
+        }
+        self.key
+            .as_ref()
+            .is_some_and(|key| with_record(|record| record.done.lock().unwrap_or_else(|e| e.into_inner()).contains(key)))
+    }
+
+    /// Whether the last pass completed without stepping the child.
+    pub fn was_skipped(&self) -> bool {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    fn record_run(&self) {
+        self.done.store(true, Ordering::Relaxed);
+        if let Some(key) = &self.key {
+            with_record(|record| record.done.lock().unwrap_or_else(|e| e.into_inner()).insert(key.clone()));
+        }
+    }
+}
+
+#[async_trait]
+impl Generator for Once {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        if self.has_run() {
+            self.logger().verbose(3, format!("{} already ran, skipping", self.inner.base().label()));
+            self.skipped.store(true, Ordering::Relaxed);
+            self.complete();
+            return Ok(());
+        }
+
+        if self.inner.is_active() && self.inner.is_running() && !self.inner.is_completed() {
+            if let Err(e) = self.inner.traced_step().await {
+                self.logger().error(format!("Once generator step failed: {}", e));
+                self.inner.base().step_errored(&e.to_string());
+            }
+        }
+
+        if self.inner.is_failed() {
+            self.base.fail(&format!("{} failed", self.inner.base().label()));
+        } else if self.inner.is_completed() {
+            self.record_run();
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        // A child that ran stays as it finished; there is no next run.
+        if !self.has_run() && !self.inner.reset().await {
+            return false;
+        }
+        self.skipped.store(false, Ordering::Relaxed);
+        self.base.reset();
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        self.slot.read().await.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.slot.try_read().ok().map(|slot| slot.clone())
+    }
+}
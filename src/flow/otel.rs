@@ -0,0 +1,65 @@
+use opentelemetry::trace::{TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+use crate::flow::{GeneratorBase, LifecycleObserver};
+
+/// Emits one OpenTelemetry span per generator, from its first step until it
+/// completes or fails. Spans nest the same way the flow tree does.
+pub struct OtelObserver<T> {
+    tracer: T,
+    spans: Mutex<HashMap<Uuid, Context>>,
+}
+
+impl<T> OtelObserver<T>
+where
+    T: Tracer + Send + Sync,
+    T::Span: Send + Sync + 'static,
+{
+    pub fn new(tracer: T) -> Self {
+        Self {
+            tracer,
+            spans: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn finish(&self, generator: &GeneratorBase, error: Option<&str>) {
+        let context = self.spans.lock().unwrap_or_else(|e| e.into_inner()).remove(&generator.id());
+        if let Some(context) = context {
+            let span = context.span();
+            if let Some(reason) = error {
+                span.set_status(opentelemetry::trace::Status::error(reason.to_string()));
+            }
+            span.end();
+        }
+    }
+}
+
+impl<T> LifecycleObserver for OtelObserver<T>
+where
+    T: Tracer + Send + Sync,
+    T::Span: Send + Sync + 'static,
+{
+    fn on_start(&self, generator: &GeneratorBase) {
+        let mut spans = self.spans.lock().unwrap_or_else(|e| e.into_inner());
+        let parent = generator
+            .parent_id()
+            .and_then(|id| spans.get(&id).cloned())
+            .unwrap_or_default();
+        let span = self
+            .tracer
+            .span_builder(generator.label())
+            .with_attributes(vec![KeyValue::new("flow.node.id", generator.id().to_string())])
+            .start_with_context(&self.tracer, &parent);
+        spans.insert(generator.id(), parent.with_span(span));
+    }
+
+    fn on_complete(&self, generator: &GeneratorBase) {
+        self.finish(generator, None);
+    }
+
+    fn on_fail(&self, generator: &GeneratorBase, reason: &str) {
+        self.finish(generator, Some(reason));
+    }
+}
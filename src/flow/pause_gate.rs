@@ -0,0 +1,230 @@
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{accept_child, check_no_cycle, reset_subtree, set_children_active, Generator, GeneratorBase, ParentLink};
+use crate::{flow_now, with_layer_time, Logger, Result};
+
+/// Pauses and resumes a `PauseGate` from anywhere, such as a menu's open
+/// and close callbacks. Clones control the same gate.
+#[derive(Clone, Default)]
+pub struct PauseHandle {
+    paused: Arc<AtomicBool>,
+}
+
+impl PauseHandle {
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+}
+
+// The gate's clock: flow time minus however long the gate spent paused,
+// measured from one step to the next.
+#[derive(Default)]
+struct FrozenClock {
+    last_step: Option<Instant>,
+    frozen: Duration,
+}
+
+/// Steps its children in order until all of them complete, except while
+/// paused: then nothing below it steps, and its timers see no time pass, so
+/// they pick up where they left off on `resume`. Paused time is counted per
+/// kernel tick. Children stay active throughout, unlike deactivating them.
+pub struct PauseGate {
+    base: GeneratorBase,
+    children: Arc<RwLock<Vec<Arc<dyn Generator>>>>,
+    handle: PauseHandle,
+    clock: Mutex<FrozenClock>,
+}
+
+impl PauseGate {
+    pub fn new() -> Self {
+        Self::build(GeneratorBase::new())
+    }
+
+    pub fn with_name(name: impl Into<String>) -> Self {
+        Self::build(GeneratorBase::with_name(name))
+    }
+
+    fn build(base: GeneratorBase) -> Self {
+        Self {
+            base,
+            children: Arc::new(RwLock::new(Vec::new())),
+            handle: PauseHandle::default(),
+            clock: Mutex::new(FrozenClock::default()),
+        }
+    }
+
+    fn parent_link(&self) -> ParentLink {
+        ParentLink::new(&self.base, &self.children)
+    }
+
+    pub async fn add_child(&self, child: Arc<dyn Generator>) {
+        if accept_child(&self.base, &child) {
+            self.push_child(child).await;
+        }
+    }
+
+    /// Like `add_child`, but returns the error instead of logging it when
+    /// `child` is this container or one of its ancestors.
+    pub async fn try_add_child(&self, child: Arc<dyn Generator>) -> Result<()> {
+        check_no_cycle(&self.base, child.as_ref())?;
+        self.push_child(child).await;
+        Ok(())
+    }
+
+    async fn push_child(&self, child: Arc<dyn Generator>) {
+        child.base().set_parent(Some(self.parent_link()));
+        let mut children = self.children.write().await;
+        children.push(child);
+    }
+
+    pub fn handle(&self) -> PauseHandle {
+        self.handle.clone()
+    }
+
+    pub fn pause(&self) {
+        self.handle.pause();
+    }
+
+    pub fn resume(&self) {
+        self.handle.resume();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.handle.is_paused()
+    }
+
+    /// Total time spent paused so far.
+    pub fn paused_for(&self) -> Duration {
+        self.clock.lock().unwrap_or_else(|e| e.into_inner()).frozen
+    }
+
+    // Charges the time since the last step to the pause if paused now, and
+    // returns the gate's own time.
+    fn tick(&self, paused: bool) -> Instant {
+        let now = flow_now();
+        let mut clock = self.clock.lock().unwrap_or_else(|e| e.into_inner());
+        if paused {
+            if let Some(last_step) = clock.last_step {
+                clock.frozen += now.saturating_duration_since(last_step);
+            }
+        }
+        clock.last_step = Some(now);
+        now.checked_sub(clock.frozen).unwrap_or(now)
+    }
+}
+
+impl Default for PauseGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Generator for PauseGate {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let paused = self.is_paused();
+        let now = self.tick(paused);
+        if paused {
+            return Ok(());
+        }
+
+        let children = self.children.read().await;
+        with_layer_time(now, async {
+            for child in children.iter() {
+                if child.is_active() && child.is_running() && !child.is_completed() {
+                    if let Err(e) = child.traced_step().await {
+                        self.logger().error(format!("Child step failed in pause gate: {}", e));
+                        child.base().step_errored(&e.to_string());
+                    }
+                }
+            }
+        })
+        .await;
+
+        if !children.is_empty() && children.iter().all(|child| child.is_completed()) {
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        *self.clock.lock().unwrap_or_else(|e| e.into_inner()) = FrozenClock::default();
+        reset_subtree(self).await;
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        let children = self.children.read().await;
+        children.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.children.try_read().ok().map(|children| children.clone())
+    }
+}
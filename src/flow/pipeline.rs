@@ -0,0 +1,259 @@
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream, StreamExt};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{self, Receiver};
+use tokio::task::JoinSet;
+use tracing::Instrument;
+use uuid::Uuid;
+use crate::flow::{task_span, Generator, GeneratorBase};
+use crate::{Logger, Result};
+
+const DEFAULT_CAPACITY: usize = 16;
+
+#[derive(Default)]
+struct Throughput {
+    processed: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+fn receiver_stream<T: Send + 'static>(receiver: Receiver<T>) -> BoxStream<'static, T> {
+    stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|item| (item, receiver))
+    })
+    .boxed()
+}
+
+/// Builds a `Pipeline` one stage at a time, where `T` is what the stages
+/// added so far produce. Finished with `sink`.
+pub struct PipelineBuilder<T> {
+    base: GeneratorBase,
+    capacity: usize,
+    output: BoxStream<'static, T>,
+    stages: Vec<BoxFuture<'static, ()>>,
+    throughput: Arc<Throughput>,
+}
+
+impl<T: Send + 'static> PipelineBuilder<T> {
+    fn new(base: GeneratorBase, source: BoxStream<'static, T>) -> Self {
+        Self {
+            base,
+            capacity: DEFAULT_CAPACITY,
+            output: source,
+            stages: Vec::new(),
+            throughput: Arc::new(Throughput::default()),
+        }
+    }
+
+    /// How many items may wait between each of the following stages and the
+    /// next before the earlier one holds off. Defaults to 16.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Adds a stage turning each item into the next stage's input, running
+    /// `stage` on up to `concurrency` items at once. Items may overtake each
+    /// other when `concurrency` is above one. An item whose stage fails is
+    /// logged, counted and dropped.
+    pub fn stage<U, F, Fut>(mut self, concurrency: usize, stage: F) -> PipelineBuilder<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<U>> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(self.capacity);
+        let (throughput, logger) = (self.throughput.clone(), self.base.logger().clone());
+        let index = self.stages.len() + 1;
+        let mut results = self.output.map(stage).buffer_unordered(concurrency.max(1));
+        self.stages.push(Box::pin(async move {
+            while let Some(result) = results.next().await {
+                match result {
+                    Ok(item) => {
+                        if sender.send(item).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        throughput.failed.fetch_add(1, Ordering::Relaxed);
+                        logger.error(format!("Pipeline stage {} failed: {}", index, e));
+                    }
+                }
+            }
+        }));
+        PipelineBuilder {
+            base: self.base,
+            capacity: self.capacity,
+            output: receiver_stream(receiver),
+            stages: self.stages,
+            throughput: self.throughput,
+        }
+    }
+
+    /// Ends the pipeline with a stage consuming each item, on up to
+    /// `concurrency` items at once.
+    pub fn sink<F, Fut>(mut self, concurrency: usize, sink: F) -> Pipeline
+    where
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let (throughput, logger) = (self.throughput.clone(), self.base.logger().clone());
+        let mut results = self.output.map(sink).buffer_unordered(concurrency.max(1));
+        self.stages.push(Box::pin(async move {
+            while let Some(result) = results.next().await {
+                match result {
+                    Ok(()) => {
+                        throughput.processed.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(e) => {
+                        throughput.failed.fetch_add(1, Ordering::Relaxed);
+                        logger.error(format!("Pipeline sink failed: {}", e));
+                    }
+                }
+            }
+        }));
+        Pipeline {
+            base: self.base,
+            stage_count: self.stages.len(),
+            stages: Mutex::new(self.stages),
+            tasks: Mutex::new(JoinSet::new()),
+            throughput: self.throughput,
+        }
+    }
+}
+
+/// Passes items from a source through a chain of async stages, each on its
+/// own task and joined to the next by a bounded channel, so a slow stage
+/// holds back the ones before it. The stage tasks start on the first step.
+/// Completes once the source is drained and every item has left the sink;
+/// failing items are dropped without failing the pipeline.
+pub struct Pipeline {
+    base: GeneratorBase,
+    stage_count: usize,
+    // Taken and spawned on the first step.
+    stages: Mutex<Vec<BoxFuture<'static, ()>>>,
+    tasks: Mutex<JoinSet<()>>,
+    throughput: Arc<Throughput>,
+}
+
+impl Pipeline {
+    /// Items sent on `receiver`; the pipeline drains once every sender is
+    /// dropped.
+    pub fn from_channel<T: Send + 'static>(receiver: Receiver<T>) -> PipelineBuilder<T> {
+        PipelineBuilder::new(GeneratorBase::new(), receiver_stream(receiver))
+    }
+
+    pub fn from_items<I>(items: I) -> PipelineBuilder<I::Item>
+    where
+        I: IntoIterator,
+        I::IntoIter: Send + 'static,
+        I::Item: Send + 'static,
+    {
+        PipelineBuilder::new(GeneratorBase::new(), stream::iter(items).boxed())
+    }
+
+    /// Stages including the sink.
+    pub fn stages(&self) -> usize {
+        self.stage_count
+    }
+
+    /// Items the sink has taken successfully.
+    pub fn processed(&self) -> usize {
+        self.throughput.processed.load(Ordering::Relaxed)
+    }
+
+    /// Items dropped because a stage or the sink failed on them.
+    pub fn failed(&self) -> usize {
+        self.throughput.failed.load(Ordering::Relaxed)
+    }
+
+    fn spawn_stages(&self, tasks: &mut JoinSet<()>) {
+        let stages = std::mem::take(&mut *self.stages.lock().unwrap_or_else(|e| e.into_inner()));
+        for stage in stages {
+            tasks.spawn(stage.instrument(task_span(&self.base)));
+        }
+    }
+
+    fn reap(&self, tasks: &mut JoinSet<()>) {
+        while let Some(outcome) = tasks.try_join_next() {
+            if let Err(e) = outcome {
+                self.logger().error(format!("Pipeline stage stopped: {}", e));
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Generator for Pipeline {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let finished = {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            self.spawn_stages(&mut tasks);
+            self.reap(&mut tasks);
+            tasks.is_empty()
+        };
+        if finished {
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    fn cancel(&self) {
+        self.stages.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner()).abort_all();
+        self.base.cancel();
+    }
+}
@@ -0,0 +1,334 @@
+//! Proxies for generators running in another process, behind the `remote`
+//! feature. The worker process serves a generator with `serve_remote`; the
+//! orchestrating kernel adds a `RemoteNode` that mirrors its state.
+//!
+//! The protocol is a stream of frames, each a big-endian `u32` length
+//! followed by that many bytes. The proxy sends one-byte commands (activate,
+//! deactivate, complete, cancel, poll) and the host answers every command
+//! with a two-byte state frame: a tag and the remote generator's flags.
+
+use async_trait::async_trait;
+use std::io;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use crate::flow::{Generator, GeneratorBase};
+use crate::{Logger, Result};
+
+// Frames longer than this are treated as a protocol error.
+const MAX_FRAME: u32 = 64 * 1024;
+const STATE_TAG: u8 = 0x80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Activate = 1,
+    Deactivate = 2,
+    Complete = 3,
+    Cancel = 4,
+    Poll = 5,
+}
+
+impl Command {
+    fn decode(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Self::Activate),
+            2 => Some(Self::Deactivate),
+            3 => Some(Self::Complete),
+            4 => Some(Self::Cancel),
+            5 => Some(Self::Poll),
+            _ => None,
+        }
+    }
+}
+
+/// The remote generator's state as last reported by its host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RemoteState {
+    pub active: bool,
+    pub running: bool,
+    pub completed: bool,
+    pub failed: bool,
+}
+
+impl RemoteState {
+    fn of(generator: &dyn Generator) -> Self {
+        Self {
+            active: generator.is_active(),
+            running: generator.is_running(),
+            completed: generator.is_completed(),
+            failed: generator.is_failed(),
+        }
+    }
+
+    fn encode(self) -> u8 {
+        self.active as u8 | (self.running as u8) << 1 | (self.completed as u8) << 2 | (self.failed as u8) << 3
+    }
+
+    fn decode(flags: u8) -> Self {
+        Self {
+            active: flags & 1 != 0,
+            running: flags & 2 != 0,
+            completed: flags & 4 != 0,
+            failed: flags & 8 != 0,
+        }
+    }
+}
+
+async fn write_frame(stream: &mut (impl AsyncWrite + Unpin), body: &[u8]) -> io::Result<()> {
+    stream.write_all(&(body.len() as u32).to_be_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.flush().await
+}
+
+// `None` when the peer closed the stream between frames.
+async fn read_frame(stream: &mut (impl AsyncRead + Unpin)) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0; 4];
+    match stream.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_be_bytes(len);
+    if len > MAX_FRAME {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("frame of {} bytes", len)));
+    }
+    let mut body = vec![0; len as usize];
+    stream.read_exact(&mut body).await?;
+    Ok(Some(body))
+}
+
+/// Serves `generator` to every `RemoteNode` that connects to `listener`,
+/// one task per connection. Runs until accepting fails.
+pub async fn serve_remote(listener: TcpListener, generator: Arc<dyn Generator>) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let generator = generator.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve_remote_connection(stream, generator.clone()).await {
+                generator.logger().warn(format!("Remote connection failed: {}", e));
+            }
+        });
+    }
+}
+
+/// Serves `generator` over one already-accepted connection, such as a Unix
+/// socket, until the proxy disconnects.
+pub async fn serve_remote_connection<S>(mut stream: S, generator: Arc<dyn Generator>) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    while let Some(frame) = read_frame(&mut stream).await? {
+        let command = frame.first().and_then(|byte| Command::decode(*byte));
+        match command {
+            Some(Command::Activate) => generator.activate(),
+            Some(Command::Deactivate) => generator.deactivate(),
+            Some(Command::Complete) => generator.complete(),
+            Some(Command::Cancel) => generator.cancel(),
+            Some(Command::Poll) => {}
+            None => return Err(format!("unknown remote command {:?}", frame).into()),
+        }
+        write_frame(&mut stream, &[STATE_TAG, RemoteState::of(generator.as_ref()).encode()]).await?;
+    }
+    Ok(())
+}
+
+trait Transport: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Transport for T {}
+
+enum Endpoint {
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl Endpoint {
+    async fn connect(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(match self {
+            Self::Tcp(addr) => Box::new(TcpStream::connect(addr).await?),
+            #[cfg(unix)]
+            Self::Unix(path) => Box::new(tokio::net::UnixStream::connect(path).await?),
+        })
+    }
+}
+
+#[derive(Default)]
+struct Link {
+    state: Mutex<Option<RemoteState>>,
+    error: Mutex<Option<String>>,
+    polling: AtomicBool,
+}
+
+/// Mirrors a generator served by `serve_remote` in another process. It
+/// connects on its first step, forwards `activate`, `deactivate`, `complete`
+/// and `cancel` to the remote generator, polls its state once per step, and
+/// completes or fails when it does. Losing the connection fails the node.
+pub struct RemoteNode {
+    base: GeneratorBase,
+    endpoint: Arc<Endpoint>,
+    commands: mpsc::UnboundedSender<Command>,
+    pending: Mutex<Option<mpsc::UnboundedReceiver<Command>>>,
+    link: Arc<Link>,
+}
+
+impl RemoteNode {
+    /// A proxy for the generator served at the TCP address `addr`.
+    pub fn tcp(addr: impl Into<String>) -> Self {
+        Self::build(Endpoint::Tcp(addr.into()))
+    }
+
+    /// A proxy for the generator served on the Unix socket at `path`.
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        Self::build(Endpoint::Unix(path.into()))
+    }
+
+    fn build(endpoint: Endpoint) -> Self {
+        let (commands, pending) = mpsc::unbounded_channel();
+        Self {
+            base: GeneratorBase::new(),
+            endpoint: Arc::new(endpoint),
+            commands,
+            pending: Mutex::new(Some(pending)),
+            link: Arc::new(Link::default()),
+        }
+    }
+
+    /// The last state the remote generator reported; `None` before the first reply.
+    pub fn remote_state(&self) -> Option<RemoteState> {
+        *self.link.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.remote_state().is_some() && self.connection_error().is_none()
+    }
+
+    pub fn connection_error(&self) -> Option<String> {
+        self.link.error.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn send(&self, command: Command) {
+        // Fails only once the connection task is gone, which `step` reports.
+        let _ = self.commands.send(command);
+    }
+
+    // Starts the connection task the first time it is called.
+    fn connect(&self) {
+        let Some(mut commands) = self.pending.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+            return;
+        };
+        let (endpoint, link) = (self.endpoint.clone(), self.link.clone());
+        tokio::spawn(async move {
+            let exchange = async {
+                let mut stream = endpoint.connect().await?;
+                while let Some(command) = commands.recv().await {
+                    write_frame(&mut stream, &[command as u8]).await?;
+                    let Some(reply) = read_frame(&mut stream).await? else {
+                        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "remote host closed the connection"));
+                    };
+                    let [STATE_TAG, flags] = reply[..] else {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, "malformed remote state"));
+                    };
+                    *link.state.lock().unwrap_or_else(|e| e.into_inner()) = Some(RemoteState::decode(flags));
+                    if command == Command::Poll {
+                        link.polling.store(false, Ordering::Relaxed);
+                    }
+                }
+                Ok(())
+            };
+            if let Err(e) = exchange.await {
+                *link.error.lock().unwrap_or_else(|e| e.into_inner()) = Some(e.to_string());
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl Generator for RemoteNode {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        self.send(Command::Activate);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        self.send(Command::Deactivate);
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+        self.send(Command::Complete);
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+        self.connect();
+
+        if let Some(error) = self.connection_error() {
+            self.base.fail(&format!("Remote connection lost: {}", error));
+            return Ok(());
+        }
+
+        if let Some(state) = self.remote_state() {
+            if state.completed {
+                if state.failed {
+                    self.base.fail(&format!("Remote generator {} failed", self.base.label()));
+                } else {
+                    self.base.complete();
+                }
+                return Ok(());
+            }
+        }
+
+        if !self.link.polling.swap(true, Ordering::Relaxed) {
+            self.send(Command::Poll);
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    fn cancel(&self) {
+        self.send(Command::Cancel);
+    }
+}
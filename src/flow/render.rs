@@ -0,0 +1,54 @@
+use std::fmt::Write;
+use crate::flow::{label, walk, FlowVisitor, Generator, VisitControl, VisitInfo};
+
+struct TreeRenderer {
+    out: String,
+}
+
+impl FlowVisitor for TreeRenderer {
+    fn enter(&mut self, generator: &dyn Generator, info: &VisitInfo) -> VisitControl {
+        write_line(&mut self.out, generator, info.depth);
+        VisitControl::Continue
+    }
+}
+
+fn write_line(out: &mut String, generator: &dyn Generator, depth: usize) {
+    let _ = writeln!(
+        out,
+        "{}{} [{}] {}",
+        "  ".repeat(depth),
+        label(generator),
+        generator.type_name(),
+        generator.state()
+    );
+}
+
+/// Renders `root` and its subtree as an indented list of
+/// `name [Type] state` lines.
+pub async fn render_tree(root: &dyn Generator) -> String {
+    let mut renderer = TreeRenderer { out: String::new() };
+    walk(root, &mut renderer).await;
+    renderer.out
+}
+
+/// Like `render_tree`, but never waits: child lists that are locked at the
+/// time of the call are shown as `(busy)`. Used by `Display` impls.
+pub fn render_tree_now(root: &dyn Generator) -> String {
+    let mut out = String::new();
+    render_now(root, 0, &mut out);
+    out
+}
+
+fn render_now(generator: &dyn Generator, depth: usize, out: &mut String) {
+    write_line(out, generator, depth);
+    match generator.try_children() {
+        Some(children) => {
+            for child in children {
+                render_now(child.as_ref(), depth + 1, out);
+            }
+        }
+        None => {
+            let _ = writeln!(out, "{}(busy)", "  ".repeat(depth + 1));
+        }
+    }
+}
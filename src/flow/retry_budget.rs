@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Option<Instant>,
+}
+
+/// A token bucket of retries shared by the nodes that retry against the
+/// same dependency, so a struggling service sees at most `retries` retries
+/// per `window` in total rather than a burst from every subtree at once.
+/// Tokens come back steadily over the window. First attempts are free;
+/// only retries spend. Clones share the same bucket.
+#[derive(Clone)]
+pub struct RetryBudget {
+    name: Arc<str>,
+    retries: u32,
+    window: Duration,
+    bucket: Arc<Mutex<Bucket>>,
+    denied: Arc<AtomicU32>,
+}
+
+impl RetryBudget {
+    pub fn new(name: impl Into<String>, retries: u32, window: Duration) -> Self {
+        Self {
+            name: name.into().into(),
+            retries,
+            window,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: retries as f64,
+                refilled_at: None,
+            })),
+            denied: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Retries allowed per window.
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Takes a retry from the budget, or returns false if it is spent.
+    pub fn try_spend(&self) -> bool {
+        let mut bucket = self.refilled();
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            self.denied.fetch_add(1, Ordering::Relaxed);
+            false
+        }
+    }
+
+    /// Whole retries left right now.
+    pub fn remaining(&self) -> u32 {
+        self.refilled().tokens as u32
+    }
+
+    /// Retries turned away so far.
+    pub fn denied(&self) -> u32 {
+        self.denied.load(Ordering::Relaxed)
+    }
+
+    fn refilled(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        if let Some(refilled_at) = bucket.refilled_at {
+            let elapsed = now.duration_since(refilled_at).as_secs_f64();
+            let per_second = self.retries as f64 / self.window.as_secs_f64().max(f64::EPSILON);
+            bucket.tokens = (bucket.tokens + elapsed * per_second).min(self.retries as f64);
+        }
+        bucket.refilled_at = Some(now);
+        bucket
+    }
+}
@@ -0,0 +1,161 @@
+use async_trait::async_trait;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+use crate::flow::{CallbackHandle, CallbackList, FlowCallback, Generator, GeneratorBase};
+use crate::{Logger, Result};
+
+/// Watches a condition like a `Trigger`, but keeps watching after it holds
+/// and fires its callbacks only on every `every`th time the condition turns
+/// true, for cutting down the work behind a chatty condition without
+/// throttling by time. A condition that stays true counts once; it has to
+/// turn false in between. Never completes on its own.
+pub struct SampledTrigger {
+    base: GeneratorBase,
+    condition: Box<dyn Fn() -> bool + Send + Sync>,
+    every: u32,
+    triggered_callbacks: RwLock<CallbackList>,
+    holding: AtomicBool,
+    occurrences: AtomicU32,
+    fires: AtomicU32,
+}
+
+impl SampledTrigger {
+    pub fn new<F>(every: u32, condition: F) -> Self
+    where
+        F: Fn() -> bool + Send + Sync + 'static,
+    {
+        Self::build(GeneratorBase::new(), every, Box::new(condition))
+    }
+
+    pub fn with_name<F>(name: impl Into<String>, every: u32, condition: F) -> Self
+    where
+        F: Fn() -> bool + Send + Sync + 'static,
+    {
+        Self::build(GeneratorBase::with_name(name), every, Box::new(condition))
+    }
+
+    fn build(base: GeneratorBase, every: u32, condition: Box<dyn Fn() -> bool + Send + Sync>) -> Self {
+        Self {
+            base,
+            condition,
+            every: every.max(1),
+            triggered_callbacks: RwLock::new(CallbackList::new()),
+            holding: AtomicBool::new(false),
+            occurrences: AtomicU32::new(0),
+            fires: AtomicU32::new(0),
+        }
+    }
+
+    pub async fn add_triggered_callback<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.triggered_callbacks.write().await.add(FlowCallback::from_sync(callback))
+    }
+
+    pub async fn add_triggered_callback_async<F, Fut>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.triggered_callbacks.write().await.add(FlowCallback::from_async(callback))
+    }
+
+    pub async fn remove_triggered_callback(&self, handle: CallbackHandle) -> bool {
+        self.triggered_callbacks.write().await.remove(handle)
+    }
+
+    pub fn every(&self) -> u32 {
+        self.every
+    }
+
+    /// Times the condition has turned true, fired on or not.
+    pub fn occurrences(&self) -> u32 {
+        self.occurrences.load(Ordering::Relaxed)
+    }
+
+    /// Times the callbacks have fired.
+    pub fn fires(&self) -> u32 {
+        self.fires.load(Ordering::Relaxed)
+    }
+}
+
+#[async_trait]
+impl Generator for SampledTrigger {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let holds = (self.condition)();
+        if !holds || self.holding.swap(true, Ordering::Relaxed) {
+            self.holding.store(holds, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        let occurrences = self.occurrences.fetch_add(1, Ordering::Relaxed) + 1;
+        if occurrences.is_multiple_of(self.every) {
+            self.fires.fetch_add(1, Ordering::Relaxed);
+            self.triggered_callbacks.read().await.invoke_all();
+            self.base.notify(|observer| observer.on_triggered(&self.base));
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        self.holding.store(false, Ordering::Relaxed);
+        self.occurrences.store(0, Ordering::Relaxed);
+        self.fires.store(0, Ordering::Relaxed);
+        self.base.reset();
+        true
+    }
+}
@@ -1,22 +1,31 @@
 use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
-use crate::flow::{Generator, GeneratorBase};
+use crate::flow::{
+    accept_child, check_no_cycle, reset_subtree, set_children_active, Generator, GeneratorBase, NodeSnapshot,
+    ParentLink, SequenceBuilder,
+};
 use crate::{Logger, Result};
 
 pub struct Sequence {
     base: GeneratorBase,
     children: Arc<RwLock<Vec<Arc<dyn Generator>>>>,
     current_index: Arc<RwLock<usize>>,
+    advance_limit: AtomicUsize,
 }
 
+/// How many children a sequence moves through in one step by default.
+pub const DEFAULT_ADVANCE_LIMIT: usize = 64;
+
 impl Sequence {
     pub fn new() -> Self {
         Self {
             base: GeneratorBase::new(),
             children: Arc::new(RwLock::new(Vec::new())),
             current_index: Arc::new(RwLock::new(0)),
+            advance_limit: AtomicUsize::new(DEFAULT_ADVANCE_LIMIT),
         }
     }
 
@@ -25,24 +34,205 @@ impl Sequence {
             base: GeneratorBase::with_name(name),
             children: Arc::new(RwLock::new(Vec::new())),
             current_index: Arc::new(RwLock::new(0)),
+            advance_limit: AtomicUsize::new(DEFAULT_ADVANCE_LIMIT),
+        }
+    }
+
+    // Used by the builders: fills the child list of a container that has not
+    // been shared yet, so no lock needs to be awaited.
+    pub(crate) fn with_children(self, children: Vec<Arc<dyn Generator>>) -> Self {
+        {
+            let mut existing = self.children.try_write().expect("container is not shared yet");
+            existing.extend(children.iter().cloned());
         }
+        let link = self.parent_link();
+        for child in &children {
+            child.base().set_parent(Some(link.clone()));
+        }
+        self
+    }
+
+    fn parent_link(&self) -> ParentLink {
+        ParentLink::new(&self.base, &self.children).with_cursor(&self.current_index)
     }
 
     pub async fn add_child(&self, child: Arc<dyn Generator>) {
+        if accept_child(&self.base, &child) {
+            self.push_child(child).await;
+        }
+    }
+
+    /// Like `add_child`, but returns the error instead of logging it when
+    /// `child` is this container or one of its ancestors.
+    pub async fn try_add_child(&self, child: Arc<dyn Generator>) -> Result<()> {
+        check_no_cycle(&self.base, child.as_ref())?;
+        self.push_child(child).await;
+        Ok(())
+    }
+
+    async fn push_child(&self, child: Arc<dyn Generator>) {
+        child.base().set_parent(Some(self.parent_link()));
         let mut children = self.children.write().await;
         children.push(child);
     }
 
+    pub async fn add_children<I>(&self, children: I)
+    where
+        I: IntoIterator<Item = Arc<dyn Generator>>,
+    {
+        let link = self.parent_link();
+        let mut existing = self.children.write().await;
+        existing.extend(children.into_iter().filter(|child| accept_child(&self.base, child)).inspect(|child| {
+            child.base().set_parent(Some(link.clone()));
+        }));
+    }
+
+    /// Inserts `child` at `index`; indices past the end append. Inserting at
+    /// the current position makes the new child current.
+    pub async fn insert_at(&self, index: usize, child: Arc<dyn Generator>) {
+        if !accept_child(&self.base, &child) {
+            return;
+        }
+        child.base().set_parent(Some(self.parent_link()));
+        let mut children = self.children.write().await;
+        let mut current_index = self.current_index.write().await;
+        let index = index.min(children.len());
+        children.insert(index, child);
+        if index < *current_index {
+            *current_index += 1;
+        }
+    }
+
+    pub async fn insert_before(&self, id: Uuid, child: Arc<dyn Generator>) -> bool {
+        if !accept_child(&self.base, &child) {
+            return false;
+        }
+        let mut children = self.children.write().await;
+        let mut current_index = self.current_index.write().await;
+        if let Some(pos) = children.iter().position(|c| c.id() == id) {
+            child.base().set_parent(Some(self.parent_link()));
+            children.insert(pos, child);
+            if pos < *current_index {
+                *current_index += 1;
+            }
+            return true;
+        }
+        false
+    }
+
+    pub async fn replace_child(&self, id: Uuid, child: Arc<dyn Generator>) -> bool {
+        if !accept_child(&self.base, &child) {
+            return false;
+        }
+        let mut children = self.children.write().await;
+        if let Some(pos) = children.iter().position(|c| c.id() == id) {
+            children[pos].base().set_parent(None);
+            child.base().set_parent(Some(self.parent_link()));
+            children[pos] = child;
+            return true;
+        }
+        false
+    }
+
+    pub async fn remove_child(&self, id: Uuid) -> bool {
+        let mut children = self.children.write().await;
+        let mut current_index = self.current_index.write().await;
+        if let Some(pos) = children.iter().position(|c| c.id() == id) {
+            children.remove(pos).base().set_parent(None);
+            if pos < *current_index {
+                *current_index -= 1;
+            }
+            return true;
+        }
+        false
+    }
+
+    /// Moves past the current child without waiting for it; it is left as
+    /// it is and no longer stepped. False once the sequence has run out.
+    pub async fn skip_current(&self) -> bool {
+        let children = self.children.read().await;
+        let mut current_index = self.current_index.write().await;
+        if *current_index >= children.len() {
+            return false;
+        }
+        *current_index += 1;
+        true
+    }
+
+    /// Makes the child at `index` current; `child_count()` jumps to the end.
+    /// Jumping back to a child that already completed moves straight past it.
+    pub async fn jump_to(&self, index: usize) -> bool {
+        let children = self.children.read().await;
+        if index > children.len() {
+            return false;
+        }
+        *self.current_index.write().await = index;
+        true
+    }
+
+    pub async fn jump_to_name(&self, name: &str) -> bool {
+        let children = self.children.read().await;
+        match children.iter().position(|child| child.name().as_deref() == Some(name)) {
+            Some(index) => {
+                *self.current_index.write().await = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Caps how many finished children one step moves past before yielding
+    /// to the next tick, so a run of instantly-completing children cannot
+    /// monopolise a tick. At least one.
+    pub fn set_advance_limit(&self, limit: usize) {
+        self.advance_limit.store(limit.max(1), Ordering::Relaxed);
+    }
+
+    pub fn advance_limit(&self) -> usize {
+        self.advance_limit.load(Ordering::Relaxed)
+    }
+
     pub async fn current_index(&self) -> usize {
         *self.current_index.read().await
     }
 
+    /// The child being waited on, or `None` once the sequence has run out.
+    pub async fn current_child(&self) -> Option<Arc<dyn Generator>> {
+        let children = self.children.read().await;
+        let current_index = *self.current_index.read().await;
+        children.get(current_index).cloned()
+    }
+
+    /// Children not yet finished or skipped, including the current one.
+    pub async fn remaining_count(&self) -> usize {
+        let children = self.children.read().await;
+        let current_index = *self.current_index.read().await;
+        children.len().saturating_sub(current_index)
+    }
+
+    /// The fraction of children moved past, from 0.0 to 1.0. An empty
+    /// sequence reports 1.0 once completed and 0.0 before.
+    pub async fn progress(&self) -> f32 {
+        let children = self.children.read().await;
+        if children.is_empty() {
+            return if self.is_completed() { 1.0 } else { 0.0 };
+        }
+        let current_index = *self.current_index.read().await;
+        current_index.min(children.len()) as f32 / children.len() as f32
+    }
+
     pub async fn child_count(&self) -> usize {
         let children = self.children.read().await;
         children.len()
     }
 }
 
+impl Sequence {
+    pub fn builder() -> SequenceBuilder {
+        SequenceBuilder::new()
+    }
+}
+
 impl Default for Sequence {
     fn default() -> Self {
         Self::new()
@@ -55,11 +245,11 @@ impl Generator for Sequence {
         self.base.id()
     }
 
-    fn name(&self) -> Option<&str> {
+    fn name(&self) -> Option<String> {
         self.base.name()
     }
 
-    fn set_name(&mut self, name: String) {
+    fn set_name(&self, name: String) {
         self.base.set_name(name);
     }
 
@@ -77,10 +267,12 @@ impl Generator for Sequence {
 
     fn activate(&self) {
         self.base.activate();
+        set_children_active(self, true);
     }
 
     fn deactivate(&self) {
         self.base.deactivate();
+        set_children_active(self, false);
     }
 
     fn complete(&self) {
@@ -92,36 +284,77 @@ impl Generator for Sequence {
             return Ok(());
         }
 
+        self.base.start();
+
         let children = self.children.read().await;
         if children.is_empty() {
             self.complete();
             return Ok(());
         }
 
+        // A child that finishes is left behind at once and its successor is
+        // stepped in the same call, so each stage costs no extra tick.
         let mut current_index = self.current_index.write().await;
-        
-        if *current_index >= children.len() {
-            self.complete();
-            return Ok(());
-        }
-
-        let current_child = &children[*current_index];
-        
-        if current_child.is_completed() {
-            *current_index += 1;
-            if *current_index >= children.len() {
-                self.complete();
+        let mut advanced = 0;
+        while *current_index < children.len() {
+            let current_child = &children[*current_index];
+            if !current_child.is_completed() {
+                if !current_child.is_active() || !current_child.is_running() {
+                    break;
+                }
+                if let Err(e) = current_child.traced_step().await {
+                    self.logger().error(format!("Child step failed in sequence: {}", e));
+                    current_child.base().step_errored(&e.to_string());
+                }
+                if !current_child.is_completed() {
+                    break;
+                }
             }
-        } else if current_child.is_active() && current_child.is_running() {
-            if let Err(e) = current_child.step().await {
-                self.logger().error(format!("Child step failed in sequence: {}", e));
+            *current_index += 1;
+            advanced += 1;
+            if advanced >= self.advance_limit() {
+                break;
             }
         }
 
+        if *current_index >= children.len() {
+            self.complete();
+        }
+
         Ok(())
     }
 
     fn logger(&self) -> &Logger {
         self.base.logger()
     }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn fill_snapshot(&self, snapshot: &mut NodeSnapshot) {
+        snapshot.sequence_index = Some(self.current_index().await);
+    }
+
+    async fn restore_snapshot(&self, snapshot: &NodeSnapshot) {
+        if let Some(index) = snapshot.sequence_index {
+            *self.current_index.write().await = index;
+        }
+    }
+
+    /// Rewinds to the first child and resets every child that supports it.
+    async fn reset(&self) -> bool {
+        *self.current_index.write().await = 0;
+        reset_subtree(self).await;
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        let children = self.children.read().await;
+        children.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.children.try_read().ok().map(|children| children.clone())
+    }
 }
\ No newline at end of file
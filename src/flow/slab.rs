@@ -0,0 +1,176 @@
+use std::sync::Arc;
+use crate::flow::Generator;
+
+/// A stable reference to a child of a `Node`, returned by `add_child`.
+/// Removing through a handle takes constant time; a handle to a child that
+/// has since been removed no longer matches anything, even once its slot is
+/// reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChildHandle {
+    key: u32,
+    generation: u32,
+}
+
+struct Entry {
+    key: u32,
+    child: Arc<dyn Generator>,
+}
+
+struct Key {
+    generation: u32,
+    position: Option<usize>,
+}
+
+/// Children in insertion order, addressed by `ChildHandle`. Removal leaves a
+/// hole rather than shifting the rest; holes are compacted away once they
+/// make up half the slots.
+#[derive(Default)]
+pub struct ChildSlab {
+    slots: Vec<Option<Entry>>,
+    keys: Vec<Key>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+// Below this many holes compaction isn't worth a pass over the slots.
+const MIN_HOLES_TO_COMPACT: usize = 32;
+
+impl ChildSlab {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, child: Arc<dyn Generator>) -> ChildHandle {
+        let position = self.slots.len();
+        let handle = self.allocate_key(position);
+        self.slots.push(Some(Entry { key: handle.key, child }));
+        self.len += 1;
+        handle
+    }
+
+    /// Inserts `child` so that `index` children come before it.
+    pub fn insert(&mut self, index: usize, child: Arc<dyn Generator>) -> ChildHandle {
+        self.compact();
+        let position = index.min(self.slots.len());
+        let handle = self.allocate_key(position);
+        self.slots.insert(position, Some(Entry { key: handle.key, child }));
+        self.len += 1;
+        self.reindex(position + 1);
+        handle
+    }
+
+    pub fn get(&self, handle: ChildHandle) -> Option<&Arc<dyn Generator>> {
+        let position = self.position_of(handle)?;
+        self.slots[position].as_ref().map(|entry| &entry.child)
+    }
+
+    pub fn remove(&mut self, handle: ChildHandle) -> Option<Arc<dyn Generator>> {
+        let position = self.position_of(handle)?;
+        let entry = self.slots[position].take()?;
+        self.release(entry.key);
+        self.len -= 1;
+        let holes = self.slots.len() - self.len;
+        if holes >= MIN_HOLES_TO_COMPACT && holes * 2 >= self.slots.len() {
+            self.compact();
+        }
+        Some(entry.child)
+    }
+
+    /// Swaps in `child` under the same handle, returning the old child.
+    pub fn replace(&mut self, handle: ChildHandle, child: Arc<dyn Generator>) -> Option<Arc<dyn Generator>> {
+        let position = self.position_of(handle)?;
+        let entry = self.slots[position].as_mut()?;
+        Some(std::mem::replace(&mut entry.child, child))
+    }
+
+    /// The handle of the child with `id`, found by scanning.
+    pub fn handle_of(&self, id: uuid::Uuid) -> Option<ChildHandle> {
+        self.slots.iter().flatten().find(|entry| entry.child.id() == id).map(|entry| ChildHandle {
+            key: entry.key,
+            generation: self.keys[entry.key as usize].generation,
+        })
+    }
+
+    /// Keeps the children `keep` returns true for, in order, and compacts.
+    pub fn retain(&mut self, mut keep: impl FnMut(&Arc<dyn Generator>) -> bool) {
+        for position in 0..self.slots.len() {
+            let drop = matches!(&self.slots[position], Some(entry) if !keep(&entry.child));
+            if drop {
+                if let Some(entry) = self.slots[position].take() {
+                    self.release(entry.key);
+                    self.len -= 1;
+                }
+            }
+        }
+        self.compact();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn Generator>> {
+        self.slots.iter().flatten().map(|entry| &entry.child)
+    }
+
+    pub fn to_vec(&self) -> Vec<Arc<dyn Generator>> {
+        self.iter().cloned().collect()
+    }
+
+    /// The number of slots, holes included; `slot` takes indices below this.
+    pub(crate) fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub(crate) fn slot(&self, index: usize) -> Option<&Arc<dyn Generator>> {
+        self.slots.get(index)?.as_ref().map(|entry| &entry.child)
+    }
+
+    fn position_of(&self, handle: ChildHandle) -> Option<usize> {
+        let key = self.keys.get(handle.key as usize)?;
+        if key.generation != handle.generation {
+            return None;
+        }
+        key.position
+    }
+
+    fn allocate_key(&mut self, position: usize) -> ChildHandle {
+        let key = match self.free.pop() {
+            Some(key) => key,
+            None => {
+                self.keys.push(Key { generation: 0, position: None });
+                (self.keys.len() - 1) as u32
+            }
+        };
+        let slot = &mut self.keys[key as usize];
+        slot.position = Some(position);
+        ChildHandle { key, generation: slot.generation }
+    }
+
+    fn release(&mut self, key: u32) {
+        let slot = &mut self.keys[key as usize];
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.position = None;
+        self.free.push(key);
+    }
+
+    fn compact(&mut self) {
+        if self.slots.len() == self.len {
+            return;
+        }
+        self.slots.retain(Option::is_some);
+        self.reindex(0);
+    }
+
+    fn reindex(&mut self, from: usize) {
+        for (position, entry) in self.slots.iter().enumerate().skip(from) {
+            if let Some(entry) = entry {
+                self.keys[entry.key as usize].position = Some(position);
+            }
+        }
+    }
+}
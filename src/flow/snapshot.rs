@@ -0,0 +1,44 @@
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+use crate::flow::{Generator, GeneratorState};
+
+/// Point-in-time view of a generator and its subtree, suitable for
+/// serving to external dashboards.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeSnapshot {
+    pub id: Uuid,
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub state: GeneratorState,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub timer_remaining: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sequence_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub condition: Option<String>,
+    pub children: Vec<NodeSnapshot>,
+}
+
+pub fn snapshot(generator: &dyn Generator) -> BoxFuture<'_, NodeSnapshot> {
+    Box::pin(async move {
+        let mut node = NodeSnapshot {
+            id: generator.id(),
+            name: generator.name(),
+            kind: generator.type_name().to_string(),
+            state: generator.state(),
+            timer_remaining: None,
+            sequence_index: None,
+            condition: None,
+            children: Vec::new(),
+        };
+        generator.fill_snapshot(&mut node).await;
+
+        for child in generator.children().await {
+            node.children.push(snapshot(child.as_ref()).await);
+        }
+        node
+    })
+}
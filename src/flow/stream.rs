@@ -0,0 +1,158 @@
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tokio::sync::Notify;
+use uuid::Uuid;
+use crate::flow::{Generator, GeneratorBase};
+use crate::{Logger, Result};
+
+/// A multi-value `AsyncFuture`: producers `push` any number of values and
+/// `close` when done. As a generator it completes once closed and drained.
+pub struct AsyncStream<T> {
+    base: GeneratorBase,
+    queue: Mutex<VecDeque<T>>,
+    closed: AtomicBool,
+    notify: Notify,
+}
+
+impl<T: Send + 'static> AsyncStream<T> {
+    pub fn new() -> Self {
+        Self {
+            base: GeneratorBase::new(),
+            queue: Mutex::new(VecDeque::new()),
+            closed: AtomicBool::new(false),
+            notify: Notify::new(),
+        }
+    }
+
+    pub fn with_name(name: impl Into<String>) -> Self {
+        Self {
+            base: GeneratorBase::with_name(name),
+            ..Self::new()
+        }
+    }
+
+    /// Queues `value`; returns it back if the stream is already closed.
+    pub fn push(&self, value: T) -> std::result::Result<(), T> {
+        if self.is_closed() {
+            return Err(value);
+        }
+        self.lock_queue().push_back(value);
+        self.notify.notify_waiters();
+        Ok(())
+    }
+
+    /// No more values will be pushed; queued values can still be read.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+        self.notify.notify_waiters();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock_queue().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock_queue().is_empty()
+    }
+
+    /// The next value without waiting.
+    pub fn try_next(&self) -> Option<T> {
+        self.lock_queue().pop_front()
+    }
+
+    /// The next value, waiting for one to be pushed, or `None` once the
+    /// stream is closed and drained.
+    pub async fn next(&self) -> Option<T> {
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(value) = self.try_next() {
+                return Some(value);
+            }
+            if self.is_closed() {
+                return None;
+            }
+
+            notified.await;
+        }
+    }
+
+    fn lock_queue(&self) -> std::sync::MutexGuard<'_, VecDeque<T>> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T: Send + 'static> Default for AsyncStream<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> Generator for AsyncStream<T> {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        if self.is_closed() && self.is_empty() {
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+}
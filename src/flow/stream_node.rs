@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use futures::stream::{BoxStream, Stream, StreamExt};
+use futures::FutureExt;
+use std::sync::Mutex;
+use uuid::Uuid;
+use crate::flow::{Generator, GeneratorBase, ItemHandler, DEFAULT_BATCH_LIMIT};
+use crate::{Logger, Result};
+
+/// Drives any `futures::Stream` from the flow, handing each item to the
+/// handler and completing when the stream ends. Each step takes the items
+/// that are ready, up to the batch limit, and never waits for more.
+pub struct StreamNode<T> {
+    base: GeneratorBase,
+    stream: Mutex<BoxStream<'static, T>>,
+    handler: ItemHandler<T>,
+    batch_limit: usize,
+}
+
+impl<T: Send + 'static> StreamNode<T> {
+    pub fn new<S, F>(stream: S, handler: F) -> Self
+    where
+        S: Stream<Item = T> + Send + 'static,
+        F: Fn(T) + Send + Sync + 'static,
+    {
+        Self {
+            base: GeneratorBase::new(),
+            stream: Mutex::new(stream.boxed()),
+            handler: Box::new(handler),
+            batch_limit: DEFAULT_BATCH_LIMIT,
+        }
+    }
+
+    pub fn with_name<S, F>(name: impl Into<String>, stream: S, handler: F) -> Self
+    where
+        S: Stream<Item = T> + Send + 'static,
+        F: Fn(T) + Send + Sync + 'static,
+    {
+        let node = Self::new(stream, handler);
+        node.base.set_name(name.into());
+        node
+    }
+
+    /// Caps how many items one step consumes; at least one.
+    pub fn with_batch_limit(mut self, limit: usize) -> Self {
+        self.batch_limit = limit.max(1);
+        self
+    }
+
+    // Takes the ready items; true once the stream has ended.
+    fn poll_ready(&self) -> bool {
+        let mut stream = self.stream.lock().unwrap_or_else(|e| e.into_inner());
+        for _ in 0..self.batch_limit {
+            match stream.next().now_or_never() {
+                Some(Some(item)) => (self.handler)(item),
+                Some(None) => return true,
+                None => return false,
+            }
+        }
+        false
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> Generator for StreamNode<T> {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        if self.poll_ready() {
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+}
@@ -0,0 +1,265 @@
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{set_children_active, ChildList, Generator, GeneratorBase, ParentLink};
+use crate::{flow_now, Logger, Result};
+
+/// Which children a `Supervisor` restarts when one fails, as in Erlang/OTP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Only the failed child.
+    OneForOne,
+    /// Every child, for children that cannot run without each other.
+    OneForAll,
+    /// The failed child and those added after it, for children that depend
+    /// on the ones before them.
+    RestForOne,
+}
+
+type ChildFactory = Box<dyn Fn() -> Arc<dyn Generator> + Send + Sync>;
+
+/// Keeps long-running children alive: each is built by a factory, and when
+/// one fails the supervisor replaces it, along with its siblings as its
+/// `policy` says, with fresh instances. If restarts come faster than the
+/// intensity allows, by default more than one within five seconds of flow
+/// time, it gives up: the children are cancelled and the supervisor fails,
+/// so its own parent can react. Children that complete successfully are
+/// left alone; the supervisor completes once all of them have.
+pub struct Supervisor {
+    base: GeneratorBase,
+    policy: RestartPolicy,
+    max_restarts: usize,
+    period: Duration,
+    factories: Vec<ChildFactory>,
+    // The current instance of each child, in the order they were added.
+    children: Arc<ChildList>,
+    recent_restarts: Mutex<VecDeque<Instant>>,
+    restarts: AtomicU32,
+}
+
+impl Supervisor {
+    pub fn new(policy: RestartPolicy) -> Self {
+        Self::build(GeneratorBase::new(), policy)
+    }
+
+    pub fn with_name(name: impl Into<String>, policy: RestartPolicy) -> Self {
+        Self::build(GeneratorBase::with_name(name), policy)
+    }
+
+    fn build(base: GeneratorBase, policy: RestartPolicy) -> Self {
+        Self {
+            base,
+            policy,
+            max_restarts: 1,
+            period: Duration::from_secs(5),
+            factories: Vec::new(),
+            children: Arc::new(RwLock::new(Vec::new())),
+            recent_restarts: Mutex::new(VecDeque::new()),
+            restarts: AtomicU32::new(0),
+        }
+    }
+
+    /// Allows at most `max_restarts` restarts within any `period`.
+    pub fn intensity(mut self, max_restarts: usize, period: Duration) -> Self {
+        self.max_restarts = max_restarts;
+        self.period = period;
+        self
+    }
+
+    /// Supervises the child `factory` builds, now and on every restart.
+    pub fn child<F>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> Arc<dyn Generator> + Send + Sync + 'static,
+    {
+        let child = factory();
+        child.base().set_parent(Some(self.parent_link()));
+        self.children.try_write().expect("supervisor is not shared yet").push(child);
+        self.factories.push(Box::new(factory));
+        self
+    }
+
+    pub fn policy(&self) -> RestartPolicy {
+        self.policy
+    }
+
+    /// Children restarted so far; a one-for-all restart counts each child.
+    pub fn restarts(&self) -> u32 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+
+    fn parent_link(&self) -> ParentLink {
+        ParentLink::new(&self.base, &self.children)
+    }
+
+    // Which children to restart for the failures, and how many restarts
+    // that counts as against the intensity.
+    fn restart_plan(&self, failed: &[usize], len: usize) -> (Vec<usize>, usize) {
+        match self.policy {
+            RestartPolicy::OneForOne => (failed.to_vec(), failed.len()),
+            RestartPolicy::OneForAll => ((0..len).collect(), 1),
+            RestartPolicy::RestForOne => ((failed[0]..len).collect(), 1),
+        }
+    }
+
+    // Records `restarts` if the intensity allows them.
+    fn allow_restarts(&self, restarts: usize) -> bool {
+        let now = flow_now();
+        let mut recent = self.recent_restarts.lock().unwrap_or_else(|e| e.into_inner());
+        while recent.front().is_some_and(|at| now.duration_since(*at) >= self.period) {
+            recent.pop_front();
+        }
+        if recent.len() + restarts > self.max_restarts {
+            return false;
+        }
+        recent.extend(std::iter::repeat_n(now, restarts));
+        true
+    }
+
+    fn replace(&self, children: &mut [Arc<dyn Generator>], index: usize) {
+        // Detached first, so cancelling it is not seen from above.
+        let old = &children[index];
+        old.base().set_parent(None);
+        if !old.is_completed() {
+            old.cancel();
+            old.deactivate();
+        }
+        let child = (self.factories[index])();
+        child.base().set_parent(Some(self.parent_link()));
+        children[index] = child;
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn give_up(&self, children: &[Arc<dyn Generator>]) {
+        for child in children.iter().filter(|child| !child.is_completed()) {
+            child.cancel();
+            child.deactivate();
+        }
+        let reason = format!("more than {} restarts within {:?}", self.max_restarts, self.period);
+        self.logger().error(format!("Supervisor giving up: {}", reason));
+        self.base.fail(&reason);
+    }
+}
+
+#[async_trait]
+impl Generator for Supervisor {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let children = self.children.read().await;
+        for child in children.iter() {
+            if child.is_active() && child.is_running() && !child.is_completed() {
+                if let Err(e) = child.traced_step().await {
+                    self.logger().error(format!("Supervised child step failed: {}", e));
+                    child.base().step_errored(&e.to_string());
+                }
+            }
+        }
+
+        let failed: Vec<usize> = (0..children.len()).filter(|&index| children[index].is_failed()).collect();
+        if failed.is_empty() {
+            if children.iter().all(|child| child.is_completed()) {
+                self.complete();
+            }
+            return Ok(());
+        }
+        drop(children);
+
+        let mut children = self.children.write().await;
+        let (restart, count) = self.restart_plan(&failed, children.len());
+        if !self.allow_restarts(count) {
+            self.give_up(&children);
+            return Ok(());
+        }
+        for &index in &failed {
+            self.logger().warn(format!("Supervisor restarting failed {}", children[index].base().label()));
+        }
+        for index in restart {
+            self.replace(&mut children, index);
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    // Starts every child afresh from its factory.
+    async fn reset(&self) -> bool {
+        let mut children = self.children.write().await;
+        for index in 0..children.len() {
+            self.replace(&mut children, index);
+        }
+        self.restarts.store(0, Ordering::Relaxed);
+        self.recent_restarts.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        self.base.reset();
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        self.children.read().await.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.children.try_read().ok().map(|children| children.clone())
+    }
+
+    // Cancelled children are not restarted.
+    fn cancel(&self) {
+        for child in self.try_children().unwrap_or_default() {
+            child.cancel();
+        }
+        self.base.cancel();
+    }
+}
@@ -1,60 +1,227 @@
 use async_trait::async_trait;
-use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::{Notify, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
 use uuid::Uuid;
-use crate::flow::{Generator, GeneratorBase};
-use crate::{Logger, Result};
+use crate::flow::{CallbackHandle, CallbackList, FlowCallback, Generator, GeneratorBase, NodeSnapshot};
+use crate::{flow_now, Logger, Result};
+
+// When a timer's current period started, pushed back by however long the
+// timer spends deactivated so that time does not count towards it. Read
+// from `flow_now`, so timers inside a kernel layer follow its clock. A timer
+// is activated and deactivated outside its layer's step, where `flow_now`
+// is the tokio clock, so a pause starts at the last reading taken while
+// stepping and a resume is settled on the next one.
+#[derive(Default)]
+struct PausableClock {
+    started: Option<Instant>,
+    seen: Option<Instant>,
+    paused_at: Option<Instant>,
+    resuming: bool,
+}
+
+impl PausableClock {
+    // Reads the clock from a step, settling a pending resume.
+    fn step(&mut self) -> Instant {
+        let now = flow_now();
+        if std::mem::take(&mut self.resuming) {
+            if let (Some(paused_at), Some(started)) = (self.paused_at.take(), self.started.as_mut()) {
+                *started += now.saturating_duration_since(paused_at);
+            }
+        }
+        self.seen = Some(now);
+        now
+    }
+
+    // As of the last step, so reads from outside the layer agree with it.
+    fn elapsed(&self) -> Option<Duration> {
+        let now = self.paused_at.or(self.seen).unwrap_or_else(flow_now);
+        self.started.map(|started| now.saturating_duration_since(started))
+    }
+
+    fn pause(&mut self) {
+        self.resuming = false;
+        if self.paused_at.is_none() {
+            self.paused_at = Some(self.seen.unwrap_or_else(flow_now));
+        }
+    }
+
+    fn resume(&mut self) {
+        self.resuming = self.paused_at.is_some();
+    }
+}
+
+// Set by a precise timer's sleeping task when its deadline passes.
+#[derive(Default)]
+struct Deadline {
+    fired: AtomicBool,
+    fired_at: Mutex<Option<Instant>>,
+    notify: Notify,
+}
 
 pub struct Timer {
     base: GeneratorBase,
     duration: Duration,
-    start_time: Arc<RwLock<Option<Instant>>>,
-    elapsed_callback: Arc<RwLock<Option<Box<dyn Fn() + Send + Sync>>>>,
+    clock: Mutex<PausableClock>,
+    elapsed_callbacks: Arc<RwLock<CallbackList>>,
+    precise: bool,
+    deadline: Arc<Deadline>,
+    armed: Mutex<Option<JoinHandle<()>>>,
 }
 
 impl Timer {
     pub fn new(duration: Duration) -> Self {
-        Self {
-            base: GeneratorBase::new(),
-            duration,
-            start_time: Arc::new(RwLock::new(None)),
-            elapsed_callback: Arc::new(RwLock::new(None)),
-        }
+        Self::build(GeneratorBase::new(), duration)
     }
 
     pub fn with_name(name: impl Into<String>, duration: Duration) -> Self {
+        Self::build(GeneratorBase::with_name(name), duration)
+    }
+
+    fn build(base: GeneratorBase, duration: Duration) -> Self {
         Self {
-            base: GeneratorBase::with_name(name),
+            base,
             duration,
-            start_time: Arc::new(RwLock::new(None)),
-            elapsed_callback: Arc::new(RwLock::new(None)),
+            clock: Mutex::new(PausableClock::default()),
+            elapsed_callbacks: Arc::new(RwLock::new(CallbackList::new())),
+            precise: false,
+            deadline: Arc::new(Deadline::default()),
+            armed: Mutex::new(None),
+        }
+    }
+
+    /// Arms a tokio `sleep_until` for the deadline on the first step instead
+    /// of checking the clock each step. Elapsed callbacks then fire from the
+    /// sleeping task as soon as the deadline passes, to the tokio timer's
+    /// millisecond resolution rather than whenever the kernel next ticks;
+    /// the timer itself completes on the next step. The deadline runs on the
+    /// tokio clock, so a precise timer ignores layer time scales.
+    pub fn precise(mut self) -> Self {
+        self.precise = true;
+        self
+    }
+
+    pub fn is_precise(&self) -> bool {
+        self.precise
+    }
+
+    /// When a precise timer's deadline actually passed.
+    pub fn fired_at(&self) -> Option<Instant> {
+        *self.deadline.fired_at.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Waits until a precise timer's deadline passes, without waiting for
+    /// the kernel to step it.
+    pub async fn wait_elapsed(&self) {
+        loop {
+            let notified = self.deadline.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.deadline.fired.load(Ordering::Relaxed) {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    // Spawns the sleep for whatever time is left, unless one is pending.
+    fn arm(&self) {
+        let mut armed = self.armed.lock().unwrap_or_else(|e| e.into_inner());
+        if armed.is_some() || self.deadline.fired.load(Ordering::Relaxed) {
+            return;
+        }
+        let remaining = match self.clock().elapsed() {
+            Some(elapsed) => self.duration.saturating_sub(elapsed),
+            None => self.duration,
+        };
+        let (deadline, callbacks) = (self.deadline.clone(), self.elapsed_callbacks.clone());
+        let at = Instant::now() + remaining;
+        *armed = Some(tokio::spawn(async move {
+            tokio::time::sleep_until(at).await;
+            *deadline.fired_at.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+            deadline.fired.store(true, Ordering::Relaxed);
+            deadline.notify.notify_waiters();
+            callbacks.read().await.invoke_all();
+        }));
+    }
+
+    // Cancels a pending sleep; the next step arms a new one.
+    fn disarm(&self) {
+        if let Some(task) = self.armed.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            task.abort();
         }
     }
 
-    pub async fn set_elapsed_callback<F>(&self, callback: F)
+    /// Replaces all registered elapsed callbacks with `callback`.
+    pub async fn set_elapsed_callback<F>(&self, callback: F) -> CallbackHandle
     where
         F: Fn() + Send + Sync + 'static,
     {
-        let mut elapsed_callback = self.elapsed_callback.write().await;
-        *elapsed_callback = Some(Box::new(callback));
+        let mut elapsed_callbacks = self.elapsed_callbacks.write().await;
+        elapsed_callbacks.clear();
+        elapsed_callbacks.add(FlowCallback::from_sync(callback))
+    }
+
+    /// Replaces all registered elapsed callbacks with an async `callback`.
+    pub async fn set_elapsed_callback_async<F, Fut>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut elapsed_callbacks = self.elapsed_callbacks.write().await;
+        elapsed_callbacks.clear();
+        elapsed_callbacks.add(FlowCallback::from_async(callback))
+    }
+
+    pub async fn add_elapsed_callback<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let mut elapsed_callbacks = self.elapsed_callbacks.write().await;
+        elapsed_callbacks.add(FlowCallback::from_sync(callback))
+    }
+
+    pub async fn add_elapsed_callback_async<F, Fut>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut elapsed_callbacks = self.elapsed_callbacks.write().await;
+        elapsed_callbacks.add(FlowCallback::from_async(callback))
+    }
+
+    pub async fn remove_elapsed_callback(&self, handle: CallbackHandle) -> bool {
+        let mut elapsed_callbacks = self.elapsed_callbacks.write().await;
+        elapsed_callbacks.remove(handle)
     }
 
     pub async fn is_elapsed(&self) -> bool {
-        let start_time = self.start_time.read().await;
-        if let Some(start) = *start_time {
-            start.elapsed() >= self.duration
-        } else {
-            false
+        if self.precise {
+            return self.deadline.fired.load(Ordering::Relaxed);
         }
+        self.clock().elapsed().is_some_and(|elapsed| elapsed >= self.duration)
     }
 
-    async fn start_if_needed(&self) {
-        let mut start_time = self.start_time.write().await;
-        if start_time.is_none() {
-            *start_time = Some(Instant::now());
+    pub async fn remaining(&self) -> Duration {
+        match self.clock().elapsed() {
+            Some(elapsed) => self.duration.saturating_sub(elapsed),
+            None => self.duration,
         }
     }
+
+    async fn start_if_needed(&self) {
+        let mut clock = self.clock();
+        let now = clock.step();
+        clock.started.get_or_insert(now);
+    }
+
+    fn clock(&self) -> MutexGuard<'_, PausableClock> {
+        self.clock.lock().unwrap_or_else(|e| e.into_inner())
+    }
 }
 
 #[async_trait]
@@ -63,11 +230,11 @@ impl Generator for Timer {
         self.base.id()
     }
 
-    fn name(&self) -> Option<&str> {
+    fn name(&self) -> Option<String> {
         self.base.name()
     }
 
-    fn set_name(&mut self, name: String) {
+    fn set_name(&self, name: String) {
         self.base.set_name(name);
     }
 
@@ -85,14 +252,18 @@ impl Generator for Timer {
 
     fn activate(&self) {
         self.base.activate();
+        self.clock().resume();
     }
 
     fn deactivate(&self) {
         self.base.deactivate();
+        self.clock().pause();
+        self.disarm();
     }
 
     fn complete(&self) {
         self.base.complete();
+        self.disarm();
     }
 
     async fn step(&self) -> Result<()> {
@@ -100,13 +271,22 @@ impl Generator for Timer {
             return Ok(());
         }
 
+        self.base.start();
+
         self.start_if_needed().await;
 
-        if self.is_elapsed().await {
-            let elapsed_callback = self.elapsed_callback.read().await;
-            if let Some(ref callback) = *elapsed_callback {
-                callback();
+        if self.precise {
+            if self.is_elapsed().await {
+                self.complete();
+            } else {
+                self.arm();
             }
+            return Ok(());
+        }
+
+        if self.is_elapsed().await {
+            let elapsed_callbacks = self.elapsed_callbacks.read().await;
+            elapsed_callbacks.invoke_all();
             self.complete();
         }
 
@@ -116,13 +296,44 @@ impl Generator for Timer {
     fn logger(&self) -> &Logger {
         self.base.logger()
     }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        self.disarm();
+        *self.clock() = PausableClock::default();
+        self.deadline.fired.store(false, Ordering::Relaxed);
+        self.deadline.fired_at.lock().unwrap_or_else(|e| e.into_inner()).take();
+        self.base.reset();
+        true
+    }
+
+    async fn fill_snapshot(&self, snapshot: &mut NodeSnapshot) {
+        snapshot.timer_remaining = Some(self.remaining().await);
+    }
+
+    // A timer that had not started yet is left to start on its next step.
+    async fn restore_snapshot(&self, snapshot: &NodeSnapshot) {
+        if let Some(remaining) = snapshot.timer_remaining.filter(|remaining| *remaining < self.duration) {
+            let now = flow_now();
+            self.clock().started = Some(now.checked_sub(self.duration - remaining).unwrap_or(now));
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.disarm();
+    }
 }
 
 pub struct PeriodicTimer {
     base: GeneratorBase,
     interval: Duration,
-    last_trigger: Arc<RwLock<Option<Instant>>>,
-    elapsed_callback: Arc<RwLock<Option<Box<dyn Fn() + Send + Sync>>>>,
+    clock: Mutex<PausableClock>,
+    elapsed_callbacks: Arc<RwLock<CallbackList>>,
 }
 
 impl PeriodicTimer {
@@ -130,8 +341,8 @@ impl PeriodicTimer {
         Self {
             base: GeneratorBase::new(),
             interval,
-            last_trigger: Arc::new(RwLock::new(None)),
-            elapsed_callback: Arc::new(RwLock::new(None)),
+            clock: Mutex::new(PausableClock::default()),
+            elapsed_callbacks: Arc::new(RwLock::new(CallbackList::new())),
         }
     }
 
@@ -139,31 +350,71 @@ impl PeriodicTimer {
         Self {
             base: GeneratorBase::with_name(name),
             interval,
-            last_trigger: Arc::new(RwLock::new(None)),
-            elapsed_callback: Arc::new(RwLock::new(None)),
+            clock: Mutex::new(PausableClock::default()),
+            elapsed_callbacks: Arc::new(RwLock::new(CallbackList::new())),
         }
     }
 
-    pub async fn set_elapsed_callback<F>(&self, callback: F)
+    /// Replaces all registered elapsed callbacks with `callback`.
+    pub async fn set_elapsed_callback<F>(&self, callback: F) -> CallbackHandle
     where
         F: Fn() + Send + Sync + 'static,
     {
-        let mut elapsed_callback = self.elapsed_callback.write().await;
-        *elapsed_callback = Some(Box::new(callback));
+        let mut elapsed_callbacks = self.elapsed_callbacks.write().await;
+        elapsed_callbacks.clear();
+        elapsed_callbacks.add(FlowCallback::from_sync(callback))
     }
 
-    async fn should_trigger(&self) -> bool {
-        let last_trigger = self.last_trigger.read().await;
-        if let Some(last) = *last_trigger {
-            last.elapsed() >= self.interval
-        } else {
-            true
+    /// Replaces all registered elapsed callbacks with an async `callback`.
+    pub async fn set_elapsed_callback_async<F, Fut>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut elapsed_callbacks = self.elapsed_callbacks.write().await;
+        elapsed_callbacks.clear();
+        elapsed_callbacks.add(FlowCallback::from_async(callback))
+    }
+
+    pub async fn add_elapsed_callback<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let mut elapsed_callbacks = self.elapsed_callbacks.write().await;
+        elapsed_callbacks.add(FlowCallback::from_sync(callback))
+    }
+
+    pub async fn add_elapsed_callback_async<F, Fut>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut elapsed_callbacks = self.elapsed_callbacks.write().await;
+        elapsed_callbacks.add(FlowCallback::from_async(callback))
+    }
+
+    pub async fn remove_elapsed_callback(&self, handle: CallbackHandle) -> bool {
+        let mut elapsed_callbacks = self.elapsed_callbacks.write().await;
+        elapsed_callbacks.remove(handle)
+    }
+
+    pub async fn remaining(&self) -> Duration {
+        match self.clock().elapsed() {
+            Some(since_trigger) => self.interval.saturating_sub(since_trigger),
+            None => Duration::ZERO,
         }
     }
 
+    async fn should_trigger(&self) -> bool {
+        self.clock().elapsed().is_none_or(|since_trigger| since_trigger >= self.interval)
+    }
+
     async fn trigger(&self) {
-        let mut last_trigger = self.last_trigger.write().await;
-        *last_trigger = Some(Instant::now());
+        self.clock().started = Some(flow_now());
+    }
+
+    fn clock(&self) -> MutexGuard<'_, PausableClock> {
+        self.clock.lock().unwrap_or_else(|e| e.into_inner())
     }
 }
 
@@ -173,11 +424,11 @@ impl Generator for PeriodicTimer {
         self.base.id()
     }
 
-    fn name(&self) -> Option<&str> {
+    fn name(&self) -> Option<String> {
         self.base.name()
     }
 
-    fn set_name(&mut self, name: String) {
+    fn set_name(&self, name: String) {
         self.base.set_name(name);
     }
 
@@ -195,10 +446,12 @@ impl Generator for PeriodicTimer {
 
     fn activate(&self) {
         self.base.activate();
+        self.clock().resume();
     }
 
     fn deactivate(&self) {
         self.base.deactivate();
+        self.clock().pause();
     }
 
     fn complete(&self) {
@@ -210,11 +463,12 @@ impl Generator for PeriodicTimer {
             return Ok(());
         }
 
+        self.base.start();
+
+        self.clock().step();
         if self.should_trigger().await {
-            let elapsed_callback = self.elapsed_callback.read().await;
-            if let Some(ref callback) = *elapsed_callback {
-                callback();
-            }
+            let elapsed_callbacks = self.elapsed_callbacks.read().await;
+            elapsed_callbacks.invoke_all();
             self.trigger().await;
         }
 
@@ -224,4 +478,26 @@ impl Generator for PeriodicTimer {
     fn logger(&self) -> &Logger {
         self.base.logger()
     }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        *self.clock() = PausableClock::default();
+        self.base.reset();
+        true
+    }
+
+    async fn fill_snapshot(&self, snapshot: &mut NodeSnapshot) {
+        snapshot.timer_remaining = Some(self.remaining().await);
+    }
+
+    async fn restore_snapshot(&self, snapshot: &NodeSnapshot) {
+        if let Some(remaining) = snapshot.timer_remaining.filter(|remaining| !remaining.is_zero()) {
+            let since_trigger = self.interval.saturating_sub(remaining);
+            let now = flow_now();
+            self.clock().started = Some(now.checked_sub(since_trigger).unwrap_or(now));
+        }
+    }
 }
\ No newline at end of file
@@ -1,15 +1,22 @@
 use async_trait::async_trait;
-use std::sync::Arc;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::RwLock;
+use tokio::time::Instant;
 use uuid::Uuid;
-use crate::flow::{Generator, GeneratorBase};
-use crate::{Logger, Result};
+use crate::flow::{CallbackHandle, CallbackList, FlowCallback, Generator, GeneratorBase, NodeSnapshot};
+use crate::{flow_now, Logger, Result};
 
 pub struct Trigger {
     base: GeneratorBase,
     condition: Arc<RwLock<Box<dyn Fn() -> bool + Send + Sync>>>,
-    triggered_callback: Arc<RwLock<Option<Box<dyn Fn() + Send + Sync>>>>,
+    triggered_callbacks: Arc<RwLock<CallbackList>>,
     triggered: Arc<RwLock<bool>>,
+    condition_name: std::sync::RwLock<String>,
+    held_for: Option<Duration>,
+    // Since when the condition has held without a break.
+    holding_since: Mutex<Option<Instant>>,
 }
 
 impl Trigger {
@@ -17,32 +24,97 @@ impl Trigger {
     where
         F: Fn() -> bool + Send + Sync + 'static,
     {
-        Self {
-            base: GeneratorBase::new(),
-            condition: Arc::new(RwLock::new(Box::new(condition))),
-            triggered_callback: Arc::new(RwLock::new(None)),
-            triggered: Arc::new(RwLock::new(false)),
-        }
+        Self::build(GeneratorBase::new(), condition)
     }
 
     pub fn with_name<F>(name: impl Into<String>, condition: F) -> Self
+    where
+        F: Fn() -> bool + Send + Sync + 'static,
+    {
+        Self::build(GeneratorBase::with_name(name), condition)
+    }
+
+    fn build<F>(base: GeneratorBase, condition: F) -> Self
     where
         F: Fn() -> bool + Send + Sync + 'static,
     {
         Self {
-            base: GeneratorBase::with_name(name),
+            base,
             condition: Arc::new(RwLock::new(Box::new(condition))),
-            triggered_callback: Arc::new(RwLock::new(None)),
+            triggered_callbacks: Arc::new(RwLock::new(CallbackList::new())),
             triggered: Arc::new(RwLock::new(false)),
+            condition_name: std::sync::RwLock::new(std::any::type_name::<F>().to_string()),
+            held_for: None,
+            holding_since: Mutex::new(None),
         }
     }
 
-    pub async fn set_triggered_callback<F>(&self, callback: F)
+    /// Describes the condition in stall diagnostics and snapshots. Defaults
+    /// to the closure's type name, which names the enclosing function.
+    pub fn with_condition_name(self, name: impl Into<String>) -> Self {
+        *self.condition_name.write().unwrap_or_else(|e| e.into_inner()) = name.into();
+        self
+    }
+
+    pub fn condition_name(&self) -> String {
+        self.condition_name.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Fires only once the condition has held on every step for `duration`
+    /// of flow time, starting over whenever it is false, so "unhealthy for
+    /// 30 seconds" does not fire on a single failed check.
+    pub fn held_for(mut self, duration: Duration) -> Self {
+        self.held_for = Some(duration);
+        self
+    }
+
+    /// How long the condition has held without a break, as of the last step.
+    pub fn holding_for(&self) -> Option<Duration> {
+        let holding_since = *self.holding_since.lock().unwrap_or_else(|e| e.into_inner());
+        holding_since.map(|since| flow_now().saturating_duration_since(since))
+    }
+
+    /// Replaces all registered triggered callbacks with `callback`.
+    pub async fn set_triggered_callback<F>(&self, callback: F) -> CallbackHandle
     where
         F: Fn() + Send + Sync + 'static,
     {
-        let mut triggered_callback = self.triggered_callback.write().await;
-        *triggered_callback = Some(Box::new(callback));
+        let mut triggered_callbacks = self.triggered_callbacks.write().await;
+        triggered_callbacks.clear();
+        triggered_callbacks.add(FlowCallback::from_sync(callback))
+    }
+
+    /// Replaces all registered triggered callbacks with an async `callback`.
+    pub async fn set_triggered_callback_async<F, Fut>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut triggered_callbacks = self.triggered_callbacks.write().await;
+        triggered_callbacks.clear();
+        triggered_callbacks.add(FlowCallback::from_async(callback))
+    }
+
+    pub async fn add_triggered_callback<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let mut triggered_callbacks = self.triggered_callbacks.write().await;
+        triggered_callbacks.add(FlowCallback::from_sync(callback))
+    }
+
+    pub async fn add_triggered_callback_async<F, Fut>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut triggered_callbacks = self.triggered_callbacks.write().await;
+        triggered_callbacks.add(FlowCallback::from_async(callback))
+    }
+
+    pub async fn remove_triggered_callback(&self, handle: CallbackHandle) -> bool {
+        let mut triggered_callbacks = self.triggered_callbacks.write().await;
+        triggered_callbacks.remove(handle)
     }
 
     pub async fn is_triggered(&self) -> bool {
@@ -54,6 +126,20 @@ impl Trigger {
         condition()
     }
 
+    // Whether a condition that `holds` now has held for long enough.
+    fn sustained(&self, holds: bool) -> bool {
+        let Some(held_for) = self.held_for else {
+            return holds;
+        };
+        let mut holding_since = self.holding_since.lock().unwrap_or_else(|e| e.into_inner());
+        if !holds {
+            *holding_since = None;
+            return false;
+        }
+        let now = flow_now();
+        now.saturating_duration_since(*holding_since.get_or_insert(now)) >= held_for
+    }
+
     async fn trigger(&self) {
         let mut triggered = self.triggered.write().await;
         *triggered = true;
@@ -66,11 +152,11 @@ impl Generator for Trigger {
         self.base.id()
     }
 
-    fn name(&self) -> Option<&str> {
+    fn name(&self) -> Option<String> {
         self.base.name()
     }
 
-    fn set_name(&mut self, name: String) {
+    fn set_name(&self, name: String) {
         self.base.set_name(name);
     }
 
@@ -90,8 +176,10 @@ impl Generator for Trigger {
         self.base.activate();
     }
 
+    // A held condition starts over, as it is not checked while inactive.
     fn deactivate(&self) {
         self.base.deactivate();
+        *self.holding_since.lock().unwrap_or_else(|e| e.into_inner()) = None;
     }
 
     fn complete(&self) {
@@ -103,13 +191,21 @@ impl Generator for Trigger {
             return Ok(());
         }
 
-        if self.check_condition().await {
+        self.base.start();
+
+        let fired = match self.base.ask_observers(|observer| observer.override_trigger(&self.base)) {
+            Some(fired) => fired,
+            None => {
+                let holds = self.check_condition().await;
+                self.sustained(holds)
+            }
+        };
+        if fired {
             if !self.is_triggered().await {
-                let triggered_callback = self.triggered_callback.read().await;
-                if let Some(ref callback) = *triggered_callback {
-                    callback();
-                }
+                let triggered_callbacks = self.triggered_callbacks.read().await;
+                triggered_callbacks.invoke_all();
                 self.trigger().await;
+                self.base.notify(|observer| observer.on_triggered(&self.base));
             }
             self.complete();
         }
@@ -120,4 +216,19 @@ impl Generator for Trigger {
     fn logger(&self) -> &Logger {
         self.base.logger()
     }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        *self.triggered.write().await = false;
+        *self.holding_since.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.base.reset();
+        true
+    }
+
+    async fn fill_snapshot(&self, snapshot: &mut NodeSnapshot) {
+        snapshot.condition = Some(self.condition_name());
+    }
 }
\ No newline at end of file
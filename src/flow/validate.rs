@@ -0,0 +1,40 @@
+use futures::future::BoxFuture;
+use std::collections::HashSet;
+use uuid::Uuid;
+use crate::flow::Generator;
+use crate::Result;
+
+/// Checks the tree below `generator` for cycles, which would step forever,
+/// and for generators shared between parents, which would step twice a tick.
+pub async fn validate(generator: &dyn Generator) -> Result<()> {
+    let mut path = vec![generator.id()];
+    let mut seen = HashSet::from([generator.id()]);
+    check(generator, &mut path, &mut seen).await
+}
+
+fn check<'a>(
+    generator: &'a dyn Generator,
+    path: &'a mut Vec<Uuid>,
+    seen: &'a mut HashSet<Uuid>,
+) -> BoxFuture<'a, Result<()>> {
+    Box::pin(async move {
+        for child in generator.children().await {
+            let id = child.id();
+            if path.contains(&id) {
+                return Err(format!(
+                    "cycle: {} is its own ancestor via {}",
+                    child.base().label(),
+                    generator.base().label(),
+                )
+                .into());
+            }
+            if !seen.insert(id) {
+                return Err(format!("{} appears more than once in the tree", child.base().label()).into());
+            }
+            path.push(id);
+            check(child.as_ref(), path, seen).await?;
+            path.pop();
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use crate::flow::{Generator, GeneratorState};
+
+#[derive(Debug, Clone)]
+pub struct VisitInfo {
+    pub depth: usize,
+    pub path: String,
+    pub state: GeneratorState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitControl {
+    Continue,
+    SkipChildren,
+    Stop,
+}
+
+pub trait FlowVisitor: Send {
+    fn enter(&mut self, generator: &dyn Generator, info: &VisitInfo) -> VisitControl;
+
+    fn leave(&mut self, _generator: &dyn Generator, _info: &VisitInfo) {}
+}
+
+/// The name of a generator, or a short form of its id when it is unnamed.
+pub fn label(generator: &dyn Generator) -> String {
+    generator.base().label()
+}
+
+enum Step {
+    Enter(Arc<dyn Generator>, VisitInfo),
+    Leave(Arc<dyn Generator>, VisitInfo),
+}
+
+/// Depth-first traversal of `root` and its subtree. `leave` is called for
+/// every entered generator once its children have been visited.
+pub async fn walk(root: &dyn Generator, visitor: &mut dyn FlowVisitor) {
+    let info = VisitInfo {
+        depth: 0,
+        path: label(root),
+        state: root.state(),
+    };
+
+    match visitor.enter(root, &info) {
+        VisitControl::Stop => return,
+        VisitControl::SkipChildren => {
+            visitor.leave(root, &info);
+            return;
+        }
+        VisitControl::Continue => {}
+    }
+
+    let mut stack = Vec::new();
+    push_children(&mut stack, root.children().await, &info);
+
+    while let Some(step) = stack.pop() {
+        match step {
+            Step::Enter(generator, info) => match visitor.enter(generator.as_ref(), &info) {
+                VisitControl::Stop => return,
+                VisitControl::SkipChildren => visitor.leave(generator.as_ref(), &info),
+                VisitControl::Continue => {
+                    let children = generator.children().await;
+                    stack.push(Step::Leave(generator, info.clone()));
+                    push_children(&mut stack, children, &info);
+                }
+            },
+            Step::Leave(generator, info) => visitor.leave(generator.as_ref(), &info),
+        }
+    }
+
+    visitor.leave(root, &info);
+}
+
+fn push_children(stack: &mut Vec<Step>, children: Vec<Arc<dyn Generator>>, parent: &VisitInfo) {
+    for child in children.into_iter().rev() {
+        let info = VisitInfo {
+            depth: parent.depth + 1,
+            path: format!("{}/{}", parent.path, label(child.as_ref())),
+            state: child.state(),
+        };
+        stack.push(Step::Enter(child, info));
+    }
+}
@@ -0,0 +1,238 @@
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{set_children_active, Callback, ChildList, Generator, GeneratorBase, GeneratorState, LifecycleObserver, ParentLink};
+use crate::{flow_now, Logger, Result};
+
+// Counts lifecycle events anywhere below the generator it is registered on.
+#[derive(Default)]
+pub(crate) struct ProgressCounter {
+    events: AtomicU64,
+}
+
+impl ProgressCounter {
+    pub(crate) fn bump(&self) {
+        self.events.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        self.events.load(Ordering::Relaxed)
+    }
+}
+
+impl LifecycleObserver for ProgressCounter {
+    fn on_added(&self, _generator: &GeneratorBase) {
+        self.bump();
+    }
+
+    fn on_start(&self, _generator: &GeneratorBase) {
+        self.bump();
+    }
+
+    fn on_complete(&self, _generator: &GeneratorBase) {
+        self.bump();
+    }
+
+    fn on_fail(&self, _generator: &GeneratorBase, _reason: &str) {
+        self.bump();
+    }
+
+    fn on_triggered(&self, _generator: &GeneratorBase) {
+        self.bump();
+    }
+}
+
+/// Wraps a generator and raises the alarm if it makes no observable progress
+/// within `timeout`. Progress is any start, completion, failure, trigger or
+/// added child anywhere in the wrapped subtree, or an explicit `feed`.
+/// Without a stall callback the watchdog fails and stops the wrapped
+/// generator; with one, the callback fires and the countdown restarts.
+/// Completes, or fails, along with the wrapped generator.
+pub struct Watchdog {
+    base: GeneratorBase,
+    inner: Arc<dyn Generator>,
+    slot: Arc<ChildList>,
+    timeout: Duration,
+    progress: Arc<ProgressCounter>,
+    last_seen: Mutex<Option<(u64, GeneratorState, Instant)>>,
+    on_stall: Mutex<Option<Callback>>,
+    stalls: AtomicUsize,
+}
+
+impl Watchdog {
+    pub fn new(inner: Arc<dyn Generator>, timeout: Duration) -> Self {
+        Self::build(GeneratorBase::new(), inner, timeout)
+    }
+
+    pub fn with_name(name: impl Into<String>, inner: Arc<dyn Generator>, timeout: Duration) -> Self {
+        Self::build(GeneratorBase::with_name(name), inner, timeout)
+    }
+
+    fn build(base: GeneratorBase, inner: Arc<dyn Generator>, timeout: Duration) -> Self {
+        let progress = Arc::new(ProgressCounter::default());
+        base.add_observer(progress.clone());
+        let slot = Arc::new(RwLock::new(vec![inner.clone()]));
+        inner.base().set_parent(Some(ParentLink::new(&base, &slot)));
+        Self {
+            base,
+            inner,
+            slot,
+            timeout,
+            progress,
+            last_seen: Mutex::new(None),
+            on_stall: Mutex::new(None),
+            stalls: AtomicUsize::new(0),
+        }
+    }
+
+    /// Calls `callback` on each stall instead of failing.
+    pub fn on_stall<F>(self, callback: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        *self.on_stall.lock().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(callback));
+        self
+    }
+
+    /// Counts as progress, for wrapped work that advances without lifecycle
+    /// events, such as a long-running coroutine reporting in.
+    pub fn feed(&self) {
+        self.progress.bump();
+    }
+
+    pub fn inner(&self) -> &Arc<dyn Generator> {
+        &self.inner
+    }
+
+    pub fn stalls(&self) -> usize {
+        self.stalls.load(Ordering::Relaxed)
+    }
+
+    // True when the wrapped generator has shown no progress for `timeout`.
+    fn stalled(&self) -> bool {
+        let now = flow_now();
+        let seen = (self.progress.count(), self.inner.state());
+        let mut last_seen = self.last_seen.lock().unwrap_or_else(|e| e.into_inner());
+        match *last_seen {
+            Some((events, state, since)) if (events, state) == seen => {
+                if now.duration_since(since) < self.timeout {
+                    return false;
+                }
+                *last_seen = Some((seen.0, seen.1, now));
+                true
+            }
+            _ => {
+                *last_seen = Some((seen.0, seen.1, now));
+                false
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Generator for Watchdog {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    // The countdown starts afresh once reactivated, as time spent inactive
+    // is no fault of the wrapped generator.
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+        *self.last_seen.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        if self.inner.is_active() && self.inner.is_running() && !self.inner.is_completed() {
+            if let Err(e) = self.inner.traced_step().await {
+                self.logger().error(format!("Watched generator step failed: {}", e));
+                self.inner.base().step_errored(&e.to_string());
+            }
+        }
+
+        if self.inner.is_failed() {
+            self.base.fail(&format!("{} failed", self.inner.base().label()));
+        } else if self.inner.is_completed() {
+            self.complete();
+        } else if self.stalled() {
+            self.stalls.fetch_add(1, Ordering::Relaxed);
+            let callback = self.on_stall.lock().unwrap_or_else(|e| e.into_inner()).clone();
+            match callback {
+                Some(callback) => callback(),
+                None => {
+                    let reason = format!("{} made no progress for {:?}", self.inner.base().label(), self.timeout);
+                    self.logger().error(format!("Watchdog: {}", reason));
+                    self.inner.deactivate();
+                    self.base.fail(&reason);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn reset(&self) -> bool {
+        if !self.inner.reset().await {
+            return false;
+        }
+        *self.last_seen.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.base.reset();
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        self.slot.read().await.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.slot.try_read().ok().map(|slot| slot.clone())
+    }
+}
@@ -0,0 +1,256 @@
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{Generator, GeneratorBase};
+use crate::{flow_now, Logger, Result};
+
+const MIN_SPAN: Duration = Duration::from_millis(1);
+
+/// How a `Window` cuts time into windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowKind {
+    /// Back-to-back windows of this length.
+    Tumbling(Duration),
+    /// Windows of `size` starting every `slide`, overlapping when `slide`
+    /// is shorter.
+    Sliding { size: Duration, slide: Duration },
+}
+
+impl WindowKind {
+    fn size(&self) -> Duration {
+        match *self {
+            WindowKind::Tumbling(size) | WindowKind::Sliding { size, .. } => size.max(MIN_SPAN),
+        }
+    }
+
+    fn slide(&self) -> Duration {
+        match *self {
+            WindowKind::Tumbling(size) => size.max(MIN_SPAN),
+            WindowKind::Sliding { slide, .. } => slide.max(MIN_SPAN),
+        }
+    }
+}
+
+struct Incoming<T> {
+    items: Vec<T>,
+    closed: bool,
+}
+
+/// The push side of a `Window`; clone it freely across producers.
+pub struct WindowHandle<T> {
+    incoming: Arc<Mutex<Incoming<T>>>,
+}
+
+impl<T> WindowHandle<T> {
+    /// Queues `item` for the window open at the next step; returns it back
+    /// once closed.
+    pub fn push(&self, item: T) -> std::result::Result<(), T> {
+        let mut incoming = self.incoming.lock().unwrap_or_else(|e| e.into_inner());
+        if incoming.closed {
+            return Err(item);
+        }
+        incoming.items.push(item);
+        Ok(())
+    }
+
+    /// No more items; the windows still holding some are handed over early.
+    pub fn close(&self) {
+        self.incoming.lock().unwrap_or_else(|e| e.into_inner()).closed = true;
+    }
+}
+
+impl<T> Clone for WindowHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            incoming: self.incoming.clone(),
+        }
+    }
+}
+
+type WindowHandler<T> = Box<dyn Fn(Range<Duration>, &[T]) + Send + Sync>;
+
+struct Timeline<T> {
+    started: Option<Instant>,
+    // When each item was taken in, since `started`, oldest first; the items
+    // themselves are kept apart so a window's share is one slice.
+    stamps: VecDeque<Duration>,
+    items: VecDeque<T>,
+    next_start: Duration,
+}
+
+/// Groups pushed items into tumbling or sliding windows of flow time, and
+/// calls `handler` with each window's span, counted from the first step,
+/// and its items once the window has passed. Items are stamped when the
+/// window takes them in on its step, so kernel time scaling and pauses
+/// apply. Empty windows are handed over too. Completes once the handle is
+/// closed and the last item has been handed over.
+pub struct Window<T> {
+    base: GeneratorBase,
+    kind: WindowKind,
+    incoming: Arc<Mutex<Incoming<T>>>,
+    timeline: Mutex<Timeline<T>>,
+    handler: WindowHandler<T>,
+    windows: AtomicUsize,
+}
+
+impl<T: Send + 'static> Window<T> {
+    pub fn tumbling<F>(size: Duration, handler: F) -> Self
+    where
+        F: Fn(Range<Duration>, &[T]) + Send + Sync + 'static,
+    {
+        Self::new(WindowKind::Tumbling(size), handler)
+    }
+
+    pub fn sliding<F>(size: Duration, slide: Duration, handler: F) -> Self
+    where
+        F: Fn(Range<Duration>, &[T]) + Send + Sync + 'static,
+    {
+        Self::new(WindowKind::Sliding { size, slide }, handler)
+    }
+
+    pub fn new<F>(kind: WindowKind, handler: F) -> Self
+    where
+        F: Fn(Range<Duration>, &[T]) + Send + Sync + 'static,
+    {
+        Self {
+            base: GeneratorBase::new(),
+            kind,
+            incoming: Arc::new(Mutex::new(Incoming {
+                items: Vec::new(),
+                closed: false,
+            })),
+            timeline: Mutex::new(Timeline {
+                started: None,
+                stamps: VecDeque::new(),
+                items: VecDeque::new(),
+                next_start: Duration::ZERO,
+            }),
+            handler: Box::new(handler),
+            windows: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn handle(&self) -> WindowHandle<T> {
+        WindowHandle {
+            incoming: self.incoming.clone(),
+        }
+    }
+
+    pub fn kind(&self) -> WindowKind {
+        self.kind
+    }
+
+    /// Windows handed to the handler so far.
+    pub fn windows(&self) -> usize {
+        self.windows.load(Ordering::Relaxed)
+    }
+
+    /// Items not yet in a handed-over window, or still due in a later one.
+    pub fn pending(&self) -> usize {
+        let incoming = self.incoming.lock().unwrap_or_else(|e| e.into_inner()).items.len();
+        incoming + self.timeline.lock().unwrap_or_else(|e| e.into_inner()).items.len()
+    }
+
+    // Hands over every window ending by `now`, or every one still holding
+    // items once `closed`. Returns whether everything has been handed over.
+    fn emit(&self, timeline: &mut Timeline<T>, now: Duration, closed: bool) -> bool {
+        let (size, slide) = (self.kind.size(), self.kind.slide());
+        loop {
+            let start = timeline.next_start;
+            let end = start + size;
+            let leftovers = closed && !timeline.items.is_empty();
+            if end > now && !leftovers {
+                return closed && timeline.items.is_empty();
+            }
+
+            // Older items are gone, so the window's share leads the queue.
+            let count = timeline.stamps.iter().take_while(|at| **at < end).count();
+            (self.handler)(start..end, &timeline.items.make_contiguous()[..count]);
+            self.windows.fetch_add(1, Ordering::Relaxed);
+
+            timeline.next_start = start + slide;
+            while timeline.stamps.front().is_some_and(|at| *at < timeline.next_start) {
+                timeline.stamps.pop_front();
+                timeline.items.pop_front();
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> Generator for Window<T> {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let (arrived, closed) = {
+            let mut incoming = self.incoming.lock().unwrap_or_else(|e| e.into_inner());
+            (std::mem::take(&mut incoming.items), incoming.closed)
+        };
+        let now = flow_now();
+        let finished = {
+            let mut timeline = self.timeline.lock().unwrap_or_else(|e| e.into_inner());
+            let since_start = now.saturating_duration_since(*timeline.started.get_or_insert(now));
+            timeline.stamps.extend(std::iter::repeat_n(since_start, arrived.len()));
+            timeline.items.extend(arrived);
+            self.emit(&mut timeline, since_start, closed)
+        };
+        if finished {
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+}
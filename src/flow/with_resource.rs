@@ -0,0 +1,342 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
+use tokio::sync::{OwnedSemaphorePermit, RwLock, Semaphore};
+use uuid::Uuid;
+use crate::flow::{set_children_active, ChildList, Generator, GeneratorBase, ParentLink};
+use crate::{current_resources, Logger, Result};
+
+/// A named, fixed number of leases shared by `WithResource` nodes, such as
+/// database connections or GPU slots. Clones share the same leases.
+#[derive(Clone)]
+pub struct LeasePool {
+    name: Arc<str>,
+    capacity: usize,
+    slots: Arc<Semaphore>,
+}
+
+impl LeasePool {
+    pub fn new(name: impl Into<String>, capacity: usize) -> Self {
+        Self {
+            name: name.into().into(),
+            capacity,
+            slots: Arc::new(Semaphore::new(capacity)),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Leases not currently held.
+    pub fn available(&self) -> usize {
+        self.slots.available_permits()
+    }
+}
+
+// The single-lease pools behind `Exclusive`, one per lock name. Each kernel
+// keeps its own table in its resources; generators stepped outside a kernel
+// share a process-wide one.
+#[derive(Default)]
+struct LockTable {
+    locks: Mutex<HashMap<String, LeasePool>>,
+}
+
+static UNSCOPED_LOCKS: LazyLock<LockTable> = LazyLock::new(LockTable::default);
+
+impl LockTable {
+    fn lock(&self, name: &str) -> LeasePool {
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        locks.entry(name.to_string()).or_insert_with(|| LeasePool::new(name, 1)).clone()
+    }
+}
+
+fn named_lock(name: &str) -> LeasePool {
+    match current_resources() {
+        Some(resources) => resources.get_or_insert_with(LockTable::default).lock(name),
+        None => UNSCOPED_LOCKS.lock(name),
+    }
+}
+
+/// Holds a lease from a `LeasePool` while its child runs: the child is not
+/// stepped until a lease is free, and the lease goes back to the pool as soon
+/// as the child completes or fails, or the node is cancelled or reset.
+/// Completes, or fails, along with the child. As with `Limited`, only lazily
+/// started work, such as `AsyncCoroutine::lazy`, is actually held back.
+pub struct WithResource {
+    base: GeneratorBase,
+    inner: Arc<dyn Generator>,
+    slot: Arc<ChildList>,
+    // Set on construction, or on the first step for a named lock.
+    pool: OnceLock<LeasePool>,
+    lock_name: Option<String>,
+    lease: Mutex<Option<OwnedSemaphorePermit>>,
+}
+
+impl WithResource {
+    pub fn new(pool: &LeasePool, inner: Arc<dyn Generator>) -> Self {
+        Self::build(GeneratorBase::new(), Some(pool), None, inner)
+    }
+
+    pub fn with_name(name: impl Into<String>, pool: &LeasePool, inner: Arc<dyn Generator>) -> Self {
+        Self::build(GeneratorBase::with_name(name), Some(pool), None, inner)
+    }
+
+    fn build(
+        base: GeneratorBase,
+        pool: Option<&LeasePool>,
+        lock_name: Option<String>,
+        inner: Arc<dyn Generator>,
+    ) -> Self {
+        let slot = Arc::new(RwLock::new(vec![inner.clone()]));
+        inner.base().set_parent(Some(ParentLink::new(&base, &slot)));
+        Self {
+            base,
+            inner,
+            slot,
+            pool: pool.cloned().map(OnceLock::from).unwrap_or_default(),
+            lock_name,
+            lease: Mutex::new(None),
+        }
+    }
+
+    pub fn inner(&self) -> &Arc<dyn Generator> {
+        &self.inner
+    }
+
+    /// The pool leases come from; for a named lock, known from the first step.
+    pub fn pool(&self) -> Option<&LeasePool> {
+        self.pool.get()
+    }
+
+    fn resolve_pool(&self) -> &LeasePool {
+        self.pool.get_or_init(|| named_lock(self.lock_name.as_deref().unwrap_or_default()))
+    }
+
+    pub fn holds_lease(&self) -> bool {
+        self.lease.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+    }
+
+    // Takes a lease if this node has none; false while the pool is exhausted.
+    fn acquire(&self) -> bool {
+        let mut lease = self.lease.lock().unwrap_or_else(|e| e.into_inner());
+        if lease.is_none() {
+            match self.resolve_pool().slots.clone().try_acquire_owned() {
+                Ok(permit) => *lease = Some(permit),
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+
+    fn release(&self) {
+        self.lease.lock().unwrap_or_else(|e| e.into_inner()).take();
+    }
+}
+
+#[async_trait]
+impl Generator for WithResource {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+        set_children_active(self, true);
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        set_children_active(self, false);
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        if !self.inner.is_completed() {
+            if !self.acquire() {
+                self.logger().verbose_with(4, || format!("Waiting for a lease from {}", self.resolve_pool().name()));
+                return Ok(());
+            }
+            if self.inner.is_active() && self.inner.is_running() {
+                if let Err(e) = self.inner.traced_step().await {
+                    self.logger().error(format!("Leased generator step failed: {}", e));
+                    self.inner.base().step_errored(&e.to_string());
+                }
+            }
+        }
+
+        if self.inner.is_completed() {
+            self.release();
+            if self.inner.is_failed() {
+                self.base.fail(&format!("{} failed", self.inner.base().label()));
+            } else {
+                self.complete();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    fn cancel(&self) {
+        self.inner.cancel();
+        self.release();
+    }
+
+    async fn reset(&self) -> bool {
+        if !self.inner.reset().await {
+            return false;
+        }
+        self.release();
+        self.base.reset();
+        true
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        self.slot.read().await.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.slot.try_read().ok().map(|slot| slot.clone())
+    }
+}
+
+/// Runs its child only while holding the lock called `lock_name`, so at most
+/// one subtree per lock name runs at a time across the whole kernel, for
+/// flows sharing a device or file. Otherwise behaves like `WithResource`.
+pub struct Exclusive {
+    inner: WithResource,
+}
+
+impl Exclusive {
+    pub fn new(lock_name: impl Into<String>, inner: Arc<dyn Generator>) -> Self {
+        Self {
+            inner: WithResource::build(GeneratorBase::new(), None, Some(lock_name.into()), inner),
+        }
+    }
+
+    pub fn with_name(name: impl Into<String>, lock_name: impl Into<String>, inner: Arc<dyn Generator>) -> Self {
+        Self {
+            inner: WithResource::build(GeneratorBase::with_name(name), None, Some(lock_name.into()), inner),
+        }
+    }
+
+    pub fn lock_name(&self) -> &str {
+        self.inner.lock_name.as_deref().unwrap_or_default()
+    }
+
+    pub fn inner(&self) -> &Arc<dyn Generator> {
+        self.inner.inner()
+    }
+
+    pub fn holds_lock(&self) -> bool {
+        self.inner.holds_lease()
+    }
+}
+
+#[async_trait]
+impl Generator for Exclusive {
+    fn id(&self) -> Uuid {
+        self.inner.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.inner.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.inner.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.inner.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.inner.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.inner.is_completed()
+    }
+
+    fn activate(&self) {
+        self.inner.activate();
+    }
+
+    fn deactivate(&self) {
+        self.inner.deactivate();
+    }
+
+    fn complete(&self) {
+        self.inner.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        self.inner.step().await
+    }
+
+    fn logger(&self) -> &Logger {
+        self.inner.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        self.inner.base()
+    }
+
+    fn cancel(&self) {
+        self.inner.cancel();
+    }
+
+    async fn reset(&self) -> bool {
+        self.inner.reset().await
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        self.inner.children().await
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.inner.try_children()
+    }
+}
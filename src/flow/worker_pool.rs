@@ -0,0 +1,208 @@
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::Receiver;
+use tokio::task::JoinSet;
+use tracing::Instrument;
+use uuid::Uuid;
+use crate::flow::{task_span, Generator, GeneratorBase};
+use crate::{Logger, Result};
+
+enum JobSource<J> {
+    Channel(Receiver<J>),
+    Function(Box<dyn FnMut() -> Option<J> + Send>),
+}
+
+enum NextJob<J> {
+    Ready(J),
+    Waiting,
+    Drained,
+}
+
+impl<J> JobSource<J> {
+    fn next(&mut self) -> NextJob<J> {
+        match self {
+            JobSource::Channel(receiver) => match receiver.try_recv() {
+                Ok(job) => NextJob::Ready(job),
+                Err(TryRecvError::Empty) => NextJob::Waiting,
+                Err(TryRecvError::Disconnected) => NextJob::Drained,
+            },
+            JobSource::Function(next) => next().map_or(NextJob::Drained, NextJob::Ready),
+        }
+    }
+}
+
+type JobHandler<J> = Box<dyn Fn(J) -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+/// Pulls jobs from a channel or closure and runs each on its own task, with
+/// at most `workers` in flight. Completes once the source is drained and
+/// every job has finished. A failing job is logged and counted but does not
+/// fail the pool.
+pub struct WorkerPool<J> {
+    base: GeneratorBase,
+    workers: usize,
+    source: Mutex<JobSource<J>>,
+    handler: JobHandler<J>,
+    tasks: Mutex<JoinSet<Result<()>>>,
+    drained: AtomicBool,
+    completed_jobs: AtomicUsize,
+    failed_jobs: AtomicUsize,
+}
+
+impl<J: Send + 'static> WorkerPool<J> {
+    /// Jobs sent on `receiver`; the pool drains once every sender is dropped.
+    pub fn from_channel<F, Fut>(workers: usize, receiver: Receiver<J>, handler: F) -> Self
+    where
+        F: Fn(J) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self::with_source(workers, JobSource::Channel(receiver), handler)
+    }
+
+    /// Jobs produced by `next_job`; the pool drains once it returns `None`.
+    pub fn from_fn<N, F, Fut>(workers: usize, next_job: N, handler: F) -> Self
+    where
+        N: FnMut() -> Option<J> + Send + 'static,
+        F: Fn(J) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self::with_source(workers, JobSource::Function(Box::new(next_job)), handler)
+    }
+
+    fn with_source<F, Fut>(workers: usize, source: JobSource<J>, handler: F) -> Self
+    where
+        F: Fn(J) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self {
+            base: GeneratorBase::new(),
+            workers: workers.max(1),
+            source: Mutex::new(source),
+            handler: Box::new(move |job| Box::pin(handler(job))),
+            tasks: Mutex::new(JoinSet::new()),
+            drained: AtomicBool::new(false),
+            completed_jobs: AtomicUsize::new(0),
+            failed_jobs: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn completed_jobs(&self) -> usize {
+        self.completed_jobs.load(Ordering::Relaxed)
+    }
+
+    pub fn failed_jobs(&self) -> usize {
+        self.failed_jobs.load(Ordering::Relaxed)
+    }
+
+    fn reap(&self, tasks: &mut JoinSet<Result<()>>) {
+        while let Some(outcome) = tasks.try_join_next() {
+            let error = match outcome {
+                Ok(Ok(())) => {
+                    self.completed_jobs.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+                Ok(Err(e)) => e.to_string(),
+                Err(e) => e.to_string(),
+            };
+            self.failed_jobs.fetch_add(1, Ordering::Relaxed);
+            self.logger().error(format!("Worker pool job failed: {}", error));
+        }
+    }
+
+    fn fill(&self, tasks: &mut JoinSet<Result<()>>) {
+        let mut source = self.source.lock().unwrap_or_else(|e| e.into_inner());
+        while !self.drained.load(Ordering::Relaxed) && tasks.len() < self.workers {
+            match source.next() {
+                NextJob::Ready(job) => {
+                    tasks.spawn((self.handler)(job).instrument(task_span(&self.base)));
+                }
+                NextJob::Waiting => break,
+                NextJob::Drained => self.drained.store(true, Ordering::Relaxed),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<J: Send + 'static> Generator for WorkerPool<J> {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    fn activate(&self) {
+        self.base.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let finished = {
+            let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+            self.reap(&mut tasks);
+            self.fill(&mut tasks);
+            self.drained.load(Ordering::Relaxed) && tasks.is_empty()
+        };
+        if finished {
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    fn cancel(&self) {
+        self.drained.store(true, Ordering::Relaxed);
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner()).abort_all();
+        self.base.cancel();
+    }
+}
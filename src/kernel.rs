@@ -1,29 +1,90 @@
 use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use std::fmt;
+use std::future::{Future, IntoFuture};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::JoinHandle;
 use tokio::time::{sleep, Instant};
 use uuid::Uuid;
-use crate::flow::{Generator, GeneratorBase, Node};
-use crate::{Logger, TimeFrame, Result};
-
+use crate::flow::{
+    render_tree_now, set_children_active, ErrorLog, EventBroadcaster, FlowEvent, Generator, GeneratorBase, GeneratorState,
+    ChildList, LifecycleObserver, Node, NodeSnapshot, NodeError, ParentLink, ProgressCounter, StalledNode,
+};
+use crate::profile::{self, Profiler};
+use crate::resources::{self, Resources};
+use crate::schedule::{self, SharedRng};
+use crate::{restore_tree, FlowCheckpoint, FlowRecorder, NodeRegistry, SeededRng};
+use crate::{with_layer_time, with_time_frame, CaptureSink, FlowError, ProfileReport, KernelLayer, LogEntry, LogSink, Logger, TimeFrame, Result};
+
+/// Clones share the whole kernel, lifecycle included, so completing or
+/// breaking any clone ends a loop run from another, such as `start`'s.
 #[derive(Clone)]
 pub struct AsyncKernel {
-    base: GeneratorBase,
+    base: Arc<GeneratorBase>,
     root: Arc<Node>,
     time_frame: Arc<RwLock<TimeFrame>>,
     break_flag: Arc<RwLock<bool>>,
     wait_until: Arc<RwLock<Option<Instant>>>,
+    events: broadcast::Sender<FlowEvent>,
+    recorder: Arc<RwLock<Option<Arc<FlowRecorder>>>>,
+    scheduler: Arc<RwLock<Option<(u64, SharedRng)>>>,
+    // Only filled while checkpointing, as it grows with every named root
+    // child that finishes.
+    checkpointing: Arc<AtomicBool>,
+    finished: Arc<RwLock<Vec<String>>>,
+    progress: Arc<ProgressCounter>,
+    errors: Arc<ErrorLog>,
+    stall: Arc<std::sync::Mutex<StallWatch>>,
+    layers: Arc<std::sync::RwLock<Vec<Arc<KernelLayer>>>>,
+    layer_nodes: Arc<ChildList>,
+    capture: Arc<std::sync::RwLock<Option<Arc<CaptureSink>>>>,
+    profiler: Arc<std::sync::RwLock<Option<Arc<Profiler>>>>,
+    resources: Resources,
+}
+
+// When the tree last showed progress, for stall diagnostics.
+struct StallWatch {
+    timeout: Option<Duration>,
+    events: u64,
+    since: Instant,
 }
 
 impl AsyncKernel {
     pub fn new() -> Self {
+        let (events, _) = broadcast::channel(256);
+        let root = Arc::new(Node::with_name("Root"));
+        root.base().add_observer(Arc::new(EventBroadcaster::new(events.clone())));
+        let progress = Arc::new(ProgressCounter::default());
+        root.base().add_observer(progress.clone());
+        let errors = Arc::new(ErrorLog::default());
+        root.base().add_observer(errors.clone());
         Self {
-            base: GeneratorBase::with_name("AsyncKernel"),
-            root: Arc::new(Node::with_name("Root")),
+            base: Arc::new(GeneratorBase::with_name("AsyncKernel")),
+            root,
             time_frame: Arc::new(RwLock::new(TimeFrame::new())),
             break_flag: Arc::new(RwLock::new(false)),
             wait_until: Arc::new(RwLock::new(None)),
+            events,
+            recorder: Arc::new(RwLock::new(None)),
+            scheduler: Arc::new(RwLock::new(None)),
+            checkpointing: Arc::new(AtomicBool::new(false)),
+            finished: Arc::new(RwLock::new(Vec::new())),
+            progress,
+            errors,
+            stall: Arc::new(std::sync::Mutex::new(StallWatch {
+                timeout: None,
+                events: 0,
+                since: Instant::now(),
+            })),
+            layers: Arc::new(std::sync::RwLock::new(Vec::new())),
+            layer_nodes: Arc::new(RwLock::new(Vec::new())),
+            capture: Arc::new(std::sync::RwLock::new(None)),
+            profiler: Arc::new(std::sync::RwLock::new(None)),
+            resources: Resources::new(),
         }
     }
 
@@ -31,14 +92,394 @@ impl AsyncKernel {
         self.root.clone()
     }
 
+    /// The layer called `name`, created after the existing ones if there is
+    /// none yet. Layers report to the kernel's observers and event stream
+    /// like the root does.
+    pub async fn layer(&self, name: &str) -> Arc<KernelLayer> {
+        if let Some(layer) = self.find_layer(name) {
+            return layer;
+        }
+        let mut layer_nodes = self.layer_nodes.write().await;
+        if let Some(layer) = self.find_layer(name) {
+            return layer;
+        }
+        let layer = Arc::new(KernelLayer::new(name));
+        let node = layer.node();
+        node.base().set_parent(Some(ParentLink::new(self.root.base(), &self.layer_nodes)));
+        layer_nodes.push(node);
+        self.layers.write().unwrap_or_else(|e| e.into_inner()).push(layer.clone());
+        layer
+    }
+
+    /// Every failure and step error in the tree since the last call, oldest
+    /// first. Failures are recorded whether or not their container carries on;
+    /// cancelled nodes, such as the losers of a `first_of`, are not.
+    pub fn take_errors(&self) -> Vec<NodeError> {
+        self.errors.take()
+    }
+
+    /// Caps how many errors are kept between `take_errors` calls, dropping
+    /// the oldest beyond it. Defaults to `DEFAULT_ERROR_CAPACITY`; 0 keeps
+    /// none, leaving only the error handler.
+    pub fn set_error_capacity(&self, capacity: usize) {
+        self.errors.set_capacity(capacity);
+    }
+
+    /// How many errors have been dropped over the capacity so far.
+    pub fn dropped_errors(&self) -> u64 {
+        self.errors.dropped()
+    }
+
+    /// Calls `handler` with the node path and error of every failure anywhere
+    /// in the tree, as it happens, for central alerting or retry decisions.
+    pub fn set_error_handler<F>(&self, handler: F)
+    where
+        F: Fn(&str, &FlowError) + Send + Sync + 'static,
+    {
+        self.errors.set_handler(Some(Arc::new(handler)));
+    }
+
+    pub fn clear_error_handler(&self) {
+        self.errors.set_handler(None);
+    }
+
+    /// Times every generator step from the next update on; turning it off
+    /// discards what was recorded.
+    pub fn set_profiling(&self, enabled: bool) {
+        let mut profiler = self.profiler.write().unwrap_or_else(|e| e.into_inner());
+        match (enabled, profiler.is_some()) {
+            (true, false) => *profiler = Some(Arc::new(Profiler::default())),
+            (false, _) => *profiler = None,
+            _ => {}
+        }
+    }
+
+    pub fn is_profiling(&self) -> bool {
+        self.profiler().is_some()
+    }
+
+    /// Step durations per node path, busiest first; empty unless profiling.
+    pub fn profile_report(&self) -> ProfileReport {
+        self.profiler().map(|profiler| profiler.report()).unwrap_or_default()
+    }
+
+    /// Starts the profile over without turning profiling off.
+    pub fn reset_profile(&self) {
+        if let Some(profiler) = self.profiler() {
+            profiler.clear();
+        }
+    }
+
+    fn profiler(&self) -> Option<Arc<Profiler>> {
+        self.profiler.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Registers a shared service for everything this kernel steps, replacing
+    /// any earlier `T`. Lazy coroutines read it with `ctx.resource::<T>()`,
+    /// trigger conditions with `async_flow::resource::<T>()`.
+    pub fn insert_resource<T: Send + Sync + 'static>(&self, value: T) {
+        self.resources.insert(value);
+    }
+
+    pub fn resource<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.resources.get()
+    }
+
+    pub fn remove_resource<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.resources.remove()
+    }
+
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    /// Layers in the order they step.
+    pub fn layers(&self) -> Vec<Arc<KernelLayer>> {
+        self.layers.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn find_layer(&self, name: &str) -> Option<Arc<KernelLayer>> {
+        self.layers().into_iter().find(|layer| layer.name() == name)
+    }
+
+    /// True once neither the root nor any layer has work left.
+    pub async fn is_empty(&self) -> bool {
+        if self.root.child_count().await > 0 {
+            return false;
+        }
+        for layer in self.layers() {
+            if layer.node().child_count().await > 0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Routes the logs of the kernel and everything in it, layers and nested
+    /// kernels included, to `sink` instead of `tracing`.
+    pub fn set_log_sink(&self, sink: Arc<dyn LogSink>) {
+        self.install_sink(Some(sink));
+        *self.capture.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    pub fn clear_log_sink(&self) {
+        self.install_sink(None);
+        *self.capture.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Replaces the log sink with a fresh `CaptureSink`, read back with
+    /// `captured_logs`.
+    pub fn capture_logs(&self) -> Arc<CaptureSink> {
+        let capture = Arc::new(CaptureSink::new());
+        self.install_sink(Some(capture.clone()));
+        *self.capture.write().unwrap_or_else(|e| e.into_inner()) = Some(capture.clone());
+        capture
+    }
+
+    /// Everything logged since `capture_logs`; empty when not capturing.
+    pub fn captured_logs(&self) -> Vec<LogEntry> {
+        let capture = self.capture.read().unwrap_or_else(|e| e.into_inner()).clone();
+        capture.map(|capture| capture.entries()).unwrap_or_default()
+    }
+
+    fn install_sink(&self, sink: Option<Arc<dyn LogSink>>) {
+        self.base.logger().set_sink(sink.clone());
+        self.root.logger().set_sink(sink);
+    }
+
+    /// Lifecycle events for every generator in the flow, from now on.
+    /// Slow receivers see `RecvError::Lagged` rather than blocking the kernel.
+    pub fn subscribe(&self) -> broadcast::Receiver<FlowEvent> {
+        self.events.subscribe()
+    }
+
+    /// Records every following tick into `recorder`, replacing any previous one.
+    pub async fn record(&self, recorder: Arc<FlowRecorder>) {
+        let mut current = self.recorder.write().await;
+        if let Some(previous) = current.take() {
+            let previous: Arc<dyn LifecycleObserver> = previous;
+            self.remove_observer(&previous);
+        }
+        self.add_observer(recorder.clone());
+        *current = Some(recorder);
+    }
+
+    pub async fn stop_recording(&self) -> Option<Arc<FlowRecorder>> {
+        let recorder = self.recorder.write().await.take()?;
+        let observer: Arc<dyn LifecycleObserver> = recorder.clone();
+        self.remove_observer(&observer);
+        Some(recorder)
+    }
+
+    /// Steps parallel children in an order shuffled by `seed`; the same seed
+    /// reproduces the same order. The seed is logged so failures can be rerun.
+    pub async fn set_seed(&self, seed: u64) {
+        self.logger().info(format!("Deterministic scheduling seed: {}", seed));
+        let rng = Arc::new(std::sync::Mutex::new(SeededRng::new(seed)));
+        *self.scheduler.write().await = Some((seed, rng));
+    }
+
+    /// Seeds from `ASYNC_FLOW_SEED` when set, otherwise from the clock.
+    pub async fn seed_from_env(&self) -> u64 {
+        let seed = std::env::var("ASYNC_FLOW_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(SeededRng::entropy_seed);
+        self.set_seed(seed).await;
+        seed
+    }
+
+    pub async fn seed(&self) -> Option<u64> {
+        self.scheduler.read().await.as_ref().map(|(seed, _)| *seed)
+    }
+
+    pub async fn clear_seed(&self) {
+        *self.scheduler.write().await = None;
+    }
+
+    /// Reports a stall when nothing in the tree, layers included, has
+    /// started, completed, failed or fired for `timeout`: a warning listing
+    /// the generators still running, and `FlowEvent::Stalled` on the event stream. Repeats every
+    /// `timeout` while the stall lasts. Off by default.
+    pub fn set_stall_timeout(&self, timeout: Option<Duration>) {
+        let mut stall = self.stall.lock().unwrap_or_else(|e| e.into_inner());
+        stall.timeout = timeout;
+        stall.since = Instant::now();
+    }
+
+    async fn check_stall(&self) {
+        let idle = {
+            let mut stall = self.stall.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(timeout) = stall.timeout else { return };
+            let events = self.progress.count();
+            let now = Instant::now();
+            if events != stall.events {
+                stall.events = events;
+                stall.since = now;
+                return;
+            }
+            let idle = now.duration_since(stall.since);
+            if idle < timeout {
+                return;
+            }
+            stall.since = now;
+            idle
+        };
+        let mut roots = vec![self.root.clone()];
+        roots.extend(self.layers().iter().filter(|layer| !layer.is_paused()).map(|layer| layer.node()));
+        let mut running = Vec::new();
+        let mut empty = true;
+        for root in &roots {
+            empty &= root.child_count().await == 0;
+            collect_running(&root.snapshot().await, &mut running);
+        }
+        if empty {
+            return;
+        }
+
+        let mut report = format!("Flow stalled: no progress for {:?}; still running:", idle);
+        for node in &running {
+            report.push_str(&format!("\n  {} {}", node.kind, node.name.as_deref().unwrap_or("(unnamed)")));
+            if let Some(condition) = &node.condition {
+                report.push_str(&format!(" waiting on {}", condition));
+            }
+        }
+        self.logger().warn(report);
+        let _ = self.events.send(FlowEvent::Stalled { idle, running });
+    }
+
+    /// Prunes finished generators from every `Node` in the tree; see
+    /// `Generator::prune`.
+    pub async fn prune(&self) -> usize {
+        let mut removed = self.root.prune().await;
+        for layer in self.layers() {
+            removed += layer.node().prune().await;
+        }
+        removed
+    }
+
+    /// Starts or stops remembering which named root children finished, so
+    /// `checkpoint` can tell they already ran. Off by default; `restore`
+    /// turns it on. Turning it off forgets them.
+    pub async fn set_checkpointing(&self, enabled: bool) {
+        self.checkpointing.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.finished.write().await.clear();
+        }
+    }
+
+    pub fn is_checkpointing(&self) -> bool {
+        self.checkpointing.load(Ordering::Relaxed)
+    }
+
+    /// The flow's progress so far, for `restore` after a restart. Finished
+    /// root children are only included while `set_checkpointing` is on.
+    pub async fn checkpoint(&self) -> FlowCheckpoint {
+        FlowCheckpoint {
+            tree: self.root.snapshot().await,
+            finished: self.finished.read().await.clone(),
+        }
+    }
+
+    /// Fast-forwards the tree to `checkpoint`. Root children the checkpoint
+    /// saw that the tree lacks are built from `registry` by name, so a
+    /// restarted process can start from an empty root or a partial one.
+    /// Returns how many generators were restored.
+    pub async fn restore(&self, checkpoint: &FlowCheckpoint, registry: &NodeRegistry) -> Result<usize> {
+        let mut restored = 0;
+        let mut finished = checkpoint.finished.clone();
+        for child in self.root.children().await {
+            let Some(name) = child.name() else { continue };
+            if let Some(position) = finished.iter().position(|done| *done == name) {
+                finished.remove(position);
+                child.base().restore_state(GeneratorState::Completed);
+                restored += 1;
+            }
+        }
+        *self.finished.write().await = checkpoint.finished.clone();
+        self.checkpointing.store(true, Ordering::Relaxed);
+
+        let tree = &checkpoint.tree;
+        self.root.base().restore_state(tree.state);
+        self.root.restore_snapshot(tree).await;
+        restored += 1;
+        let mut unmatched = self.root.children().await;
+        for saved in &tree.children {
+            let matches = |child: &Arc<dyn Generator>| child.type_name() == saved.kind && child.name() == saved.name;
+            let child = match unmatched.iter().position(matches) {
+                Some(index) => unmatched.remove(index),
+                None => {
+                    let built = saved.name.as_deref().and_then(|name| registry.build(name)).filter(matches);
+                    let built = built.ok_or_else(|| {
+                        format!(
+                            "checkpoint node {} {} is not in the tree or the registry",
+                            saved.kind,
+                            saved.name.as_deref().unwrap_or("(unnamed)"),
+                        )
+                    })?;
+                    self.root.add_child(built.clone()).await;
+                    built
+                }
+            };
+            restored += restore_tree(child.as_ref(), saved).await?;
+        }
+        self.root.clear_completed().await;
+        Ok(restored)
+    }
+
+    async fn step_layers(&self, rng: Option<SharedRng>) -> Result<()> {
+        let delta = self.time_frame.read().await.delta;
+        for layer in self.layers() {
+            if layer.is_paused() {
+                continue;
+            }
+            let now = layer.advance(delta);
+            let node = layer.node();
+            let step = schedule::scope(rng.clone(), node.traced_step());
+            let step = with_layer_time(now, profile::scope(self.profiler(), step));
+            with_time_frame(layer.time_frame(), step).await?;
+            node.clear_completed().await;
+        }
+        Ok(())
+    }
+
+    // Remembers named root children before they are cleared, so checkpoints
+    // can tell they already ran.
+    async fn remember_finished(&self) {
+        if !self.is_checkpointing() {
+            return;
+        }
+        let done = self.root.completed_names().await;
+        if !done.is_empty() {
+            self.finished.write().await.extend(done);
+        }
+    }
+
+    /// Observes the lifecycle of every generator in the flow.
+    pub fn add_observer(&self, observer: Arc<dyn LifecycleObserver>) {
+        self.root.base().add_observer(observer);
+    }
+
+    pub fn remove_observer(&self, observer: &Arc<dyn LifecycleObserver>) -> bool {
+        self.root.base().remove_observer(observer)
+    }
+
     pub async fn time_frame(&self) -> TimeFrame {
         let time_frame = self.time_frame.read().await;
         time_frame.clone()
     }
 
+    /// Stops the flow and cancels work still running in it; see
+    /// `Generator::cancel`.
     pub async fn break_flow(&self) {
         let mut break_flag = self.break_flag.write().await;
         *break_flag = true;
+        drop(break_flag);
+        let _ = self.events.send(FlowEvent::KernelBreak);
+        self.root.cancel();
+        for layer in self.layers() {
+            layer.node().cancel();
+        }
     }
 
     pub async fn is_breaking(&self) -> bool {
@@ -71,16 +512,27 @@ impl AsyncKernel {
             time_frame.update_with_delta(delta_time);
         }
 
-        self.step().await
+        let result = self.step().await;
+        self.end_recorded_tick(delta_time).await;
+        result
     }
 
     pub async fn update_real_time(&self) -> Result<()> {
-        {
+        let delta_time = {
             let mut time_frame = self.time_frame.write().await;
             time_frame.update();
-        }
+            time_frame.delta
+        };
 
-        self.step().await
+        let result = self.step().await;
+        self.end_recorded_tick(delta_time).await;
+        result
+    }
+
+    async fn end_recorded_tick(&self, delta_time: Duration) {
+        if let Some(recorder) = self.recorder.read().await.as_ref() {
+            recorder.end_tick(delta_time);
+        }
     }
 
     pub async fn run_until_complete(&self) -> Result<()> {
@@ -92,7 +544,7 @@ impl AsyncKernel {
 
             self.update_real_time().await?;
             
-            if self.root.child_count().await == 0 {
+            if self.is_empty().await {
                 break;
             }
 
@@ -121,6 +573,126 @@ impl AsyncKernel {
         
         Ok(())
     }
+
+    /// Runs in real time until `done` returns true, checking before every
+    /// tick, e.g. until a particular node completes. Also stops if the flow
+    /// is broken or stopped; unlike `run_until_complete`, an empty tree keeps
+    /// ticking, since `done` may be waiting on something outside it.
+    pub async fn run_until<'a, F, Fut>(&'a self, mut done: F) -> Result<()>
+    where
+        F: FnMut(&'a AsyncKernel) -> Fut,
+        Fut: Future<Output = bool> + 'a,
+    {
+        while self.is_running() && !self.is_breaking().await {
+            if done(self).await {
+                break;
+            }
+
+            if self.is_waiting().await {
+                sleep(Duration::from_millis(1)).await;
+                continue;
+            }
+
+            self.update_real_time().await?;
+            sleep(Duration::from_millis(1)).await;
+        }
+
+        Ok(())
+    }
+
+    /// Runs the flow on a spawned task, like `run_until_complete`, so a
+    /// service can own the loop next to its other subsystems. Dropping the
+    /// handle leaves the loop running.
+    pub fn start(&self) -> KernelHandle {
+        let stopping = Arc::new(AtomicBool::new(false));
+        let (kernel, stop) = (self.clone(), stopping.clone());
+        let task = tokio::spawn(async move {
+            let mut ticked = false;
+            kernel
+                .run_until(|kernel| {
+                    let stopped = stop.load(Ordering::Relaxed);
+                    let first = !std::mem::replace(&mut ticked, true);
+                    async move { stopped || (!first && kernel.is_empty().await) }
+                })
+                .await
+        });
+        KernelHandle { stopping, task }
+    }
+
+    /// The kernel's run loop as a stream: each item is one real-time tick,
+    /// yielding the clock it ran at. Ticks happen only as the stream is
+    /// polled, at most one per millisecond, so a consumer can rate-limit them
+    /// or merge the flow into a `select!` loop. Ends like `run_until_complete`,
+    /// including when this kernel or a clone of it is completed, or after
+    /// yielding a step error.
+    pub fn tick_stream(&self) -> BoxStream<'static, Result<TimeFrame>> {
+        stream::unfold((self.clone(), true), |(kernel, first)| async move {
+            if !first {
+                if kernel.is_empty().await {
+                    return None;
+                }
+                sleep(Duration::from_millis(1)).await;
+            }
+            while kernel.is_waiting().await {
+                sleep(Duration::from_millis(1)).await;
+            }
+            if !kernel.is_running() || kernel.is_breaking().await {
+                return None;
+            }
+            match kernel.update_real_time().await {
+                Ok(()) => {
+                    let frame = kernel.time_frame().await;
+                    Some((Ok(frame), (kernel, false)))
+                }
+                Err(e) => Some((Err(e), (kernel, false))),
+            }
+        })
+        .scan(false, |failed, tick| {
+            let item = (!*failed).then(|| {
+                *failed = tick.is_err();
+                tick
+            });
+            async move { item }
+        })
+        .boxed()
+    }
+
+    /// Runs in real time for as long as `keep_going` returns true; the
+    /// inverse of `run_until`.
+    pub async fn run_while<'a, F, Fut>(&'a self, mut keep_going: F) -> Result<()>
+    where
+        F: FnMut(&'a AsyncKernel) -> Fut,
+        Fut: Future<Output = bool> + 'a,
+    {
+        self.run_until(move |kernel| {
+            let keep_going = keep_going(kernel);
+            async move { !keep_going.await }
+        })
+        .await
+    }
+}
+
+/// Controls a kernel loop started with `AsyncKernel::start`.
+pub struct KernelHandle {
+    stopping: Arc<AtomicBool>,
+    task: JoinHandle<Result<()>>,
+}
+
+impl KernelHandle {
+    /// Asks the loop to finish after the current tick. Unlike `break_flow`,
+    /// running coroutines are left alone.
+    pub fn stop(&self) {
+        self.stopping.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.task.is_finished()
+    }
+
+    /// Waits for the loop to finish, returning the first step error, if any.
+    pub async fn join(self) -> Result<()> {
+        self.task.await?
+    }
 }
 
 impl Default for AsyncKernel {
@@ -129,17 +701,57 @@ impl Default for AsyncKernel {
     }
 }
 
+fn collect_running(node: &NodeSnapshot, running: &mut Vec<StalledNode>) {
+    for child in &node.children {
+        if child.state == GeneratorState::Running {
+            running.push(StalledNode {
+                id: child.id,
+                name: child.name.clone(),
+                kind: child.kind.clone(),
+                condition: child.condition.clone(),
+            });
+        }
+        collect_running(child, running);
+    }
+}
+
+/// `kernel.await` runs the flow like `run_until_complete`, so a kernel can
+/// sit in `tokio::select!` or `join!` next to other application futures.
+impl IntoFuture for AsyncKernel {
+    type Output = Result<()>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move { self.run_until_complete().await })
+    }
+}
+
+impl<'a> IntoFuture for &'a AsyncKernel {
+    type Output = Result<()>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.run_until_complete())
+    }
+}
+
+impl fmt::Display for AsyncKernel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&render_tree_now(self))
+    }
+}
+
 #[async_trait]
 impl Generator for AsyncKernel {
     fn id(&self) -> Uuid {
         self.base.id()
     }
 
-    fn name(&self) -> Option<&str> {
+    fn name(&self) -> Option<String> {
         self.base.name()
     }
 
-    fn set_name(&mut self, name: String) {
+    fn set_name(&self, name: String) {
         self.base.set_name(name);
     }
 
@@ -157,10 +769,12 @@ impl Generator for AsyncKernel {
 
     fn activate(&self) {
         self.base.activate();
+        set_children_active(self, true);
     }
 
     fn deactivate(&self) {
         self.base.deactivate();
+        set_children_active(self, false);
     }
 
     fn complete(&self) {
@@ -172,6 +786,8 @@ impl Generator for AsyncKernel {
             return Ok(());
         }
 
+        self.base.start();
+
         if self.is_breaking().await {
             return Ok(());
         }
@@ -180,13 +796,21 @@ impl Generator for AsyncKernel {
             return Ok(());
         }
 
-        let child_count = self.root.child_count().await;
-        if child_count > 0 {
-            self.logger().verbose(4, format!("Stepping kernel with {} root children", child_count));
+        if self.logger().verbosity() >= 4 {
+            let child_count = self.root.child_count().await;
+            if child_count > 0 {
+                self.logger().verbose(4, format!("Stepping kernel with {} root children", child_count));
+            }
         }
 
-        self.root.step().await?;
+        let rng = self.scheduler.read().await.as_ref().map(|(_, rng)| rng.clone());
+        let step = schedule::scope(rng.clone(), self.root.traced_step());
+        let step = with_time_frame(self.time_frame().await, profile::scope(self.profiler(), step));
+        resources::scope(self.resources.clone(), step).await?;
+        self.remember_finished().await;
         self.root.clear_completed().await;
+        resources::scope(self.resources.clone(), self.step_layers(rng)).await?;
+        self.check_stall().await;
 
         Ok(())
     }
@@ -194,4 +818,20 @@ impl Generator for AsyncKernel {
     fn logger(&self) -> &Logger {
         self.base.logger()
     }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        let mut children: Vec<Arc<dyn Generator>> = vec![self.root.clone()];
+        children.extend(self.layer_nodes.read().await.iter().cloned());
+        children
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        let mut children: Vec<Arc<dyn Generator>> = vec![self.root.clone()];
+        children.extend(self.layer_nodes.try_read().ok()?.iter().cloned());
+        Some(children)
+    }
 }
\ No newline at end of file
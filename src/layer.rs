@@ -0,0 +1,81 @@
+//! Named layers of a kernel, such as "ui", "simulation" and "network". Each
+//! has its own root node, can be paused on its own and runs on its own clock,
+//! scaled from the kernel's. Layers step after the kernel's root, in the
+//! order they were created.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use crate::flow::{Generator, Node};
+use crate::TimeFrame;
+
+pub struct KernelLayer {
+    name: String,
+    node: Arc<Node>,
+    paused: AtomicBool,
+    time_scale: Mutex<f64>,
+    time_frame: Mutex<TimeFrame>,
+}
+
+impl KernelLayer {
+    pub(crate) fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            node: Arc::new(Node::with_name(name.clone())),
+            name,
+            paused: AtomicBool::new(false),
+            time_scale: Mutex::new(1.0),
+            time_frame: Mutex::new(TimeFrame::new()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn node(&self) -> Arc<Node> {
+        self.node.clone()
+    }
+
+    pub async fn add_child(&self, child: Arc<dyn Generator>) {
+        self.node.add_child(child).await;
+    }
+
+    /// Stops stepping the layer and stops its clock, so its timers resume
+    /// where they left off.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// How fast the layer's clock runs relative to the kernel's: 0.5 runs
+    /// its timers at half speed. Negative scales are treated as 0.
+    pub fn set_time_scale(&self, scale: f64) {
+        *self.time_scale.lock().unwrap_or_else(|e| e.into_inner()) = scale.max(0.0);
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        *self.time_scale.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The layer's clock, advanced by each kernel update's scaled delta.
+    pub fn time_frame(&self) -> TimeFrame {
+        self.time_frame.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    // Moves the layer's clock on by the kernel's `delta` and returns its time.
+    pub(crate) fn advance(&self, delta: Duration) -> Instant {
+        let scaled = delta.mul_f64(self.time_scale());
+        let mut time_frame = self.time_frame.lock().unwrap_or_else(|e| e.into_inner());
+        time_frame.update_with_delta(scaled);
+        time_frame.now
+    }
+}
@@ -1,13 +1,39 @@
 pub mod kernel;
+pub mod layer;
+pub mod local_kernel;
+pub mod sub_kernel;
+pub mod tick_driver;
+#[cfg(feature = "bevy")]
+pub mod bevy_plugin;
 pub mod flow;
 pub mod factory;
 pub mod time_frame;
 pub mod logger;
+pub mod error;
+pub mod prelude;
+pub mod recording;
+pub mod checkpoint;
+pub mod schedule;
+pub mod profile;
+pub mod resources;
+pub mod testing;
+mod macros;
 
 pub use kernel::*;
+pub use layer::*;
+pub use local_kernel::*;
+pub use sub_kernel::*;
+pub use tick_driver::*;
 pub use flow::*;
 pub use factory::*;
 pub use time_frame::*;
 pub use logger::*;
+pub use error::*;
+pub use recording::*;
+pub use checkpoint::*;
+pub use schedule::*;
+pub use profile::*;
+pub use resources::*;
+pub use async_flow_macros::flow_task;
 
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
\ No newline at end of file
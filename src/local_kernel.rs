@@ -0,0 +1,74 @@
+//! Running flows that drive futures which are not `Send`, such as `Rc`-based
+//! GUI or scripting handles, on a `tokio::task::LocalSet`.
+
+use std::future::Future;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::LocalSet;
+use tracing::Instrument;
+use crate::flow::{task_span, GeneratorBase, LocalCoroutine};
+use crate::{AsyncKernel, Result};
+
+/// An `AsyncKernel` paired with the `LocalSet` its local coroutines run on.
+/// The kernel's run methods drive the set too, so local work makes progress
+/// while the flow steps.
+pub struct LocalKernel {
+    kernel: AsyncKernel,
+    local: LocalSet,
+}
+
+impl LocalKernel {
+    pub fn new() -> Self {
+        Self {
+            kernel: AsyncKernel::new(),
+            local: LocalSet::new(),
+        }
+    }
+
+    pub fn kernel(&self) -> &AsyncKernel {
+        &self.kernel
+    }
+
+    /// Spawns `future` on this kernel's `LocalSet`; it starts running once
+    /// the kernel does. Add the returned node to the tree like any other.
+    pub fn coroutine<F>(&self, name: impl Into<String>, future: F) -> Arc<LocalCoroutine>
+    where
+        F: Future<Output = Result<()>> + 'static,
+    {
+        let base = GeneratorBase::with_name(name);
+        let handle = self.local.spawn_local(future.instrument(task_span(&base)));
+        Arc::new(LocalCoroutine::from_handle(base, handle))
+    }
+
+    /// Runs any future, `Send` or not, with the `LocalSet` active.
+    pub async fn run_until<F: Future>(&self, future: F) -> F::Output {
+        self.local.run_until(future).await
+    }
+
+    pub async fn run_until_complete(&self) -> Result<()> {
+        self.local.run_until(self.kernel.run_until_complete()).await
+    }
+
+    pub async fn run_for(&self, duration: Duration) -> Result<()> {
+        self.local.run_until(self.kernel.run_for(duration)).await
+    }
+
+    pub async fn update(&self, delta_time: Duration) -> Result<()> {
+        self.local.run_until(self.kernel.update(delta_time)).await
+    }
+}
+
+impl Default for LocalKernel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for LocalKernel {
+    type Target = AsyncKernel;
+
+    fn deref(&self) -> &AsyncKernel {
+        &self.kernel
+    }
+}
@@ -1,6 +1,10 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
 use tracing::{debug, error, info, trace, warn};
+use uuid::Uuid;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LogLevel {
     Error = 0,
     Warn = 1,
@@ -9,42 +13,244 @@ pub enum LogLevel {
     Trace = 4,
 }
 
-#[derive(Debug, Clone)]
+/// Receives flow log lines instead of `tracing`, for routing them into an
+/// embedder's own logging or telemetry. Install one with
+/// `AsyncKernel::set_log_sink`. `node_path` names the logging generator and
+/// its ancestors from the top, separated by `/`.
+pub trait LogSink: Send + Sync {
+    fn log(&self, level: LogLevel, node_path: &str, message: &str);
+}
+
+/// One line recorded by a `CaptureSink`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub level: LogLevel,
+    pub node: String,
+    pub message: String,
+}
+
+/// Keeps every line in memory, for tests asserting what a node logged.
+/// `AsyncKernel::capture_logs` installs one.
+#[derive(Default)]
+pub struct CaptureSink {
+    entries: Mutex<Vec<LogEntry>>,
+}
+
+impl CaptureSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entries(&self) -> Vec<LogEntry> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Lines logged by generators whose path ends with `node`.
+    pub fn entries_for(&self, node: &str) -> Vec<LogEntry> {
+        self.entries().into_iter().filter(|entry| entry.node.ends_with(node)).collect()
+    }
+
+    pub fn contains(&self, level: LogLevel, text: &str) -> bool {
+        self.entries().iter().any(|entry| entry.level == level && entry.message.contains(text))
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+impl LogSink for CaptureSink {
+    fn log(&self, level: LogLevel, node_path: &str, message: &str) {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).push(LogEntry {
+            level,
+            node: node_path.to_string(),
+            message: message.to_string(),
+        });
+    }
+}
+
+static DEFAULT_VERBOSITY: AtomicU8 = AtomicU8::new(3);
+
+// Marks a logger without a verbosity of its own.
+const INHERIT: u8 = u8::MAX;
+
+/// The verbosity of loggers with none set on them or on any ancestor.
+pub fn set_default_verbosity(verbosity: u8) {
+    DEFAULT_VERBOSITY.store(verbosity.min(INHERIT - 1), Ordering::Relaxed);
+}
+
+pub fn default_verbosity() -> u8 {
+    DEFAULT_VERBOSITY.load(Ordering::Relaxed)
+}
+
+// What a logger inherits from its parent's: verbosity and sink, falling back
+// to the nearest ancestor that sets them.
+struct Scope {
+    label: RwLock<String>,
+    id: OnceLock<Uuid>,
+    name: RwLock<Option<String>>,
+    kind: OnceLock<&'static str>,
+    verbosity: AtomicU8,
+    sink: RwLock<Option<Arc<dyn LogSink>>>,
+    parent: RwLock<Option<Weak<Scope>>>,
+}
+
+impl Scope {
+    fn parent(&self) -> Option<Arc<Scope>> {
+        self.parent.read().unwrap_or_else(|e| e.into_inner()).as_ref().and_then(Weak::upgrade)
+    }
+
+    fn verbosity(&self) -> u8 {
+        match self.verbosity.load(Ordering::Relaxed) {
+            INHERIT => self.parent().map_or_else(default_verbosity, |parent| parent.verbosity()),
+            own => own,
+        }
+    }
+
+    fn sink(&self) -> Option<Arc<dyn LogSink>> {
+        let own = self.sink.read().unwrap_or_else(|e| e.into_inner()).clone();
+        own.or_else(|| self.parent().and_then(|parent| parent.sink()))
+    }
+
+    fn path(&self) -> String {
+        let label = self.label.read().unwrap_or_else(|e| e.into_inner()).clone();
+        match self.parent() {
+            Some(parent) => format!("{}/{}", parent.path(), label),
+            None => label,
+        }
+    }
+}
+
+/// Clones share one verbosity setting and sink.
+#[derive(Clone)]
 pub struct Logger {
-    pub verbosity: u8,
     pub prefix: String,
+    scope: Arc<Scope>,
 }
 
 impl Logger {
     pub fn new(prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
         Self {
-            verbosity: 3,
-            prefix: prefix.into(),
+            scope: Arc::new(Scope {
+                label: RwLock::new(prefix.clone()),
+                id: OnceLock::new(),
+                name: RwLock::new(None),
+                kind: OnceLock::new(),
+                verbosity: AtomicU8::new(INHERIT),
+                sink: RwLock::new(None),
+                parent: RwLock::new(None),
+            }),
+            prefix,
+        }
+    }
+
+    /// This logger's verbosity if set, else the nearest ancestor's, else
+    /// `default_verbosity()`.
+    pub fn verbosity(&self) -> u8 {
+        self.scope.verbosity()
+    }
+
+    pub fn set_verbosity(&self, verbosity: u8) {
+        self.scope.verbosity.store(verbosity.min(INHERIT - 1), Ordering::Relaxed);
+    }
+
+    /// Goes back to inheriting the verbosity.
+    pub fn clear_verbosity(&self) {
+        self.scope.verbosity.store(INHERIT, Ordering::Relaxed);
+    }
+
+    /// Sends this logger's lines, and its descendants' unless they set their
+    /// own, to `sink` instead of `tracing`. `None` inherits again.
+    pub fn set_sink(&self, sink: Option<Arc<dyn LogSink>>) {
+        *self.scope.sink.write().unwrap_or_else(|e| e.into_inner()) = sink;
+    }
+
+    /// The generator names from the top of the tree down to this logger's.
+    pub fn node_path(&self) -> String {
+        self.scope.path()
+    }
+
+    // Ties this logger to its generator; the short id labels it until named.
+    pub(crate) fn set_node(&self, id: Uuid) {
+        let _ = self.scope.id.set(id);
+        *self.scope.label.write().unwrap_or_else(|e| e.into_inner()) = crate::flow::short_id(id);
+    }
+
+    pub(crate) fn set_node_name(&self, name: String) {
+        *self.scope.label.write().unwrap_or_else(|e| e.into_inner()) = name.clone();
+        *self.scope.name.write().unwrap_or_else(|e| e.into_inner()) = Some(name);
+    }
+
+    // Recorded the first time the generator is stepped or added to a container.
+    pub(crate) fn set_kind(&self, kind: &'static str) {
+        let _ = self.scope.kind.set(kind);
+    }
+
+    // Makes this logger inherit from `parent`, or from the defaults when none.
+    pub(crate) fn inherit_from(&self, parent: Option<&Logger>) {
+        *self.scope.parent.write().unwrap_or_else(|e| e.into_inner()) =
+            parent.map(|parent| Arc::downgrade(&parent.scope));
+    }
+
+    pub fn log(&self, level: LogLevel, message: impl AsRef<str>) {
+        let message = message.as_ref();
+        if let Some(sink) = self.scope.sink() {
+            sink.log(level, &self.node_path(), message);
+            return;
+        }
+        // Field values are only evaluated for events a subscriber wants.
+        macro_rules! emit {
+            ($event:ident) => {
+                $event!(
+                    node.id = self.scope.id.get().map(tracing::field::display),
+                    node.name = self.scope.name.read().unwrap_or_else(|e| e.into_inner()).as_deref(),
+                    node.path = %self.node_path(),
+                    node.type = self.scope.kind.get().copied(),
+                    "{}",
+                    message
+                )
+            };
+        }
+        match level {
+            LogLevel::Error => emit!(error),
+            LogLevel::Warn => emit!(warn),
+            LogLevel::Info => emit!(info),
+            LogLevel::Debug => emit!(debug),
+            LogLevel::Trace => emit!(trace),
         }
     }
 
     pub fn error(&self, message: impl AsRef<str>) {
-        error!("[{}] {}", self.prefix, message.as_ref());
+        self.log(LogLevel::Error, message);
     }
 
     pub fn warn(&self, message: impl AsRef<str>) {
-        warn!("[{}] {}", self.prefix, message.as_ref());
+        self.log(LogLevel::Warn, message);
     }
 
     pub fn info(&self, message: impl AsRef<str>) {
-        info!("[{}] {}", self.prefix, message.as_ref());
+        self.log(LogLevel::Info, message);
     }
 
     pub fn debug(&self, message: impl AsRef<str>) {
-        debug!("[{}] {}", self.prefix, message.as_ref());
+        self.log(LogLevel::Debug, message);
     }
 
     pub fn trace(&self, message: impl AsRef<str>) {
-        trace!("[{}] {}", self.prefix, message.as_ref());
+        self.log(LogLevel::Trace, message);
+    }
+
+    /// Like `verbose`, but only builds the message when `level` passes the
+    /// verbosity, so step loops don't format lines nobody will see.
+    pub fn verbose_with<M: AsRef<str>>(&self, level: u8, message: impl FnOnce() -> M) {
+        if level <= self.verbosity() {
+            self.verbose(level, message());
+        }
     }
 
     pub fn verbose(&self, level: u8, message: impl AsRef<str>) {
-        if level <= self.verbosity {
+        if level <= self.verbosity() {
             match level {
                 0 => self.error(message),
                 1 => self.warn(message),
@@ -56,8 +262,18 @@ impl Logger {
     }
 }
 
+impl fmt::Debug for Logger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Logger")
+            .field("prefix", &self.prefix)
+            .field("path", &self.node_path())
+            .field("verbosity", &self.verbosity())
+            .finish()
+    }
+}
+
 impl Default for Logger {
     fn default() -> Self {
         Self::new("AsyncFlow")
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,74 @@
+/// Declarative tree construction. Expands to constructor calls and
+/// `add_child(..).await`, so it must be used inside an async context.
+///
+/// ```ignore
+/// let main = flow! {
+///     sequence "Main" {
+///         barrier "Downloads" { task download1, task download2 },
+///         timer Duration::from_millis(100),
+///         task compress
+///     }
+/// };
+/// kernel.root().add_child(main).await;
+/// ```
+///
+/// Composites are `sequence`, `barrier` and `node`, each with an optional
+/// name literal. `task <expr>` adds an existing generator and
+/// `timer <expr>` creates a `Timer` from a `Duration`.
+#[macro_export]
+macro_rules! flow {
+    (sequence $name:literal { $($body:tt)* }) => {{
+        let container = ::std::sync::Arc::new($crate::Sequence::with_name($name));
+        $crate::flow!(@children container; $($body)*);
+        container
+    }};
+    (sequence { $($body:tt)* }) => {{
+        let container = ::std::sync::Arc::new($crate::Sequence::new());
+        $crate::flow!(@children container; $($body)*);
+        container
+    }};
+    (barrier $name:literal { $($body:tt)* }) => {{
+        let container = ::std::sync::Arc::new($crate::Barrier::with_name($name));
+        $crate::flow!(@children container; $($body)*);
+        container
+    }};
+    (barrier { $($body:tt)* }) => {{
+        let container = ::std::sync::Arc::new($crate::Barrier::new());
+        $crate::flow!(@children container; $($body)*);
+        container
+    }};
+    (node $name:literal { $($body:tt)* }) => {{
+        let container = ::std::sync::Arc::new($crate::Node::with_name($name));
+        $crate::flow!(@children container; $($body)*);
+        container
+    }};
+    (node { $($body:tt)* }) => {{
+        let container = ::std::sync::Arc::new($crate::Node::new());
+        $crate::flow!(@children container; $($body)*);
+        container
+    }};
+    (task $generator:expr) => {
+        $generator
+    };
+    (timer $duration:expr) => {
+        ::std::sync::Arc::new($crate::Timer::new($duration))
+    };
+
+    (@children $container:ident; ) => {};
+    (@children $container:ident; task $generator:expr $(, $($rest:tt)*)?) => {
+        $container.add_child($generator).await;
+        $crate::flow!(@children $container; $($($rest)*)?);
+    };
+    (@children $container:ident; timer $duration:expr $(, $($rest:tt)*)?) => {
+        $container.add_child($crate::flow!(timer $duration)).await;
+        $crate::flow!(@children $container; $($($rest)*)?);
+    };
+    (@children $container:ident; $kind:ident $name:literal { $($body:tt)* } $(, $($rest:tt)*)?) => {
+        $container.add_child($crate::flow!($kind $name { $($body)* })).await;
+        $crate::flow!(@children $container; $($($rest)*)?);
+    };
+    (@children $container:ident; $kind:ident { $($body:tt)* } $(, $($rest:tt)*)?) => {
+        $container.add_child($crate::flow!($kind { $($body)* })).await;
+        $crate::flow!(@children $container; $($($rest)*)?);
+    };
+}
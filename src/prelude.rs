@@ -0,0 +1,10 @@
+//! The commonly used types, for `use async_flow::prelude::*;` without pulling
+//! every helper from the crate root into scope.
+
+pub use crate::factory::Named;
+pub use crate::flow::{
+    AsyncCoroutine, AsyncFuture, Barrier, BarrierBuilder, FlowCtx, Generator, GeneratorState,
+    Node, PeriodicTimer, Sequence, SequenceBuilder, SyncCoroutine, Timer, Trigger,
+};
+pub use crate::kernel::AsyncKernel;
+pub use crate::{flow, flow_task, Result};
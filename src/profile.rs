@@ -0,0 +1,154 @@
+//! Opt-in step profiling. With profiling on, the kernel times every
+//! generator's `step`, keyed by node path, and `AsyncKernel::profile_report`
+//! summarises the durations. Times are wall-clock and include the steps of a
+//! container's children.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Bucket `i` counts steps shorter than `2^i` microseconds; the last bucket
+/// takes everything longer.
+pub const HISTOGRAM_BUCKETS: usize = 24;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepHistogram {
+    pub count: u64,
+    pub total: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub buckets: [u64; HISTOGRAM_BUCKETS],
+}
+
+impl StepHistogram {
+    fn new() -> Self {
+        Self {
+            count: 0,
+            total: Duration::ZERO,
+            min: Duration::MAX,
+            max: Duration::ZERO,
+            buckets: [0; HISTOGRAM_BUCKETS],
+        }
+    }
+
+    fn record(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.total += elapsed;
+        self.min = self.min.min(elapsed);
+        self.max = self.max.max(elapsed);
+        let micros = elapsed.as_micros() as u64;
+        let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+        self.buckets[bucket.min(HISTOGRAM_BUCKETS - 1)] += 1;
+    }
+
+    pub fn mean(&self) -> Duration {
+        match self.count {
+            0 => Duration::ZERO,
+            count => self.total / count as u32,
+        }
+    }
+
+    /// An upper bound on the `quantile` (0.0 to 1.0) step duration, to the
+    /// resolution of the buckets.
+    pub fn quantile(&self, quantile: f64) -> Duration {
+        let target = (self.count as f64 * quantile.clamp(0.0, 1.0)).ceil() as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target.max(1) {
+                return Duration::from_micros(1 << bucket).min(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeProfile {
+    pub path: String,
+    pub kind: &'static str,
+    pub steps: StepHistogram,
+}
+
+/// Per-node step timings, busiest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileReport {
+    pub nodes: Vec<NodeProfile>,
+}
+
+impl ProfileReport {
+    pub fn node(&self, path: &str) -> Option<&NodeProfile> {
+        self.nodes.iter().find(|node| node.path == path)
+    }
+}
+
+impl fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>8} {:>12} {:>12} {:>12} {:>12}  node", "steps", "total", "mean", "p99", "max")?;
+        for node in &self.nodes {
+            let steps = &node.steps;
+            writeln!(
+                f,
+                "{:>8} {:>12?} {:>12?} {:>12?} {:>12?}  {} ({})",
+                steps.count,
+                steps.total,
+                steps.mean(),
+                steps.quantile(0.99),
+                steps.max,
+                node.path,
+                node.kind,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Profiler {
+    nodes: Mutex<HashMap<String, NodeProfile>>,
+}
+
+impl Profiler {
+    pub(crate) fn record(&self, path: String, kind: &'static str, elapsed: Duration) {
+        let mut nodes = self.nodes.lock().unwrap_or_else(|e| e.into_inner());
+        nodes
+            .entry(path)
+            .or_insert_with_key(|path| NodeProfile {
+                path: path.clone(),
+                kind,
+                steps: StepHistogram::new(),
+            })
+            .steps
+            .record(elapsed);
+    }
+
+    pub(crate) fn report(&self) -> ProfileReport {
+        let mut nodes: Vec<NodeProfile> =
+            self.nodes.lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect();
+        nodes.sort_by(|a, b| b.steps.total.cmp(&a.steps.total).then_with(|| a.path.cmp(&b.path)));
+        ProfileReport { nodes }
+    }
+
+    pub(crate) fn clear(&self) {
+        self.nodes.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+tokio::task_local! {
+    static PROFILER: Arc<Profiler>;
+}
+
+/// Runs `future` with its generator steps timed by `profiler`, if there is one.
+pub(crate) async fn scope<F: Future>(profiler: Option<Arc<Profiler>>, future: F) -> F::Output {
+    match profiler {
+        Some(profiler) => PROFILER.scope(profiler, future).await,
+        None => future.await,
+    }
+}
+
+// The profiler for the step being run, if profiling is on.
+pub(crate) fn current() -> Option<Arc<Profiler>> {
+    PROFILER.try_with(Arc::clone).ok()
+}
@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::sleep;
+use crate::flow::{GeneratorBase, LifecycleObserver};
+use crate::{AsyncKernel, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    Completed(String),
+    Failed(String, String),
+    Triggered(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TickRecord {
+    pub delta: Duration,
+    pub events: Vec<RecordedEvent>,
+}
+
+/// Every tick's delta and the completions and trigger firings within it,
+/// keyed by generator name (or short id when unnamed).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recording {
+    pub ticks: Vec<TickRecord>,
+}
+
+impl Recording {
+    /// Completed and failed generators, in the order they finished.
+    pub fn completion_order(&self) -> Vec<String> {
+        self.ticks
+            .iter()
+            .flat_map(|tick| &tick.events)
+            .filter_map(|event| match event {
+                RecordedEvent::Completed(key) | RecordedEvent::Failed(key, _) => Some(key.clone()),
+                RecordedEvent::Triggered(_) => None,
+            })
+            .collect()
+    }
+
+    /// One line per tick: the delta in microseconds, then tab-separated
+    /// events `+key` (completed), `!key=reason` (failed) and `*key` (triggered).
+    pub fn to_compact(&self) -> String {
+        let mut out = String::new();
+        for tick in &self.ticks {
+            out.push_str(&tick.delta.as_micros().to_string());
+            for event in &tick.events {
+                out.push('\t');
+                match event {
+                    RecordedEvent::Completed(key) => {
+                        out.push('+');
+                        out.push_str(&escape(key));
+                    }
+                    RecordedEvent::Failed(key, reason) => {
+                        out.push('!');
+                        out.push_str(&escape(key));
+                        out.push('=');
+                        out.push_str(&escape(reason));
+                    }
+                    RecordedEvent::Triggered(key) => {
+                        out.push('*');
+                        out.push_str(&escape(key));
+                    }
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn from_compact(text: &str) -> Result<Self> {
+        let mut ticks = Vec::new();
+        for line in text.lines().filter(|line| !line.is_empty()) {
+            let mut fields = line.split('\t');
+            let micros: u64 = fields.next().unwrap_or_default().parse()?;
+            let mut events = Vec::new();
+            for field in fields {
+                let (kind, rest) = field.split_at(field.chars().next().map_or(0, char::len_utf8));
+                let event = match kind {
+                    "+" => RecordedEvent::Completed(unescape(rest)),
+                    "*" => RecordedEvent::Triggered(unescape(rest)),
+                    "!" => {
+                        let (key, reason) = split_unescaped(rest, '=');
+                        RecordedEvent::Failed(unescape(key), unescape(reason))
+                    }
+                    _ => return Err(format!("unknown recorded event: {}", field).into()),
+                };
+                events.push(event);
+            }
+            ticks.push(TickRecord { delta: Duration::from_micros(micros), events });
+        }
+        Ok(Self { ticks })
+    }
+}
+
+/// Records a kernel's ticks; attach with `AsyncKernel::record`.
+#[derive(Default)]
+pub struct FlowRecorder {
+    pending: Mutex<Vec<RecordedEvent>>,
+    ticks: Mutex<Vec<TickRecord>>,
+}
+
+impl FlowRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Closes the current tick; the kernel calls this after every update.
+    pub fn end_tick(&self, delta: Duration) {
+        let events = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        self.ticks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(TickRecord { delta, events });
+    }
+
+    pub fn recording(&self) -> Recording {
+        Recording {
+            ticks: self.ticks.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+        }
+    }
+
+    fn push(&self, event: RecordedEvent) {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).push(event);
+    }
+}
+
+impl LifecycleObserver for FlowRecorder {
+    fn on_complete(&self, generator: &GeneratorBase) {
+        self.push(RecordedEvent::Completed(generator.label()));
+    }
+
+    fn on_fail(&self, generator: &GeneratorBase, reason: &str) {
+        self.push(RecordedEvent::Failed(generator.label(), reason.to_string()));
+    }
+
+    fn on_triggered(&self, generator: &GeneratorBase) {
+        self.push(RecordedEvent::Triggered(generator.label()));
+    }
+}
+
+/// Re-runs a freshly built copy of a recorded tree with the recorded tick
+/// deltas, firing named triggers exactly on their recorded ticks. Run it
+/// under a paused tokio clock (`#[tokio::test(start_paused = true)]`) so
+/// timers see precisely the recorded timing.
+pub struct FlowReplayer {
+    recording: Recording,
+    triggers: HashSet<String>,
+    tick: AtomicUsize,
+}
+
+impl FlowReplayer {
+    pub fn new(recording: Recording) -> Arc<Self> {
+        let triggers = recording
+            .ticks
+            .iter()
+            .flat_map(|tick| &tick.events)
+            .filter_map(|event| match event {
+                RecordedEvent::Triggered(key) => Some(key.clone()),
+                _ => None,
+            })
+            .collect();
+        Arc::new(Self {
+            recording,
+            triggers,
+            tick: AtomicUsize::new(0),
+        })
+    }
+
+    /// Replays every recorded tick and returns what happened this time, so it
+    /// can be compared against the original recording.
+    pub async fn replay(self: &Arc<Self>, kernel: &AsyncKernel) -> Result<Recording> {
+        let recorder = Arc::new(FlowRecorder::new());
+        let observer: Arc<dyn LifecycleObserver> = self.clone();
+        kernel.add_observer(observer.clone());
+        kernel.record(recorder.clone()).await;
+
+        let mut result = Ok(());
+        for (index, tick) in self.recording.ticks.iter().enumerate() {
+            self.tick.store(index, Ordering::Relaxed);
+            sleep(tick.delta).await;
+            result = kernel.update(tick.delta).await;
+            if result.is_err() {
+                break;
+            }
+        }
+
+        kernel.stop_recording().await;
+        kernel.remove_observer(&observer);
+        result.map(|_| recorder.recording())
+    }
+}
+
+impl LifecycleObserver for FlowReplayer {
+    fn override_trigger(&self, generator: &GeneratorBase) -> Option<bool> {
+        let key = generator.label();
+        if !self.triggers.contains(&key) {
+            return None;
+        }
+        let tick = self.recording.ticks.get(self.tick.load(Ordering::Relaxed))?;
+        Some(tick.events.contains(&RecordedEvent::Triggered(key)))
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '=' => out.push_str("\\="),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+fn split_unescaped(text: &str, separator: char) -> (&str, &str) {
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == separator {
+            return (&text[..index], &text[index + c.len_utf8()..]);
+        }
+    }
+    (text, "")
+}
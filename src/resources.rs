@@ -0,0 +1,76 @@
+//! Shared services registered on a kernel, looked up by type. Everything the
+//! kernel steps can reach them: coroutines through `FlowCtx::resource`, and
+//! trigger conditions and other step-time code through `resource`.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+
+type ResourceMap = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+/// A type-keyed registry holding at most one value per type. Clones share
+/// the same registry.
+#[derive(Clone, Default)]
+pub struct Resources {
+    map: Arc<RwLock<ResourceMap>>,
+}
+
+impl Resources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `value`, replacing any earlier `T`.
+    pub fn insert<T: Send + Sync + 'static>(&self, value: T) {
+        self.insert_arc(Arc::new(value));
+    }
+
+    pub fn insert_arc<T: Send + Sync + 'static>(&self, value: Arc<T>) {
+        self.map.write().unwrap_or_else(|e| e.into_inner()).insert(TypeId::of::<T>(), value);
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        let value = self.map.read().unwrap_or_else(|e| e.into_inner()).get(&TypeId::of::<T>())?.clone();
+        value.downcast().ok()
+    }
+
+    /// The registered `T`, registering `make()` first if there is none.
+    pub fn get_or_insert_with<T: Send + Sync + 'static>(&self, make: impl FnOnce() -> T) -> Arc<T> {
+        if let Some(value) = self.get() {
+            return value;
+        }
+        let mut map = self.map.write().unwrap_or_else(|e| e.into_inner());
+        let value = map.entry(TypeId::of::<T>()).or_insert_with(|| Arc::new(make())).clone();
+        value.downcast().expect("resource stored under its own type id")
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.map.read().unwrap_or_else(|e| e.into_inner()).contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn remove<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        let value = self.map.write().unwrap_or_else(|e| e.into_inner()).remove(&TypeId::of::<T>())?;
+        value.downcast().ok()
+    }
+}
+
+tokio::task_local! {
+    static RESOURCES: Resources;
+}
+
+/// Runs `future` with `resources` as the registry `resource` reads.
+pub(crate) async fn scope<F: Future>(resources: Resources, future: F) -> F::Output {
+    RESOURCES.scope(resources, future).await
+}
+
+/// The registry of the kernel stepping the current generator, if any.
+pub fn current_resources() -> Option<Resources> {
+    RESOURCES.try_with(Resources::clone).ok()
+}
+
+/// The `T` registered on the kernel stepping the current generator. `None`
+/// outside a kernel step, such as in a task spawned without `FlowCtx`.
+pub fn resource<T: Send + Sync + 'static>() -> Option<Arc<T>> {
+    RESOURCES.try_with(|resources| resources.get::<T>()).ok().flatten()
+}
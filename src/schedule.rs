@@ -0,0 +1,93 @@
+//! Seeded scheduling. With a seed set on the kernel, parallel containers step
+//! their children in a shuffled but reproducible order, so order-dependent
+//! bugs found in CI can be replayed exactly from the printed seed.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// SplitMix64: tiny, and stable across releases so a seed keeps reproducing.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// A seed drawn from the clock, for runs that should print theirs.
+    pub fn entropy_seed() -> u64 {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        SeededRng::new(nanos ^ COUNTER.fetch_add(1, Ordering::Relaxed)).next_u64()
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `0..bound`; `bound` must be non-zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+pub(crate) type SharedRng = Arc<Mutex<SeededRng>>;
+
+tokio::task_local! {
+    static SCHEDULER: SharedRng;
+}
+
+/// Runs `future` with `rng` driving scheduling decisions, if there is one.
+pub(crate) async fn scope<F: Future>(rng: Option<SharedRng>, future: F) -> F::Output {
+    match rng {
+        Some(rng) => SCHEDULER.scope(rng, future).await,
+        None => future.await,
+    }
+}
+
+/// The order a parallel container steps `len` children in this tick:
+/// insertion order, or a seeded shuffle when the kernel has a seed.
+pub fn step_order(len: usize) -> Vec<usize> {
+    let mut order = Vec::with_capacity(len);
+    fill_step_order(&mut order, len);
+    order
+}
+
+/// `step_order` written into `order`, so containers can reuse one buffer
+/// from tick to tick.
+pub fn fill_step_order(order: &mut Vec<usize>, len: usize) {
+    order.clear();
+    order.extend(0..len);
+    let _ = SCHEDULER.try_with(|rng| {
+        rng.lock().unwrap_or_else(|e| e.into_inner()).shuffle(order);
+    });
+}
+
+/// A random index in `0..len` for selectors, drawn from the kernel's seeded
+/// RNG when there is one. Returns `None` for an empty range.
+pub fn random_index(len: usize) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    SCHEDULER
+        .try_with(|rng| rng.lock().unwrap_or_else(|e| e.into_inner()).below(len))
+        .ok()
+        .or_else(|| Some(SeededRng::new(SeededRng::entropy_seed()).below(len)))
+}
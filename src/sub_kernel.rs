@@ -0,0 +1,164 @@
+//! A kernel nested inside another flow as a single child generator.
+
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::flow::{ChildList, Generator, GeneratorBase, ParentLink};
+use crate::{flow_now, with_layer_time, AsyncKernel, Logger, Result, TimeFrame};
+
+/// Steps an inner `AsyncKernel` once per outer step, with the time passed
+/// since the previous step scaled by its time scale; the inner kernel's
+/// timers follow that scaled clock. Completes once the inner kernel runs out
+/// of work or breaks, and a break stays inside: the outer flow carries on.
+/// The inner kernel's own run loops must not be used while it is nested.
+pub struct SubKernel {
+    base: GeneratorBase,
+    kernel: AsyncKernel,
+    slot: Arc<ChildList>,
+    time_scale: Mutex<f64>,
+    time_frame: Mutex<TimeFrame>,
+    last_step: Mutex<Option<Instant>>,
+}
+
+impl SubKernel {
+    pub fn new(kernel: AsyncKernel) -> Self {
+        Self::build(GeneratorBase::new(), kernel)
+    }
+
+    pub fn with_name(name: impl Into<String>, kernel: AsyncKernel) -> Self {
+        Self::build(GeneratorBase::with_name(name), kernel)
+    }
+
+    // The inner root is linked below this node, so the outer flow's
+    // observers and event stream see the inner flow too.
+    fn build(base: GeneratorBase, kernel: AsyncKernel) -> Self {
+        let root: Arc<dyn Generator> = kernel.root();
+        let slot = Arc::new(RwLock::new(vec![root.clone()]));
+        root.base().set_parent(Some(ParentLink::new(&base, &slot)));
+        Self {
+            base,
+            kernel,
+            slot,
+            time_scale: Mutex::new(1.0),
+            time_frame: Mutex::new(TimeFrame::new()),
+            last_step: Mutex::new(None),
+        }
+    }
+
+    pub fn kernel(&self) -> &AsyncKernel {
+        &self.kernel
+    }
+
+    /// How fast the inner clock runs relative to the outer one. Negative
+    /// scales are treated as 0.
+    pub fn set_time_scale(&self, scale: f64) {
+        *self.time_scale.lock().unwrap_or_else(|e| e.into_inner()) = scale.max(0.0);
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        *self.time_scale.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The inner clock, advanced by each step's scaled delta.
+    pub fn time_frame(&self) -> TimeFrame {
+        self.time_frame.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    // The scaled time since the previous step, and the inner clock after it.
+    fn advance(&self) -> (Duration, Instant) {
+        let now = flow_now();
+        let delta = {
+            let mut last_step = self.last_step.lock().unwrap_or_else(|e| e.into_inner());
+            let delta = last_step.map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+            *last_step = Some(now);
+            delta
+        };
+        let scaled = delta.mul_f64(self.time_scale());
+        let mut time_frame = self.time_frame.lock().unwrap_or_else(|e| e.into_inner());
+        time_frame.update_with_delta(scaled);
+        (scaled, time_frame.now)
+    }
+}
+
+#[async_trait]
+impl Generator for SubKernel {
+    fn id(&self) -> Uuid {
+        self.base.id()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.base.name()
+    }
+
+    fn set_name(&self, name: String) {
+        self.base.set_name(name);
+    }
+
+    fn is_active(&self) -> bool {
+        self.base.is_active()
+    }
+
+    fn is_running(&self) -> bool {
+        self.base.is_running()
+    }
+
+    fn is_completed(&self) -> bool {
+        self.base.is_completed()
+    }
+
+    // Time spent inactive does not reach the inner flow.
+    fn activate(&self) {
+        self.base.activate();
+        self.kernel.activate();
+    }
+
+    fn deactivate(&self) {
+        self.base.deactivate();
+        self.kernel.deactivate();
+        *self.last_step.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    fn complete(&self) {
+        self.base.complete();
+    }
+
+    async fn step(&self) -> Result<()> {
+        if !self.is_active() || !self.is_running() || self.is_completed() {
+            return Ok(());
+        }
+
+        self.base.start();
+
+        let (delta, now) = self.advance();
+        with_layer_time(now, self.kernel.update(delta)).await?;
+
+        if self.kernel.is_breaking().await || self.kernel.is_empty().await {
+            self.complete();
+        }
+
+        Ok(())
+    }
+
+    fn logger(&self) -> &Logger {
+        self.base.logger()
+    }
+
+    fn base(&self) -> &GeneratorBase {
+        &self.base
+    }
+
+    fn cancel(&self) {
+        self.kernel.cancel();
+    }
+
+    async fn children(&self) -> Vec<Arc<dyn Generator>> {
+        self.slot.read().await.clone()
+    }
+
+    fn try_children(&self) -> Option<Vec<Arc<dyn Generator>>> {
+        self.slot.try_read().ok().map(|slot| slot.clone())
+    }
+}
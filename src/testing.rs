@@ -0,0 +1,233 @@
+//! Helpers for unit-testing flows. Run tests under a paused tokio clock,
+//! `#[tokio::test(start_paused = true)]`, and `TestKernel::advance` moves
+//! time forward instantly and exactly instead of sleeping for real.
+
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::field::{Field, Visit};
+use tracing::subscriber::DefaultGuard;
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use crate::flow::Generator;
+use crate::{AsyncKernel, Result};
+
+pub struct TestKernel {
+    kernel: AsyncKernel,
+    tick: Duration,
+    elapsed: Mutex<Duration>,
+}
+
+impl TestKernel {
+    pub fn new() -> Self {
+        Self {
+            kernel: AsyncKernel::new(),
+            tick: Duration::from_millis(1),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Uses `tick` as the step size for `advance`; 1ms by default.
+    pub fn with_tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    pub fn kernel(&self) -> &AsyncKernel {
+        &self.kernel
+    }
+
+    /// Total time moved forward by `advance` so far.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub async fn advance(&self, millis: u64) -> Result<()> {
+        self.advance_by(Duration::from_millis(millis)).await
+    }
+
+    /// Moves time forward by `duration`, updating the kernel every tick.
+    pub async fn advance_by(&self, duration: Duration) -> Result<()> {
+        let mut remaining = duration;
+        while !remaining.is_zero() {
+            let step = remaining.min(self.tick);
+            self.tick_by(step).await?;
+            remaining -= step;
+        }
+        Ok(())
+    }
+
+    /// Advances tick by tick until `generator` completes or `limit` passes.
+    pub async fn advance_until_completed(&self, generator: &dyn Generator, limit: Duration) -> Result<bool> {
+        let mut waited = Duration::ZERO;
+        while !generator.is_completed() && waited < limit {
+            let step = (limit - waited).min(self.tick);
+            self.tick_by(step).await?;
+            waited += step;
+        }
+        Ok(generator.is_completed())
+    }
+
+    async fn tick_by(&self, step: Duration) -> Result<()> {
+        sleep(step).await;
+        *self.elapsed.lock().unwrap_or_else(|e| e.into_inner()) += step;
+        self.kernel.update(step).await
+    }
+}
+
+impl Default for TestKernel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for TestKernel {
+    type Target = AsyncKernel;
+
+    fn deref(&self) -> &AsyncKernel {
+        &self.kernel
+    }
+}
+
+/// Steps `generator` on its own, tick by tick, until it completes or `limit`
+/// passes. For generators whose progress depends on the rest of a flow, use
+/// `TestKernel::advance_until_completed` instead.
+pub async fn fires_within(generator: &dyn Generator, limit: Duration) -> Result<bool> {
+    let tick = Duration::from_millis(1);
+    let mut waited = Duration::ZERO;
+    loop {
+        generator.step().await?;
+        if generator.is_completed() || waited >= limit {
+            return Ok(generator.is_completed());
+        }
+        let step = (limit - waited).min(tick);
+        sleep(step).await;
+        waited += step;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedLog {
+    pub level: Level,
+    pub message: String,
+    /// The event's other fields, such as `node.path`, in recorded order.
+    pub fields: Vec<(String, String)>,
+}
+
+impl CapturedLog {
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.iter().find(|(field, _)| field == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Collects `tracing` events, including everything `Logger` emits, on the
+/// current thread until dropped.
+pub struct LogCapture {
+    logs: Arc<Mutex<Vec<CapturedLog>>>,
+    _guard: DefaultGuard,
+}
+
+impl LogCapture {
+    pub fn install() -> Self {
+        let logs = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(CaptureLayer { logs: logs.clone() });
+        Self {
+            logs,
+            _guard: tracing::subscriber::set_default(subscriber),
+        }
+    }
+
+    pub fn logs(&self) -> Vec<CapturedLog> {
+        self.logs.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn contains(&self, text: &str) -> bool {
+        self.logs().iter().any(|log| log.message.contains(text))
+    }
+
+    pub fn clear(&self) {
+        self.logs.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+struct CaptureLayer {
+    logs: Arc<Mutex<Vec<CapturedLog>>>,
+}
+
+#[derive(Default)]
+struct EventVisitor {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl EventVisitor {
+    fn record(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = value;
+        } else {
+            self.fields.push((field.name().to_string(), value));
+        }
+    }
+}
+
+impl Visit for EventVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.record(field, format!("{:?}", value));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for CaptureLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+        self.logs.lock().unwrap_or_else(|e| e.into_inner()).push(CapturedLog {
+            level: *event.metadata().level(),
+            message: visitor.message,
+            fields: visitor.fields,
+        });
+    }
+}
+
+#[macro_export]
+macro_rules! assert_completed {
+    ($generator:expr) => {{
+        let generator = &$generator;
+        assert!(
+            $crate::Generator::is_completed(&**generator),
+            "expected {} to be completed, but it is {}",
+            $crate::Generator::base(&**generator).label(),
+            $crate::Generator::state(&**generator),
+        );
+    }};
+}
+
+/// `assert_fires_within!(trigger, duration)` steps the generator on its own;
+/// `assert_fires_within!(test_kernel, trigger, duration)` advances the kernel.
+#[macro_export]
+macro_rules! assert_fires_within {
+    ($generator:expr, $limit:expr) => {{
+        let generator = &$generator;
+        let fired = $crate::testing::fires_within(&**generator, $limit).await.unwrap();
+        assert!(
+            fired,
+            "expected {} to fire within {:?}",
+            $crate::Generator::base(&**generator).label(),
+            $limit,
+        );
+    }};
+    ($kernel:expr, $generator:expr, $limit:expr) => {{
+        let generator = &$generator;
+        let fired = $kernel.advance_until_completed(&**generator, $limit).await.unwrap();
+        assert!(
+            fired,
+            "expected {} to fire within {:?}",
+            $crate::Generator::base(&**generator).label(),
+            $limit,
+        );
+    }};
+}
@@ -0,0 +1,92 @@
+//! Embedding a flow in a host loop, such as a game engine's frame loop. The
+//! host calls `tick` once per frame with its frame delta; the kernel never
+//! sleeps or runs a loop of its own.
+
+use std::time::Duration;
+use tokio::runtime::{Builder, Handle, Runtime};
+use crate::{AsyncKernel, Result};
+
+pub struct ExternalTickDriver {
+    kernel: AsyncKernel,
+    // Kept alive when the driver built its own runtime.
+    _runtime: Option<Runtime>,
+    handle: Handle,
+    frames: u64,
+    elapsed: Duration,
+}
+
+impl ExternalTickDriver {
+    /// A driver with its own single-worker runtime, so coroutines keep
+    /// running between frames.
+    pub fn new() -> Result<Self> {
+        Self::with_kernel(AsyncKernel::new())
+    }
+
+    pub fn with_kernel(kernel: AsyncKernel) -> Result<Self> {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("async-flow")
+            .enable_all()
+            .build()?;
+        let handle = runtime.handle().clone();
+        Ok(Self {
+            kernel,
+            _runtime: Some(runtime),
+            handle,
+            frames: 0,
+            elapsed: Duration::ZERO,
+        })
+    }
+
+    /// Uses a runtime the host already has. `tick` must not be called from
+    /// inside that runtime's async context.
+    pub fn with_handle(kernel: AsyncKernel, handle: Handle) -> Self {
+        Self {
+            kernel,
+            _runtime: None,
+            handle,
+            frames: 0,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn kernel(&self) -> &AsyncKernel {
+        &self.kernel
+    }
+
+    /// The runtime coroutines are spawned on; build them inside `enter` or
+    /// with `handle().spawn` from host code.
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    /// Runs `build` with the driver's runtime entered, so eagerly spawned
+    /// coroutines such as `AsyncCoroutine::new` can be created from host code.
+    pub fn enter<R>(&self, build: impl FnOnce(&AsyncKernel) -> R) -> R {
+        let _guard = self.handle.enter();
+        build(&self.kernel)
+    }
+
+    /// Steps the flow once with the host's frame delta.
+    pub fn tick(&mut self, delta: Duration) -> Result<()> {
+        self.frames += 1;
+        self.elapsed += delta;
+        self.handle.block_on(self.kernel.update(delta))
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Sum of the deltas passed to `tick`.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// True once the root has no children left or the flow was broken.
+    pub fn is_finished(&self) -> bool {
+        self.handle.block_on(async {
+            self.kernel.is_breaking().await || self.kernel.is_empty().await
+        })
+    }
+}
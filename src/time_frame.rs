@@ -1,10 +1,22 @@
-use std::time::{Duration, Instant};
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How much of each new delta goes into `TimeFrame::smoothed_delta`; the
+/// rest is carried over from the previous frames.
+pub const DELTA_SMOOTHING: f64 = 0.1;
 
 #[derive(Debug, Clone)]
 pub struct TimeFrame {
     pub now: Instant,
     pub last: Instant,
     pub delta: Duration,
+    /// The sum of every delta so far.
+    pub total: Duration,
+    /// How many updates this clock has had.
+    pub frame: u64,
+    /// An exponential moving average of `delta`, steadier for interpolation.
+    pub smoothed_delta: Duration,
 }
 
 impl TimeFrame {
@@ -14,20 +26,31 @@ impl TimeFrame {
             now,
             last: now,
             delta: Duration::ZERO,
+            total: Duration::ZERO,
+            frame: 0,
+            smoothed_delta: Duration::ZERO,
         }
     }
 
     pub fn update(&mut self) {
         let now = Instant::now();
-        self.last = self.now;
-        self.delta = now.duration_since(self.now);
-        self.now = now;
+        self.advance(now.duration_since(self.now), now);
     }
 
     pub fn update_with_delta(&mut self, delta: Duration) {
+        self.advance(delta, self.now + delta);
+    }
+
+    fn advance(&mut self, delta: Duration, now: Instant) {
         self.last = self.now;
         self.delta = delta;
-        self.now = self.last + delta;
+        self.now = now;
+        self.total += delta;
+        self.frame += 1;
+        self.smoothed_delta = match self.frame {
+            1 => delta,
+            _ => self.smoothed_delta.mul_f64(1.0 - DELTA_SMOOTHING) + delta.mul_f64(DELTA_SMOOTHING),
+        };
     }
 }
 
@@ -35,4 +58,29 @@ impl Default for TimeFrame {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+tokio::task_local! {
+    static LAYER_NOW: Instant;
+    static FRAME: TimeFrame;
+}
+
+/// The time flow timers measure against: a kernel layer's scaled clock while
+/// that layer steps, otherwise the tokio clock.
+pub fn flow_now() -> Instant {
+    LAYER_NOW.try_with(|now| *now).unwrap_or_else(|_| Instant::now())
+}
+
+pub(crate) async fn with_layer_time<F: Future>(now: Instant, future: F) -> F::Output {
+    LAYER_NOW.scope(now, future).await
+}
+
+/// The clock of the kernel or layer stepping the current generator: the
+/// kernel's own during root steps, a layer's scaled one during its steps.
+/// `None` outside a kernel step.
+pub fn current_time_frame() -> Option<TimeFrame> {
+    FRAME.try_with(TimeFrame::clone).ok()
+}
+
+pub(crate) async fn with_time_frame<F: Future>(frame: TimeFrame, future: F) -> F::Output {
+    FRAME.scope(frame, future).await
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "bevy")]
+
+use async_flow::bevy_plugin::{AsyncFlowPlugin, FlowDriver};
+use async_flow::*;
+use bevy_app::App;
+use bevy_time::TimePlugin;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_plugin_ticks_flow_every_frame() {
+    let mut app = App::new();
+    app.add_plugins((TimePlugin, AsyncFlowPlugin));
+
+    let timer = Arc::new(Timer::with_name("Cooldown", Duration::from_millis(30)));
+    {
+        let driver = &app.world().resource::<FlowDriver>().0;
+        driver.handle().block_on(driver.kernel().root().add_child(timer.clone()));
+    }
+
+    for _ in 0..20 {
+        app.update();
+        if timer.is_completed() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert!(timer.is_completed());
+    assert!(app.world().resource::<FlowDriver>().0.frames() > 1);
+}
@@ -152,7 +152,7 @@ async fn test_nested_barrier_timing() {
     
     // Add fast timers to first inner barrier
     for i in 0..3 {
-        let timer = Arc::new(Timer::new(Duration::from_micros(80 + i * 20))).named(&format!("Inner1Timer_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_micros(80 + i * 20))).named(format!("Inner1Timer_{}", i));
         
         let completion_count_clone = completion_count.clone();
         timer.set_elapsed_callback(move || {
@@ -164,7 +164,7 @@ async fn test_nested_barrier_timing() {
     
     // Add medium timers to second inner barrier
     for i in 0..2 {
-        let timer = Arc::new(Timer::new(Duration::from_micros(150 + i * 30))).named(&format!("Inner2Timer_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_micros(150 + i * 30))).named(format!("Inner2Timer_{}", i));
         
         let completion_count_clone = completion_count.clone();
         timer.set_elapsed_callback(move || {
@@ -335,10 +335,10 @@ async fn test_parallel_microsecond_barriers() {
     
     // Create 4 parallel barriers with different timer configurations
     for barrier_id in 0..4 {
-        let sub_barrier = Arc::new(Barrier::new()).named(&format!("SubBarrier_{}", barrier_id));
+        let sub_barrier = Arc::new(Barrier::new()).named(format!("SubBarrier_{}", barrier_id));
         
         for timer_id in 0..2 {
-            let timer = Arc::new(Timer::new(Duration::from_micros(80 + barrier_id * 20 + timer_id * 10))).named(&format!("B{}T{}", barrier_id, timer_id));
+            let timer = Arc::new(Timer::new(Duration::from_micros(80 + barrier_id * 20 + timer_id * 10))).named(format!("B{}T{}", barrier_id, timer_id));
             
             let completions_clone = completions.clone();
             timer.set_elapsed_callback(move || {
@@ -413,7 +413,7 @@ async fn test_rapid_sequence_execution() {
     let execution_times = Arc::new(std::sync::Mutex::new(Vec::new()));
     
     for i in 0..6 {
-        let timer = Arc::new(Timer::new(Duration::from_micros(60 + i * 15))).named(&format!("RapidTimer_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_micros(60 + i * 15))).named(format!("RapidTimer_{}", i));
         
         let execution_times_clone = execution_times.clone();
         timer.set_elapsed_callback(move || {
@@ -502,7 +502,7 @@ async fn test_burst_timer_pattern() {
     
     // Create a burst of 8 very fast timers
     for i in 0..8 {
-        let timer = Arc::new(Timer::new(Duration::from_micros(65 + i * 5))).named(&format!("BurstTimer_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_micros(65 + i * 5))).named(format!("BurstTimer_{}", i));
         
         let burst_completions_clone = burst_completions.clone();
         timer.set_elapsed_callback(move || {
@@ -593,18 +593,17 @@ async fn test_microsecond_future_coordination() {
     let kernel = AsyncKernel::new();
     let root = kernel.root();
     
-    let coordination_future = Arc::new(AsyncFuture::<u32>::new()).named("CoordinationData");
+    let (coordination_promise, coordination_future) = AsyncFuture::<u32>::named_pair("CoordinationData");
     let coordination_complete = Arc::new(AtomicBool::new(false));
     
     // Producer task sets future value after timer
     let producer_timer = Arc::new(Timer::new(Duration::from_micros(150))).named("ProducerTimer");
     
-    let coordination_future_clone = coordination_future.clone();
     producer_timer.set_elapsed_callback(move || {
         tokio::spawn({
-            let coordination_future = coordination_future_clone.clone();
+            let coordination_promise = coordination_promise.clone();
             async move {
-                coordination_future.set_value(42).await;
+                coordination_promise.set_value(42).await;
             }
         });
     }).await;
@@ -639,7 +638,7 @@ async fn test_dynamic_barrier_microseconds() {
     
     // Add initial set of timers
     for i in 0..3 {
-        let timer = Arc::new(Timer::new(Duration::from_micros(90 + i * 20))).named(&format!("InitialTimer_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_micros(90 + i * 20))).named(format!("InitialTimer_{}", i));
         
         let completion_tracker_clone = completion_tracker.clone();
         timer.set_elapsed_callback(move || {
@@ -657,7 +656,7 @@ async fn test_dynamic_barrier_microseconds() {
             sleep(Duration::from_micros(50)).await;
             
             for i in 3..5 {
-                let timer = Arc::new(Timer::new(Duration::from_micros(70 + i * 15))).named(&format!("DynamicTimer_{}", i));
+                let timer = Arc::new(Timer::new(Duration::from_micros(70 + i * 15))).named(format!("DynamicTimer_{}", i));
                 
                 let completion_tracker_clone = completion_tracker.clone();
                 timer.set_elapsed_callback(move || {
@@ -702,7 +701,7 @@ async fn test_cascading_microsecond_triggers() {
         let trigger = Arc::new(Trigger::new({
             let cascade_state = cascade_state.clone();
             move || cascade_state.load(Ordering::Relaxed) >= level
-        })).named(&format!("CascadeTrigger_{}", level));
+        })).named(format!("CascadeTrigger_{}", level));
         
         let cascade_state_clone = cascade_state.clone();
         let cascade_times_clone = cascade_times.clone();
@@ -745,7 +744,7 @@ async fn test_extreme_parallel_execution() {
         match i % 3 {
             0 => {
                 // Timer component
-                let timer = Arc::new(Timer::new(Duration::from_micros(80 + (i as u64) * 10))).named(&format!("ParallelTimer_{}", i));
+                let timer = Arc::new(Timer::new(Duration::from_micros(80 + (i as u64) * 10))).named(format!("ParallelTimer_{}", i));
                 
                 let execution_count_clone = execution_count.clone();
                 timer.set_elapsed_callback(move || {
@@ -763,13 +762,13 @@ async fn test_extreme_parallel_execution() {
                         execution_count.fetch_add(1, Ordering::Relaxed);
                         Ok(())
                     }
-                })).named(&format!("ParallelTask_{}", i));
+                })).named(format!("ParallelTask_{}", i));
                 
                 parallel_barrier.add_child(task).await;
             },
             2 => {
                 // Periodic timer component (with quick completion)
-                let periodic = Arc::new(PeriodicTimer::new(Duration::from_micros(60))).named(&format!("ParallelPeriodic_{}", i));
+                let periodic = Arc::new(PeriodicTimer::new(Duration::from_micros(60))).named(format!("ParallelPeriodic_{}", i));
                 
                 let execution_count_clone = execution_count.clone();
                 let periodic_clone = periodic.clone();
@@ -0,0 +1,73 @@
+#![cfg(feature = "http")]
+
+use async_flow::*;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+// Answers every request with whatever status `status` holds at the time.
+async fn serve(status: Arc<AtomicU16>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/health", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let status = status.load(Ordering::Relaxed);
+            tokio::spawn(async move {
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).await;
+                let response = format!("HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    url
+}
+
+async fn step_until(kernel: &AsyncKernel, done: impl Fn() -> bool) {
+    for _ in 0..500 {
+        if done() {
+            return;
+        }
+        kernel.update(Duration::from_millis(1)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(2)).await;
+    }
+    panic!("condition not reached");
+}
+
+#[tokio::test]
+async fn test_http_check_until_healthy_feeds_trigger() {
+    let status = Arc::new(AtomicU16::new(503));
+    let url = serve(status.clone()).await;
+
+    let kernel = AsyncKernel::new();
+    let check = Arc::new(HttpCheck::new(url, Duration::from_millis(5)).policy(CheckPolicy::UntilHealthy));
+    let ready = Arc::new(Trigger::new(check.condition()));
+    kernel.root().add_child(check.clone()).await;
+    kernel.root().add_child(ready.clone()).await;
+
+    step_until(&kernel, || check.consecutive_failures() >= 2).await;
+    assert_eq!(check.health(), Health::Unhealthy);
+    assert!(!check.is_completed() && !ready.is_completed());
+
+    status.store(200, Ordering::Relaxed);
+    step_until(&kernel, || check.is_completed() && ready.is_completed()).await;
+    assert!(check.is_healthy() && !check.is_failed());
+    assert_eq!(check.consecutive_failures(), 0);
+}
+
+#[tokio::test]
+async fn test_http_check_fails_after_limit() {
+    let status = Arc::new(AtomicU16::new(500));
+    let url = serve(status).await;
+
+    let kernel = AsyncKernel::new();
+    let check = Arc::new(HttpCheck::new(url, Duration::from_millis(5)).policy(CheckPolicy::FailAfter(3)));
+    kernel.root().add_child(check.clone()).await;
+
+    step_until(&kernel, || check.is_completed()).await;
+    assert!(check.is_failed());
+    assert_eq!(check.checks(), 3);
+}
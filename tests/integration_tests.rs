@@ -116,7 +116,7 @@ async fn test_periodic_timer_functionality() {
     kernel.run_for(Duration::from_millis(250)).await.unwrap();
     
     let final_count = tick_count.load(Ordering::Relaxed);
-    assert!(final_count >= 4 && final_count <= 6); // Should tick ~5 times in 250ms
+    assert!((4..=6).contains(&final_count)); // Should tick ~5 times in 250ms
 }
 
 #[tokio::test]
@@ -153,14 +153,13 @@ async fn test_trigger_functionality() {
 
 #[tokio::test]
 async fn test_future_functionality() {
-    let future = Arc::new(AsyncFuture::<String>::new()).named("TestFuture");
+    let (promise, future) = AsyncFuture::<String>::named_pair("TestFuture");
     let result = Arc::new(tokio::sync::Mutex::new(String::new()));
     
     let producer = Arc::new(AsyncCoroutine::new({
-        let future = future.clone();
         async move {
             sleep(Duration::from_millis(100)).await;
-            future.set_value("Hello Future!".to_string()).await;
+            promise.set_value("Hello Future!".to_string()).await;
             Ok(())
         }
     })).named("Producer");
@@ -292,4 +291,524 @@ async fn test_complex_flow_composition() {
     
     // Verify cleanup ran last
     assert_eq!(log[5], "Cleanup");
-}
\ No newline at end of file
+}
+struct CountingWaker(AtomicU32);
+
+impl futures::task::ArcWake for CountingWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[tokio::test]
+async fn test_future_poll_registers_waker() {
+    use std::future::Future;
+    use std::task::{Context, Poll};
+
+    let (promise, future) = AsyncFuture::<u32>::pair();
+    let mut future = &*future;
+    let counter = Arc::new(CountingWaker(AtomicU32::new(0)));
+    let waker = futures::task::waker(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    assert_eq!(std::pin::Pin::new(&mut future).poll(&mut cx), Poll::Pending);
+    assert_eq!(std::pin::Pin::new(&mut future).poll(&mut cx), Poll::Pending);
+    promise.set_value(7).await;
+    assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+    assert_eq!(std::pin::Pin::new(&mut future).poll(&mut cx), Poll::Ready(7));
+    assert_eq!(future.await, 7);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_future_wait_timeout_and_try_get() {
+    let (promise, future) = AsyncFuture::<String>::pair();
+    assert_eq!(future.try_get(), None);
+    assert_eq!(future.wait_timeout(Duration::from_millis(50)).await, None);
+
+    tokio::spawn(async move {
+        sleep(Duration::from_millis(20)).await;
+        promise.set_value("ready".to_string()).await;
+    });
+    assert_eq!(future.wait_timeout(Duration::from_millis(50)).await.as_deref(), Some("ready"));
+    assert_eq!(future.try_get().as_deref(), Some("ready"));
+}
+
+#[tokio::test]
+async fn test_future_reset_for_next_round() {
+    let (promise, future) = AsyncFuture::<u32>::pair();
+    for round in 1..=3 {
+        assert!(!future.is_completed());
+        promise.set_value(round).await;
+        assert!(future.is_completed());
+        assert_eq!(future.wait().await, round);
+        assert_eq!(future.set_count(), round as usize);
+        future.reset().await;
+    }
+
+    assert_eq!(future.try_get(), None);
+    assert!(future.is_running());
+    assert_eq!(future.set_count(), 3);
+}
+
+#[tokio::test]
+async fn test_future_error_releases_waiters() {
+    let (promise, future) = AsyncFuture::<u32>::pair();
+    let waiter = {
+        let future = future.clone();
+        tokio::spawn(async move { future.wait_result().await.map_err(|e| e.to_string()) })
+    };
+
+    sleep(Duration::from_millis(10)).await;
+    promise.set_error("upload failed");
+
+    assert_eq!(waiter.await.unwrap(), Err("upload failed".to_string()));
+    assert!(future.is_failed());
+    assert_eq!(future.error().map(|e| e.message().to_string()).as_deref(), Some("upload failed"));
+
+    future.reset().await;
+    assert!(future.error().is_none());
+    promise.set_value(3).await;
+    assert_eq!(future.wait_result().await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_future_errors_reach_derived_futures() {
+    let kernel = AsyncKernel::new();
+    let (promise, failing) = AsyncFuture::<u32>::pair();
+    let (other_promise, other) = AsyncFuture::<u32>::pair();
+    let mapped = failing.map(|value| value + 1);
+    let chained = other.and_then(move |_| failing.clone());
+    let zipped = other.zip(&mapped);
+    let (_, ok) = AsyncFuture::<u32>::pair();
+    let joined = join_all(vec![ok.clone(), mapped.clone()]);
+    let (last_promise, last) = AsyncFuture::<u32>::pair();
+    let selected = select_any(vec![mapped.clone(), last.clone()]);
+    kernel.root().add_child(joined.clone()).await;
+    kernel.root().add_child(selected.clone()).await;
+
+    promise.set_error("disk full");
+    other_promise.set_value(1).await;
+    assert_eq!(mapped.wait_result().await.unwrap_err().to_string(), "disk full");
+    assert!(chained.wait_result().await.is_err());
+    assert!(zipped.wait_result().await.is_err());
+
+    kernel.update(Duration::ZERO).await.unwrap();
+    assert_eq!(joined.wait_result().await.unwrap_err().to_string(), "disk full");
+    assert!(!selected.is_completed());
+
+    last_promise.set_error("timeout");
+    kernel.update(Duration::ZERO).await.unwrap();
+    assert!(selected.wait_result().await.is_err());
+}
+
+#[test]
+fn test_external_tick_driver_steps_per_frame() {
+    let mut driver = ExternalTickDriver::new().unwrap();
+    let timer = Arc::new(Timer::new(Duration::from_millis(30)));
+    let loaded = Arc::new(AtomicBool::new(false));
+    let loader = driver.enter(|_| {
+        let loaded = loaded.clone();
+        Arc::new(AsyncCoroutine::new(async move {
+            sleep(Duration::from_millis(5)).await;
+            loaded.store(true, Ordering::Relaxed);
+            Ok(())
+        }))
+    });
+    driver.handle().block_on(async {
+        driver.kernel().root().add_child(timer.clone()).await;
+        driver.kernel().root().add_child(loader.clone()).await;
+    });
+
+    let frame = Duration::from_millis(16);
+    while !driver.is_finished() && driver.frames() < 100 {
+        driver.tick(frame).unwrap();
+        std::thread::sleep(frame);
+    }
+
+    assert!(timer.is_completed() && loader.is_completed());
+    assert!(loaded.load(Ordering::Relaxed));
+    assert_eq!(driver.elapsed(), frame * driver.frames() as u32);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_kernel_layers() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let ui = kernel.layer("ui").await;
+    let simulation = kernel.layer("simulation").await;
+    assert!(Arc::ptr_eq(&kernel.layer("ui").await, &ui));
+    assert_eq!(
+        kernel.layers().iter().map(|layer| layer.name().to_string()).collect::<Vec<_>>(),
+        vec!["ui", "simulation"],
+    );
+
+    let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+    for (layer, label) in [(&simulation, "simulation"), (&ui, "ui")] {
+        let order = order.clone();
+        layer
+            .add_child(Arc::new(Trigger::new(move || {
+                order.lock().unwrap().push(label);
+                true
+            })))
+            .await;
+    }
+    let menu = Arc::new(Timer::new(Duration::from_millis(10)));
+    let physics = Arc::new(Timer::new(Duration::from_millis(10)));
+    ui.add_child(menu.clone()).await;
+    simulation.add_child(physics.clone()).await;
+    simulation.set_time_scale(0.5);
+
+    let mut events = kernel.subscribe();
+    kernel.advance(12).await.unwrap();
+    assert_eq!(*order.lock().unwrap(), vec!["ui", "simulation"]);
+    assert!(menu.is_completed());
+    assert!(!physics.is_completed());
+    assert!(events.try_recv().is_ok());
+
+    simulation.pause();
+    kernel.advance(20).await.unwrap();
+    assert!(!physics.is_completed());
+    simulation.resume();
+    kernel.advance(10).await.unwrap();
+    assert!(physics.is_completed());
+    assert!(kernel.is_empty().await);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_deactivated_timer_pauses_on_layer_clock() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let slow = kernel.layer("slow").await;
+    slow.set_time_scale(0.5);
+    let timer = Arc::new(Timer::new(Duration::from_millis(20)));
+    slow.add_child(timer.clone()).await;
+
+    kernel.advance(10).await.unwrap();
+    timer.deactivate();
+    kernel.advance(100).await.unwrap();
+    timer.activate();
+    kernel.advance(10).await.unwrap();
+    assert!(!timer.is_completed());
+    let remaining = timer.remaining().await;
+    assert!(remaining > Duration::from_millis(8) && remaining < Duration::from_millis(12), "{:?}", remaining);
+
+    kernel.advance(24).await.unwrap();
+    assert!(timer.is_completed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_sub_kernel() {
+    let outer = async_flow::testing::TestKernel::new();
+    let inner = AsyncKernel::new();
+    let timer = Arc::new(Timer::with_name("InnerTimer", Duration::from_millis(20)));
+    inner.root().add_child(timer.clone()).await;
+    let sub = Arc::new(SubKernel::with_name("Minigame", inner.clone()));
+    sub.set_time_scale(2.0);
+    outer.root().add_child(sub.clone()).await;
+    let mut events = outer.subscribe();
+
+    outer.advance(8).await.unwrap();
+    assert!(!timer.is_completed());
+    outer.advance(4).await.unwrap();
+    assert!(timer.is_completed());
+    assert!(sub.is_completed());
+    let mut saw_inner = false;
+    while let Ok(event) = events.try_recv() {
+        saw_inner |= matches!(event, FlowEvent::NodeCompleted { id, .. } if id == timer.id());
+    }
+    assert!(saw_inner);
+
+    let breaking = AsyncKernel::new();
+    breaking.root().add_child(Arc::new(Timer::new(Duration::from_secs(60)))).await;
+    let sub = Arc::new(SubKernel::new(breaking.clone()));
+    outer.root().add_child(sub.clone()).await;
+    outer.advance(2).await.unwrap();
+    breaking.break_flow().await;
+    outer.advance(2).await.unwrap();
+    assert!(sub.is_completed());
+    assert!(!outer.is_breaking().await);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_bridge_between_kernels() {
+    let loader = async_flow::testing::TestKernel::new();
+    let game = async_flow::testing::TestKernel::new();
+    let level: Bridge<String> = Bridge::new();
+    let loaded: Bridge<()> = Bridge::new();
+
+    let (promise, produced) = AsyncFuture::pair();
+    level.forward(&produced);
+    let load = Arc::new(Timer::with_name("Load", Duration::from_millis(10)));
+    loaded.forward_completion(load.clone(), ());
+    loader.root().add_child(load).await;
+
+    let waiting_level = level.named_future("Level").await;
+    let waiting_load = loaded.future().await;
+    let start = Arc::new(Sequence::new());
+    start.add_child(waiting_load.clone()).await;
+    start.add_child(waiting_level.clone()).await;
+    game.root().add_child(start.clone()).await;
+
+    game.advance(5).await.unwrap();
+    loader.advance(12).await.unwrap();
+    tokio::task::yield_now().await;
+    game.advance(1).await.unwrap();
+    assert!(waiting_load.is_ready());
+    assert!(!start.is_completed());
+
+    promise.set_value("forest".to_string()).await;
+    tokio::task::yield_now().await;
+    game.advance(1).await.unwrap();
+    assert!(start.is_completed());
+    assert_eq!(level.future().await.get_value().await.as_deref(), Some("forest"));
+}
+
+struct ApiClient {
+    base_url: String,
+    calls: AtomicU32,
+}
+
+#[tokio::test]
+async fn test_kernel_resources() {
+    let kernel = AsyncKernel::new();
+    kernel.insert_resource(ApiClient { base_url: "http://api".into(), calls: AtomicU32::new(0) });
+    kernel.insert_resource(3u32);
+    assert!(kernel.resources().contains::<ApiClient>());
+
+    let fetch = AsyncCoroutine::lazy("Fetch", |ctx| async move {
+        let client = ctx.resource::<ApiClient>().ok_or("no client")?;
+        assert_eq!(client.base_url, "http://api");
+        client.calls.fetch_add(1, Ordering::SeqCst);
+        // The registry is also in scope for code the task calls.
+        assert_eq!(resource::<u32>().as_deref(), Some(&3));
+        Ok(())
+    });
+    let ready = Trigger::with_name("Ready", || {
+        resource::<ApiClient>().is_some_and(|client| client.calls.load(Ordering::SeqCst) > 0)
+    });
+    kernel.root().add_child(SequenceBuilder::new().then(Arc::new(fetch)).then(Arc::new(ready)).build()).await;
+    tokio::time::timeout(Duration::from_secs(5), kernel.run_until_complete()).await.unwrap().unwrap();
+
+    assert!(kernel.take_errors().is_empty());
+    assert_eq!(kernel.resource::<ApiClient>().unwrap().calls.load(Ordering::SeqCst), 1);
+    assert!(resource::<ApiClient>().is_none());
+    assert_eq!(kernel.remove_resource::<u32>().as_deref(), Some(&3));
+    assert!(kernel.resource::<u32>().is_none());
+}
+
+#[tokio::test]
+async fn test_time_frame_counters() {
+    let kernel = AsyncKernel::new();
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    {
+        let seen = seen.clone();
+        kernel
+            .root()
+            .add_child(Arc::new(Trigger::new(move || {
+                let frame = current_time_frame().expect("stepped by a kernel");
+                seen.lock().unwrap().push((frame.frame, frame.total));
+                false
+            })))
+            .await;
+    }
+    let slow_motion = kernel.layer("slow").await;
+    slow_motion.set_time_scale(0.5);
+
+    for millis in [10, 10, 40] {
+        kernel.update(Duration::from_millis(millis)).await.unwrap();
+    }
+
+    let frame = kernel.time_frame().await;
+    assert_eq!(frame.frame, 3);
+    assert_eq!(frame.total, Duration::from_millis(60));
+    assert_eq!(frame.delta, Duration::from_millis(40));
+    // Smoothing moves a tenth of the way towards each new delta.
+    assert_eq!(frame.smoothed_delta, Duration::from_millis(13));
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![(1, Duration::from_millis(10)), (2, Duration::from_millis(20)), (3, Duration::from_millis(60))],
+    );
+    assert_eq!(slow_motion.time_frame().total, Duration::from_millis(30));
+    assert!(current_time_frame().is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_run_until_and_run_while() {
+    let kernel = AsyncKernel::new();
+    let simulation = Arc::new(Node::with_name("Simulation"));
+    simulation.add_child(Arc::new(Timer::with_name("Warmup", Duration::from_millis(20)))).await;
+    simulation.add_child(Arc::new(Timer::with_name("Physics", Duration::from_millis(50)))).await;
+    kernel.root().add_child(simulation.clone()).await;
+
+    let started = tokio::time::Instant::now();
+    kernel
+        .run_until(|kernel| async move {
+            kernel.root().find_by_name("Warmup").await.is_none_or(|warmup| warmup.is_completed())
+        })
+        .await
+        .unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(20));
+    assert!(started.elapsed() < Duration::from_millis(50));
+
+    let simulation = simulation.clone();
+    kernel
+        .run_while(move |_| {
+            let simulation = simulation.clone();
+            async move {
+                let children = simulation.children().await;
+                children.iter().any(|child| child.is_active() && !child.is_completed())
+            }
+        })
+        .await
+        .unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    assert!(kernel.root().find_by_name("Physics").await.is_none_or(|physics| physics.is_completed()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_kernel_handle() {
+    let kernel = AsyncKernel::new();
+    let timer = Arc::new(Timer::new(Duration::from_millis(30)));
+    kernel.root().add_child(timer.clone()).await;
+
+    // Runs to completion on its own task.
+    let handle = kernel.start();
+    assert!(handle.is_running());
+    handle.join().await.unwrap();
+    assert!(timer.is_completed());
+
+    // Stops on request, leaving unfinished work in the tree.
+    let forever = Arc::new(Trigger::new(|| false));
+    kernel.root().add_child(forever.clone()).await;
+    let handle = kernel.start();
+    sleep(Duration::from_millis(10)).await;
+    assert!(handle.is_running());
+    handle.stop();
+    handle.join().await.unwrap();
+    assert!(!forever.is_completed());
+    assert!(!kernel.is_empty().await);
+
+    // Completing the kernel it was started from ends the loop too.
+    let handle = kernel.start();
+    sleep(Duration::from_millis(10)).await;
+    kernel.complete();
+    tokio::time::timeout(Duration::from_secs(1), handle.join()).await.unwrap().unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_awaiting_the_kernel() {
+    let build = || async {
+        let kernel = AsyncKernel::new();
+        let timer = Arc::new(Timer::new(Duration::from_millis(20)));
+        kernel.root().add_child(timer.clone()).await;
+        (kernel, timer)
+    };
+
+    let (expected, expected_timer) = build().await;
+    expected.run_until_complete().await.unwrap();
+
+    let (kernel, timer) = build().await;
+    let (result, side) = tokio::join!(&kernel, async {
+        sleep(Duration::from_millis(5)).await;
+        "side"
+    });
+    result.unwrap();
+    assert_eq!(side, "side");
+    assert_eq!(timer.is_completed(), expected_timer.is_completed());
+    assert_eq!(kernel.is_empty().await, expected.is_empty().await);
+
+    // The owned form finishes before a later deadline in `select!`.
+    let (kernel, timer) = build().await;
+    tokio::select! {
+        result = kernel.clone() => result.unwrap(),
+        _ = sleep(Duration::from_secs(1)) => panic!("flow never finished"),
+    }
+    assert!(timer.is_completed() && kernel.is_empty().await);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tick_stream() {
+    use futures::StreamExt;
+
+    let kernel = AsyncKernel::new();
+    kernel.root().add_child(Arc::new(Timer::new(Duration::from_millis(20)))).await;
+
+    let mut ticks = kernel.tick_stream();
+    let mut frames = Vec::new();
+    let mut shutdown = Box::pin(sleep(Duration::from_secs(1)));
+    loop {
+        tokio::select! {
+            tick = ticks.next() => match tick {
+                Some(frame) => frames.push(frame.unwrap().frame),
+                None => break,
+            },
+            _ = &mut shutdown => panic!("flow never finished"),
+        }
+    }
+    assert!(frames.len() >= 20);
+    assert!(frames.windows(2).all(|pair| pair[1] == pair[0] + 1));
+    assert!(kernel.is_empty().await);
+
+    // Completing the kernel ends the stream, even with work left.
+    kernel.root().add_child(Arc::new(Trigger::new(|| false))).await;
+    let mut ticks = kernel.tick_stream();
+    assert!(ticks.next().await.is_some());
+    kernel.complete();
+    let rest = tokio::time::timeout(Duration::from_secs(1), ticks.count()).await.unwrap();
+    assert_eq!(rest, 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_dag_diamond() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let task = |name: &'static str, millis: u64| {
+        let timer = Arc::new(Timer::with_name(name, Duration::from_millis(millis)));
+        let order = order.clone();
+        timer.base().set_on_complete(move || order.lock().unwrap().push(name));
+        timer
+    };
+    let fetch = task("fetch", 10);
+    let resize = task("resize", 30);
+    let scan = task("scan", 10);
+    let publish = task("publish", 10);
+
+    let dag = Arc::new(Dag::with_name("Upload"));
+    dag.add(publish.clone()).after(&resize).after(&scan);
+    dag.add(resize.clone()).after(&fetch);
+    dag.add(scan.clone()).after(&fetch);
+    dag.add(fetch.clone());
+    assert_eq!(dag.dependencies_of(publish.id()), vec![resize.id(), scan.id()]);
+    kernel.root().add_child(dag.clone()).await;
+
+    assert!(kernel.advance_until_completed(dag.as_ref(), Duration::from_millis(200)).await.unwrap());
+    assert_eq!(*order.lock().unwrap(), vec!["fetch", "scan", "resize", "publish"]);
+    assert!(!dag.is_failed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_dag_failures_and_cycles() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let broken = Arc::new(Trigger::new(|| false));
+    let downstream = Arc::new(Timer::new(Duration::from_millis(5)));
+    let independent = Arc::new(Timer::new(Duration::from_millis(5)));
+    let dag = Arc::new(Dag::new());
+    dag.add(broken.clone());
+    dag.add(downstream.clone()).after(&broken);
+    dag.add(independent.clone());
+    kernel.root().add_child(dag.clone()).await;
+
+    kernel.advance(10).await.unwrap();
+    broken.base().fail("bad input");
+    kernel.advance(2).await.unwrap();
+    assert!(downstream.is_failed());
+    assert!(independent.is_completed() && !independent.is_failed());
+    assert!(dag.is_completed());
+
+    let a = Arc::new(Timer::new(Duration::from_millis(5)));
+    let b = Arc::new(Timer::new(Duration::from_millis(5)));
+    let cyclic = Arc::new(Dag::new());
+    cyclic.add(a.clone()).after(&b);
+    cyclic.add(b.clone()).after(&a);
+    kernel.root().add_child(cyclic.clone()).await;
+    kernel.advance(2).await.unwrap();
+    assert!(cyclic.is_failed());
+}
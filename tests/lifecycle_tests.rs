@@ -0,0 +1,322 @@
+use async_flow::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_sequence_lifecycle_hooks() {
+    let kernel = AsyncKernel::new();
+    let started = Arc::new(AtomicU32::new(0));
+    let completed = Arc::new(AtomicU32::new(0));
+
+    let sequence = Arc::new(Sequence::new()).named("ObservedSequence");
+    sequence.base().set_on_start({
+        let started = started.clone();
+        move || {
+            started.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+    sequence.base().set_on_complete({
+        let completed = completed.clone();
+        move || {
+            completed.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    sequence.add_child(Arc::new(Timer::new(Duration::from_millis(10))).named("First")).await;
+    sequence.add_child(Arc::new(Timer::new(Duration::from_millis(10))).named("Second")).await;
+
+    kernel.root().add_child(sequence.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert_eq!(started.load(Ordering::Relaxed), 1);
+    assert_eq!(completed.load(Ordering::Relaxed), 1);
+    assert!(sequence.is_completed());
+    assert!(!sequence.is_failed());
+}
+
+#[tokio::test]
+async fn test_failed_coroutine_fires_on_fail() {
+    let kernel = AsyncKernel::new();
+    let completed = Arc::new(AtomicU32::new(0));
+    let failure = Arc::new(std::sync::Mutex::new(None::<String>));
+
+    let task = Arc::new(AsyncCoroutine::new(async {
+        Err("disk full".into())
+    })).named("FailingTask");
+
+    task.base().set_on_complete({
+        let completed = completed.clone();
+        move || {
+            completed.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+    task.base().set_on_fail({
+        let failure = failure.clone();
+        move |reason| {
+            *failure.lock().unwrap() = Some(reason.to_string());
+        }
+    });
+
+    let barrier = Arc::new(Barrier::new()).named("Barrier");
+    barrier.add_child(task.clone()).await;
+    kernel.root().add_child(barrier.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert!(task.is_completed());
+    assert!(task.is_failed());
+    assert_eq!(failure.lock().unwrap().as_deref(), Some("disk full"));
+    assert_eq!(completed.load(Ordering::Relaxed), 0);
+    assert!(barrier.is_completed());
+}
+
+#[derive(Default)]
+struct EventLog {
+    events: std::sync::Mutex<Vec<String>>,
+}
+
+impl LifecycleObserver for EventLog {
+    fn on_start(&self, generator: &GeneratorBase) {
+        self.events.lock().unwrap().push(format!("start {}", generator.name().unwrap_or_default()));
+    }
+
+    fn on_complete(&self, generator: &GeneratorBase) {
+        self.events.lock().unwrap().push(format!("complete {}", generator.name().unwrap_or_default()));
+    }
+}
+
+#[tokio::test]
+async fn test_observer_sees_descendants_added_later() {
+    let kernel = AsyncKernel::new();
+    let log = Arc::new(EventLog::default());
+    kernel.add_observer(log.clone());
+
+    let sequence = Arc::new(Sequence::new()).named("Outer");
+    kernel.root().add_child(sequence.clone()).await;
+    sequence.add_child(Arc::new(Timer::new(Duration::from_millis(5))).named("Inner")).await;
+    kernel.run_until_complete().await.unwrap();
+
+    let events = log.events.lock().unwrap().clone();
+    assert_eq!(events, vec!["start Outer", "start Inner", "complete Inner", "complete Outer"]);
+}
+
+#[tokio::test]
+async fn test_kernel_event_stream() {
+    let kernel = AsyncKernel::new();
+    let mut events = kernel.subscribe();
+
+    let task = Arc::new(AsyncCoroutine::with_name("Upload", async { Err("refused".into()) }));
+    kernel.root().add_child(task.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+    kernel.break_flow().await;
+
+    let mut received = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        received.push(event);
+    }
+    let name = Some("Upload".to_string());
+    assert_eq!(received, vec![
+        FlowEvent::NodeAdded { id: task.id(), name: name.clone(), parent: Some(kernel.root().id()) },
+        FlowEvent::NodeStarted { id: task.id(), name: name.clone() },
+        FlowEvent::NodeFailed { id: task.id(), name, reason: "refused".to_string() },
+        FlowEvent::KernelBreak,
+    ]);
+}
+
+#[tokio::test]
+async fn test_wait_completed_from_outside_the_kernel() {
+    let kernel = Arc::new(AsyncKernel::new());
+    let sequence: Arc<dyn Generator> = SequenceBuilder::with_name("Upload")
+        .timer(Duration::from_millis(10))
+        .timer(Duration::from_millis(10))
+        .build();
+    let failing: Arc<dyn Generator> = Arc::new(AsyncCoroutine::new(async { Err("boom".into()) }));
+    kernel.root().add_child(sequence.clone()).await;
+    kernel.root().add_child(failing.clone()).await;
+
+    let runner = {
+        let kernel = kernel.clone();
+        tokio::spawn(async move { kernel.run_until_complete().await.unwrap() })
+    };
+
+    tokio::time::timeout(Duration::from_secs(2), async {
+        sequence.wait_completed().await;
+        failing.wait_completed().await;
+    })
+    .await
+    .expect("subtrees should finish");
+    assert!(sequence.is_completed());
+    assert!(failing.is_failed());
+
+    sequence.wait_completed().await;
+    runner.await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_kernel_reports_stalls() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let capture = async_flow::testing::LogCapture::install();
+    let mut events = kernel.subscribe();
+    kernel.set_stall_timeout(Some(Duration::from_millis(100)));
+
+    let stuck = Arc::new(Trigger::with_name("ServerReady", || false).with_condition_name("server_responds_to_ping"));
+    let main = SequenceBuilder::with_name("Main")
+        .timer(Duration::from_millis(20))
+        .then(stuck.clone())
+        .build();
+    kernel.root().add_child(main.clone()).await;
+    kernel.layer("Ui").await.node().add_child(Arc::new(Trigger::with_name("MenuReady", || false))).await;
+
+    kernel.advance(110).await.unwrap();
+    assert!(!capture.contains("Flow stalled"));
+    kernel.advance(20).await.unwrap();
+    assert!(capture.contains("Flow stalled"));
+    assert!(capture.contains("Trigger ServerReady waiting on server_responds_to_ping"));
+
+    let mut stalled = None;
+    while let Ok(event) = events.try_recv() {
+        if let FlowEvent::Stalled { running, .. } = event {
+            stalled = Some(running);
+        }
+    }
+    let running = stalled.expect("a stall event");
+    let names: Vec<_> = running.iter().filter_map(|node| node.name.as_deref()).collect();
+    assert_eq!(names, vec!["Main", "ServerReady", "MenuReady"]);
+    assert_eq!(running[1].condition.as_deref(), Some("server_responds_to_ping"));
+    assert_eq!(running[1].kind, "Trigger");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_break_cancels_running_coroutines() {
+    let kernel = AsyncKernel::new();
+    let ticks = Arc::new(AtomicU32::new(0));
+    let worker = Arc::new(AsyncCoroutine::with_name("Worker", {
+        let ticks = ticks.clone();
+        async move {
+            loop {
+                ticks.fetch_add(1, Ordering::Relaxed);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+    }));
+    let nested = Arc::new(Sequence::new());
+    nested.add_child(worker.clone()).await;
+    kernel.root().add_child(nested).await;
+    kernel.update(Duration::from_millis(1)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(25)).await;
+
+    kernel.break_flow().await;
+    assert_eq!(worker.state(), GeneratorState::Failed);
+    let seen = ticks.load(Ordering::Relaxed);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(ticks.load(Ordering::Relaxed), seen);
+}
+
+#[tokio::test]
+async fn test_cancel_drops_unstarted_lazy_coroutine() {
+    let ran = Arc::new(AtomicU32::new(0));
+    let lazy = AsyncCoroutine::lazy("Idle", {
+        let ran = ran.clone();
+        move |_ctx| async move {
+            ran.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    });
+    lazy.cancel();
+    assert_eq!(lazy.state(), GeneratorState::Failed);
+
+    lazy.step().await.unwrap();
+    tokio::task::yield_now().await;
+    assert_eq!(ran.load(Ordering::Relaxed), 0);
+    assert_eq!(lazy.state(), GeneratorState::Failed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_kernel_collects_errors() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let stage = Arc::new(Sequence::with_name("Stage"));
+    stage
+        .add_child(Arc::new(AsyncCoroutine::with_name("Upload", async { Err("refused".into()) })))
+        .await;
+    kernel.root().add_child(stage).await;
+    kernel.root().add_child(Arc::new(Timer::with_name("Later", Duration::from_millis(20)))).await;
+    kernel
+        .root()
+        .add_child(Arc::new(AsyncCoroutine::with_name("Report", async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Err("disk full".into())
+        })))
+        .await;
+
+    kernel.advance(30).await.unwrap();
+    let errors = kernel.take_errors();
+    let summary: Vec<_> = errors.iter().map(|e| (e.path.as_str(), e.error.message())).collect();
+    assert_eq!(summary, vec![("Root/Stage/Upload", "refused"), ("Root/Report", "disk full")]);
+    assert!(errors[0].at < errors[1].at);
+    assert!(kernel.take_errors().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_cancelled_nodes_are_not_errors() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let slow: Arc<dyn Generator> = Arc::new(AsyncCoroutine::with_name("Slow", async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Ok(())
+    }));
+    let fast: Arc<dyn Generator> = Arc::new(AsyncCoroutine::with_name("Fast", async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Ok(())
+    }));
+    let race = combine::first_of([slow.clone(), fast.clone()]);
+    let handled = Arc::new(AtomicU32::new(0));
+    let counter = handled.clone();
+    kernel.set_error_handler(move |_, _| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    kernel.root().add_child(race.clone()).await;
+
+    kernel.advance(20).await.unwrap();
+    assert!(race.is_completed() && !race.is_failed());
+    assert!(slow.is_cancelled() && slow.is_failed());
+    assert!(!fast.is_cancelled());
+    assert!(kernel.take_errors().is_empty());
+    assert_eq!(handled.load(Ordering::Relaxed), 0);
+}
+
+#[tokio::test]
+async fn test_kernel_error_log_is_capped() {
+    let kernel = AsyncKernel::new();
+    kernel.set_error_capacity(3);
+    for index in 0..5 {
+        let message = format!("error {}", index);
+        kernel.root().add_child(Arc::new(AsyncCoroutine::new(async move { Err(message.into()) }))).await;
+    }
+    kernel.run_until_complete().await.unwrap();
+
+    assert_eq!(kernel.take_errors().len(), 3);
+    assert_eq!(kernel.dropped_errors(), 2);
+}
+
+#[tokio::test]
+async fn test_kernel_error_handler() {
+    let kernel = AsyncKernel::new();
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let handler_seen = seen.clone();
+    kernel.set_error_handler(move |path, error| {
+        handler_seen.lock().unwrap().push(format!("{}: {}", path, error));
+    });
+
+    let stage = Arc::new(Sequence::with_name("Stage"));
+    stage
+        .add_child(Arc::new(AsyncCoroutine::with_name("Upload", async { Err("refused".into()) })))
+        .await;
+    kernel.root().add_child(stage).await;
+    kernel.run_until_complete().await.unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec!["Root/Stage/Upload: refused".to_string()]);
+
+    kernel.clear_error_handler();
+    kernel.root().add_child(Arc::new(AsyncCoroutine::new(async { Err("again".into()) }))).await;
+    kernel.run_until_complete().await.unwrap();
+    assert_eq!(seen.lock().unwrap().len(), 1);
+    assert_eq!(kernel.take_errors().len(), 2);
+}
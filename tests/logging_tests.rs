@@ -0,0 +1,151 @@
+use async_flow::testing::LogCapture;
+use async_flow::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_log_verbosity_inheritance() {
+    let outer = Arc::new(Node::with_name("Outer"));
+    let inner = Arc::new(Sequence::with_name("Inner"));
+    let timer = Arc::new(Timer::new(Duration::from_millis(5)));
+    outer.add_child(inner.clone()).await;
+    inner.add_child(timer.clone()).await;
+
+    outer.set_log_verbosity(4);
+    assert_eq!(timer.log_verbosity(), 4);
+    inner.set_log_verbosity(1);
+    assert_eq!(timer.log_verbosity(), 1);
+    assert_eq!(outer.log_verbosity(), 4);
+
+    inner.logger().clear_verbosity();
+    assert_eq!(timer.log_verbosity(), 4);
+    inner.remove_child(timer.id()).await;
+    assert_ne!(timer.log_verbosity(), 4);
+}
+
+#[tokio::test]
+async fn test_verbose_logs_follow_node_verbosity() {
+    let capture = LogCapture::install();
+    let node = Arc::new(Node::with_name("Quiet"));
+    node.add_child(Arc::new(Timer::new(Duration::from_secs(1)))).await;
+
+    node.set_log_verbosity(4);
+    node.step().await.unwrap();
+    assert!(capture.contains("Stepping node"));
+
+    capture.clear();
+    node.set_log_verbosity(2);
+    node.step().await.unwrap();
+    assert!(!capture.contains("Stepping node"));
+}
+
+#[test]
+fn test_default_verbosity_override() {
+    let logger = Logger::new("Standalone");
+    let before = default_verbosity();
+    set_default_verbosity(0);
+    assert_eq!(logger.verbosity(), 0);
+    logger.set_verbosity(2);
+    assert_eq!(logger.verbosity(), 2);
+    set_default_verbosity(before);
+}
+
+#[derive(Default)]
+struct CollectingSink {
+    lines: std::sync::Mutex<Vec<(LogLevel, String, String)>>,
+}
+
+impl LogSink for CollectingSink {
+    fn log(&self, level: LogLevel, node_path: &str, message: &str) {
+        self.lines.lock().unwrap().push((level, node_path.to_string(), message.to_string()));
+    }
+}
+
+#[tokio::test]
+async fn test_kernel_log_sink() {
+    let capture = LogCapture::install();
+    let kernel = AsyncKernel::new();
+    let sink = Arc::new(CollectingSink::default());
+    kernel.set_log_sink(sink.clone());
+
+    let stage = Arc::new(Sequence::with_name("Stage"));
+    stage
+        .add_child(Arc::new(AsyncCoroutine::with_name("Upload", async { Err("refused".into()) })))
+        .await;
+    kernel.root().add_child(stage).await;
+    kernel.run_until_complete().await.unwrap();
+
+    let lines = sink.lines.lock().unwrap().clone();
+    assert!(lines.contains(&(
+        LogLevel::Error,
+        "Root/Stage/Upload".to_string(),
+        "Coroutine failed: refused".to_string(),
+    )));
+    assert!(!capture.contains("refused"));
+
+    kernel.clear_log_sink();
+    kernel.root().logger().error("after");
+    assert!(capture.contains("after"));
+}
+
+#[tokio::test]
+async fn test_captured_logs() {
+    let kernel = AsyncKernel::new();
+    let capture = kernel.capture_logs();
+    kernel
+        .root()
+        .add_child(Arc::new(AsyncCoroutine::with_name("Upload", async { Err("refused".into()) })))
+        .await;
+    kernel.root().add_child(Arc::new(AsyncCoroutine::with_name("Download", async { Ok(()) }))).await;
+    kernel.run_until_complete().await.unwrap();
+
+    let upload = capture.entries_for("Upload");
+    assert_eq!(upload.len(), 1);
+    assert_eq!(upload[0].level, LogLevel::Error);
+    assert_eq!(upload[0].node, "Root/Upload");
+    assert!(capture.entries_for("Download").is_empty());
+    assert!(capture.contains(LogLevel::Error, "refused"));
+    assert_eq!(kernel.captured_logs(), capture.entries());
+
+    kernel.clear_log_sink();
+    assert!(kernel.captured_logs().is_empty());
+}
+
+#[tokio::test]
+async fn test_structured_log_fields() {
+    let capture = LogCapture::install();
+    let kernel = AsyncKernel::new();
+    let stage = Arc::new(Sequence::with_name("Stage"));
+    let upload = Arc::new(AsyncCoroutine::with_name("Upload", async { Err("refused".into()) }));
+    stage.add_child(upload.clone()).await;
+    kernel.root().add_child(stage).await;
+    kernel.run_until_complete().await.unwrap();
+
+    let log = capture
+        .logs()
+        .into_iter()
+        .find(|log| log.message.contains("refused"))
+        .expect("failure logged");
+    assert!(!log.message.contains("Upload"));
+    assert_eq!(log.field("node.path"), Some("Root/Stage/Upload"));
+    assert_eq!(log.field("node.name"), Some("Upload"));
+    assert_eq!(log.field("node.id"), Some(upload.id().to_string().as_str()));
+    assert_eq!(log.field("node.type"), Some("AsyncCoroutine"));
+}
+
+#[test]
+fn test_verbose_with_skips_filtered_messages() {
+    let logger = Logger::new("Quiet");
+    logger.set_verbosity(2);
+    let built = std::cell::Cell::new(0);
+    logger.verbose_with(4, || {
+        built.set(built.get() + 1);
+        "never shown"
+    });
+    assert_eq!(built.get(), 0);
+    logger.verbose_with(1, || {
+        built.set(built.get() + 1);
+        "shown"
+    });
+    assert_eq!(built.get(), 1);
+}
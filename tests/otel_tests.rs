@@ -0,0 +1,35 @@
+#![cfg(feature = "otel")]
+
+use async_flow::*;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_otel_spans_mirror_tree() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+
+    let kernel = AsyncKernel::new();
+    kernel.add_observer(Arc::new(OtelObserver::new(provider.tracer("flow"))));
+
+    let pipeline = SequenceBuilder::with_name("Pipeline")
+        .then(Arc::new(Timer::new(Duration::from_millis(5))).named("Download"))
+        .then(Arc::new(AsyncCoroutine::with_name("Upload", async { Err("refused".into()) })))
+        .build();
+    kernel.root().add_child(pipeline).await;
+    kernel.run_until_complete().await.unwrap();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let span = |name: &str| spans.iter().find(|s| s.name == name).unwrap();
+    let pipeline = span("Pipeline");
+    assert_eq!(span("Download").parent_span_id, pipeline.span_context.span_id());
+    assert_eq!(span("Upload").parent_span_id, pipeline.span_context.span_id());
+    assert_eq!(
+        span("Upload").status,
+        opentelemetry::trace::Status::error("refused")
+    );
+}
@@ -0,0 +1,17 @@
+use async_flow::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+// A downstream type that would collide with a glob import of the crate root.
+struct Logger;
+
+#[tokio::test]
+async fn test_prelude_builds_and_runs_flow() {
+    let _logger = Logger;
+    let kernel = AsyncKernel::new();
+    let sequence = Arc::new(Sequence::new()).named("Main");
+    sequence.add_child(Arc::new(Timer::new(Duration::from_millis(5)))).await;
+    kernel.root().add_child(sequence.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+    assert_eq!(sequence.state(), GeneratorState::Completed);
+}
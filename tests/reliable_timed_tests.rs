@@ -111,10 +111,10 @@ async fn test_barrier_with_timers() {
     let barrier = Arc::new(Barrier::new()).named("TestBarrier");
     let completion_count = Arc::new(AtomicU32::new(0));
     
-    let durations = vec![150, 250, 200];
+    let durations = [150, 250, 200];
     
     for (i, duration_ms) in durations.iter().enumerate() {
-        let timer = Arc::new(Timer::new(Duration::from_millis(*duration_ms))).named(&format!("Timer_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_millis(*duration_ms))).named(format!("Timer_{}", i));
         
         let completion_count_clone = completion_count.clone();
         timer.set_elapsed_callback(move || {
@@ -209,7 +209,7 @@ async fn test_sequential_timers_simple() {
     let completion_order = Arc::new(std::sync::Mutex::new(Vec::new()));
     
     for i in 0..3 {
-        let timer = Arc::new(Timer::new(Duration::from_micros(100))).named(&format!("SequentialTimer_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_micros(100))).named(format!("SequentialTimer_{}", i));
         
         let completion_order_clone = completion_order.clone();
         timer.set_elapsed_callback(move || {
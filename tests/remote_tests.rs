@@ -0,0 +1,77 @@
+#![cfg(feature = "remote")]
+
+use async_flow::*;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+async fn host(worker: Arc<dyn Generator>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    tokio::spawn(serve_remote(listener, worker));
+    addr
+}
+
+async fn step_until(kernel: &AsyncKernel, done: impl Fn() -> bool) {
+    for _ in 0..500 {
+        if done() {
+            return;
+        }
+        kernel.update(Duration::from_millis(1)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(2)).await;
+    }
+    panic!("condition not reached");
+}
+
+#[tokio::test]
+async fn test_remote_node_mirrors_completion() {
+    let worker = Arc::new(Trigger::new(|| false)).named("Worker");
+    let addr = host(worker.clone()).await;
+
+    let kernel = AsyncKernel::new();
+    let proxy = Arc::new(RemoteNode::tcp(addr)).named("Proxy");
+    kernel.root().add_child(proxy.clone()).await;
+
+    step_until(&kernel, || proxy.is_connected()).await;
+    assert_eq!(
+        proxy.remote_state(),
+        Some(RemoteState { active: true, running: true, completed: false, failed: false })
+    );
+
+    // The remote side finishing is picked up by the proxy.
+    worker.complete();
+    step_until(&kernel, || proxy.is_completed()).await;
+    assert!(!proxy.is_failed());
+}
+
+#[tokio::test]
+async fn test_remote_node_forwards_commands() {
+    let worker = Arc::new(Trigger::new(|| false));
+    let addr = host(worker.clone()).await;
+
+    let kernel = AsyncKernel::new();
+    let proxy = Arc::new(RemoteNode::tcp(addr));
+    kernel.root().add_child(proxy.clone()).await;
+    step_until(&kernel, || proxy.is_connected()).await;
+
+    proxy.deactivate();
+    step_until(&kernel, || !worker.is_active()).await;
+    proxy.activate();
+    proxy.complete();
+    step_until(&kernel, || worker.is_completed()).await;
+    assert!(worker.is_active());
+}
+
+#[tokio::test]
+async fn test_remote_node_fails_when_unreachable() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    drop(listener);
+
+    let kernel = AsyncKernel::new();
+    let proxy = Arc::new(RemoteNode::tcp(addr));
+    kernel.root().add_child(proxy.clone()).await;
+    step_until(&kernel, || proxy.is_completed()).await;
+    assert!(proxy.is_failed());
+    assert!(proxy.connection_error().is_some());
+}
@@ -0,0 +1,60 @@
+use async_flow::testing::{LogCapture, TestKernel};
+use async_flow::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test(start_paused = true)]
+async fn test_advance_moves_virtual_time_exactly() {
+    let kernel = TestKernel::new();
+    let timer = Arc::new(Timer::with_name("Timeout", Duration::from_millis(250)));
+    kernel.root().add_child(timer.clone()).await;
+
+    kernel.advance(249).await.unwrap();
+    assert!(!timer.is_completed());
+    kernel.advance(2).await.unwrap();
+    assert_completed!(timer);
+    assert_eq!(kernel.elapsed(), Duration::from_millis(251));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_assert_fires_within() {
+    let ready = Arc::new(AtomicBool::new(false));
+    let trigger = Arc::new(Trigger::with_name("Ready", {
+        let ready = ready.clone();
+        move || ready.load(Ordering::Relaxed)
+    }));
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        ready.store(true, Ordering::Relaxed);
+    });
+    assert_fires_within!(trigger, Duration::from_millis(50));
+
+    let kernel = TestKernel::new();
+    let sequence = SequenceBuilder::new()
+        .timer(Duration::from_millis(30))
+        .then(Arc::new(Timer::with_name("Second", Duration::from_millis(30))))
+        .build();
+    kernel.root().add_child(sequence.clone()).await;
+    assert_fires_within!(kernel, sequence, Duration::from_millis(70));
+}
+
+#[tokio::test(start_paused = true)]
+#[should_panic(expected = "expected Slow to fire within")]
+async fn test_assert_fires_within_reports_label() {
+    let kernel = TestKernel::new();
+    let timer = Arc::new(Timer::with_name("Slow", Duration::from_millis(100)));
+    kernel.root().add_child(timer.clone()).await;
+    assert_fires_within!(kernel, timer, Duration::from_millis(10));
+}
+
+#[tokio::test]
+async fn test_log_capture() {
+    let capture = LogCapture::install();
+    let kernel = AsyncKernel::new();
+    kernel.root().add_child(Arc::new(AsyncCoroutine::new(async { Err("boom".into()) }))).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert!(capture.contains("Coroutine failed: boom"));
+    assert!(capture.logs().iter().any(|log| log.level == tracing::Level::ERROR));
+}
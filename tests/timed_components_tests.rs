@@ -125,10 +125,10 @@ async fn test_barrier_with_multiple_timers() {
     let completion_counts = Arc::new(AtomicU32::new(0));
     
     // Create multiple timers with different durations
-    let timer_durations = vec![100, 200, 150, 250];
+    let timer_durations = [100, 200, 150, 250];
     
     for (i, duration) in timer_durations.iter().enumerate() {
-        let timer = Arc::new(Timer::new(Duration::from_micros(*duration))).named(&format!("Timer_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_micros(*duration))).named(format!("Timer_{}", i));
         
         let completion_counts_clone = completion_counts.clone();
         timer.set_elapsed_callback(move || {
@@ -392,4 +392,406 @@ async fn test_mixed_timer_barrier() {
     
     // Periodic timer should have ticked at least 3 times
     assert!(periodic_tick_count.load(Ordering::Relaxed) >= 3);
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_async_callbacks_on_timer_and_trigger() {
+    let kernel = AsyncKernel::new();
+    let root = kernel.root();
+
+    let (promise, future) = AsyncFuture::<u32>::named_pair("AsyncCallbackFuture");
+    let trigger_fired = Arc::new(AtomicBool::new(false));
+
+    let timer = Arc::new(Timer::new(Duration::from_micros(100))).named("AsyncTimer");
+    timer.set_elapsed_callback_async({
+        move || {
+            let promise = promise.clone();
+            async move {
+                promise.set_value(7).await;
+            }
+        }
+    }).await;
+
+    let trigger = Arc::new(Trigger::new({
+        let future = future.clone();
+        move || future.is_ready()
+    })).named("AsyncTrigger");
+    trigger.set_triggered_callback_async({
+        let trigger_fired = trigger_fired.clone();
+        move || {
+            let trigger_fired = trigger_fired.clone();
+            async move {
+                sleep(Duration::from_micros(50)).await;
+                trigger_fired.store(true, Ordering::Relaxed);
+            }
+        }
+    }).await;
+
+    root.add_child(timer).await;
+    root.add_child(trigger).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert_eq!(future.wait().await, 7);
+    kernel.run_for(Duration::from_millis(20)).await.unwrap();
+    assert!(trigger_fired.load(Ordering::Relaxed));
+}
+
+#[tokio::test]
+async fn test_multiple_callbacks_with_handles() {
+    let kernel = AsyncKernel::new();
+    let root = kernel.root();
+
+    let first = Arc::new(AtomicU32::new(0));
+    let second = Arc::new(AtomicU32::new(0));
+    let removed = Arc::new(AtomicU32::new(0));
+
+    let timer = Arc::new(Timer::new(Duration::from_micros(100))).named("ObservedTimer");
+    timer.add_elapsed_callback({
+        let first = first.clone();
+        move || {
+            first.fetch_add(1, Ordering::Relaxed);
+        }
+    }).await;
+    timer.add_elapsed_callback({
+        let second = second.clone();
+        move || {
+            second.fetch_add(1, Ordering::Relaxed);
+        }
+    }).await;
+    let handle = timer.add_elapsed_callback({
+        let removed = removed.clone();
+        move || {
+            removed.fetch_add(1, Ordering::Relaxed);
+        }
+    }).await;
+
+    assert!(timer.remove_elapsed_callback(handle).await);
+    assert!(!timer.remove_elapsed_callback(handle).await);
+
+    root.add_child(timer).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert_eq!(first.load(Ordering::Relaxed), 1);
+    assert_eq!(second.load(Ordering::Relaxed), 1);
+    assert_eq!(removed.load(Ordering::Relaxed), 0);
+}
+
+#[tokio::test]
+async fn test_connect_trigger_waits_for_listener() {
+    // Reserve a port, then free it so the first attempts are refused.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let kernel = AsyncKernel::new();
+    let ready = Arc::new(ConnectTrigger::tcp(addr.to_string()).retry_interval(Duration::from_millis(5)));
+    kernel.root().add_child(ready.clone()).await;
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while ready.attempts() < 2 && Instant::now() < deadline {
+        kernel.update(Duration::from_millis(1)).await.unwrap();
+        sleep(Duration::from_millis(2)).await;
+    }
+    assert!(!ready.is_completed());
+
+    let _listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    while !ready.is_completed() && Instant::now() < deadline {
+        kernel.update(Duration::from_millis(1)).await.unwrap();
+        sleep(Duration::from_millis(2)).await;
+    }
+    assert!(ready.is_connected() && ready.is_completed() && !ready.is_failed());
+}
+
+#[tokio::test]
+async fn test_connect_trigger_reset_clears_attempts() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    drop(listener);
+
+    let kernel = AsyncKernel::new();
+    let ready = Arc::new(ConnectTrigger::tcp(addr).retry_interval(Duration::from_millis(5)));
+    kernel.root().add_child(ready.clone()).await;
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while ready.attempts() < 2 && Instant::now() < deadline {
+        kernel.update(Duration::from_millis(1)).await.unwrap();
+        sleep(Duration::from_millis(2)).await;
+    }
+    assert!(ready.attempts() >= 2);
+
+    assert!(ready.reset().await);
+    sleep(Duration::from_millis(20)).await;
+    assert_eq!(ready.attempts(), 0);
+    assert!(!ready.is_connected());
+}
+
+#[tokio::test]
+async fn test_connect_trigger_times_out() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    drop(listener);
+
+    let kernel = AsyncKernel::new();
+    let ready = Arc::new(
+        ConnectTrigger::tcp(addr)
+            .retry_interval(Duration::from_millis(5))
+            .timeout(Duration::from_millis(30)),
+    );
+    kernel.root().add_child(ready.clone()).await;
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !ready.is_completed() && Instant::now() < deadline {
+        kernel.update(Duration::from_millis(1)).await.unwrap();
+        sleep(Duration::from_millis(2)).await;
+    }
+    assert!(ready.is_failed());
+    assert!(ready.attempts() >= 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_retry_budget_refills_over_window() {
+    let budget = RetryBudget::new("db", 2, Duration::from_millis(100));
+    let shared = budget.clone();
+    assert!(budget.try_spend());
+    assert!(shared.try_spend());
+    assert!(!budget.try_spend());
+    assert_eq!((budget.remaining(), shared.denied()), (0, 1));
+
+    tokio::time::advance(Duration::from_millis(50)).await;
+    assert_eq!(budget.remaining(), 1);
+    tokio::time::advance(Duration::from_secs(1)).await;
+    assert_eq!(shared.remaining(), 2);
+}
+
+#[tokio::test]
+async fn test_connect_triggers_share_retry_budget() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    drop(listener);
+
+    let kernel = AsyncKernel::new();
+    let budget = RetryBudget::new("db", 3, Duration::from_secs(60));
+    let waiters: Vec<_> = (0..2)
+        .map(|_| {
+            Arc::new(
+                ConnectTrigger::tcp(addr.clone())
+                    .retry_interval(Duration::from_millis(2))
+                    .retry_budget(budget.clone()),
+            )
+        })
+        .collect();
+    for waiter in &waiters {
+        kernel.root().add_child(waiter.clone()).await;
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(80);
+    while Instant::now() < deadline {
+        kernel.update(Duration::from_millis(1)).await.unwrap();
+        sleep(Duration::from_millis(1)).await;
+    }
+    let attempts: u32 = waiters.iter().map(|waiter| waiter.attempts()).sum();
+    assert!(attempts <= 5, "{} attempts", attempts);
+    assert!(waiters.iter().all(|waiter| waiter.attempts() >= 1));
+    assert!(budget.denied() > 0);
+}
+
+#[test]
+fn test_backoff_strategies() {
+    let ms = Duration::from_millis;
+    let mut exponential = ExponentialBackoff::new(ms(10), ms(50));
+    let delays: Vec<_> = (0..5).map(|_| exponential.next_delay()).collect();
+    assert_eq!(delays, [ms(10), ms(20), ms(40), ms(50), ms(50)]);
+    exponential.reset();
+    assert_eq!(exponential.next_delay(), ms(10));
+
+    let mut fibonacci = FibonacciBackoff::new(ms(10), ms(60));
+    let delays: Vec<_> = (0..6).map(|_| fibonacci.next_delay()).collect();
+    assert_eq!(delays, [ms(10), ms(10), ms(20), ms(30), ms(50), ms(60)]);
+
+    let mut capped = ExponentialBackoff::new(ms(10), ms(1000)).factor(3.0).capped(ms(50));
+    let delays: Vec<_> = (0..3).map(|_| capped.next_delay()).collect();
+    assert_eq!(delays, [ms(10), ms(30), ms(50)]);
+
+    let mut jittered = ConstantBackoff::new(ms(100)).jittered(0.5);
+    assert!((0..20).map(|_| jittered.next_delay()).all(|delay| delay >= ms(100) && delay <= ms(150)));
+
+    let mut first = DecorrelatedJitter::with_seed(ms(10), ms(200), 7);
+    let mut second = DecorrelatedJitter::with_seed(ms(10), ms(200), 7);
+    let mut last = ms(10);
+    for _ in 0..20 {
+        let delay = first.next_delay();
+        assert!(delay >= ms(10) && delay <= ms(200) && delay <= last * 3);
+        assert_eq!(second.next_delay(), delay);
+        last = delay;
+    }
+}
+
+#[tokio::test]
+async fn test_connect_trigger_backs_off_between_attempts() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    drop(listener);
+
+    let kernel = AsyncKernel::new();
+    let waiter = Arc::new(
+        ConnectTrigger::tcp(addr)
+            .retry_interval(Duration::from_millis(2))
+            .backoff(ExponentialBackoff::new(Duration::from_millis(10), Duration::from_secs(1))),
+    );
+    kernel.root().add_child(waiter.clone()).await;
+
+    // Attempts start at 0, 10, 30 and 70ms; without backoff there would be dozens.
+    let deadline = Instant::now() + Duration::from_millis(100);
+    while Instant::now() < deadline {
+        kernel.update(Duration::from_millis(1)).await.unwrap();
+        sleep(Duration::from_millis(1)).await;
+    }
+    assert!((2..=5).contains(&waiter.attempts()), "{} attempts", waiter.attempts());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_precise_timer_fires_at_deadline() {
+    let kernel = AsyncKernel::new();
+    let timer = Arc::new(Timer::new(Duration::from_micros(2500)).precise());
+    let fired = Arc::new(AtomicU32::new(0));
+    {
+        let fired = fired.clone();
+        timer.set_elapsed_callback(move || {
+            fired.fetch_add(1, Ordering::Relaxed);
+        }).await;
+    }
+    kernel.root().add_child(timer.clone()).await;
+
+    let armed_at = tokio::time::Instant::now();
+    kernel.update(Duration::ZERO).await.unwrap();
+    assert!(timer.is_precise() && !timer.is_completed());
+
+    // The callback fires from the sleeping task, before the kernel steps again.
+    timer.wait_elapsed().await;
+    tokio::task::yield_now().await;
+    // Tokio timers round deadlines up to the next millisecond.
+    let waited = timer.fired_at().unwrap() - armed_at;
+    assert!(waited >= Duration::from_micros(2500) && waited < Duration::from_micros(3500));
+    assert_eq!(fired.load(Ordering::Relaxed), 1);
+    assert!(!timer.is_completed());
+
+    kernel.update(Duration::ZERO).await.unwrap();
+    assert!(timer.is_completed());
+    assert_eq!(fired.load(Ordering::Relaxed), 1);
+
+    // Reset re-arms from scratch.
+    assert!(timer.reset().await);
+    assert!(timer.fired_at().is_none());
+}
+
+#[tokio::test]
+async fn test_sampled_trigger_fires_every_nth_occurrence() {
+    let kernel = AsyncKernel::new();
+    let level = Arc::new(AtomicBool::new(false));
+    let sampled = {
+        let level = level.clone();
+        Arc::new(SampledTrigger::with_name("Chatty", 3, move || level.load(Ordering::SeqCst)))
+    };
+    let fired = Arc::new(AtomicU32::new(0));
+    {
+        let fired = fired.clone();
+        sampled.add_triggered_callback(move || {
+            fired.fetch_add(1, Ordering::SeqCst);
+        }).await;
+    }
+    kernel.root().add_child(sampled.clone()).await;
+
+    for _ in 0..7 {
+        level.store(true, Ordering::SeqCst);
+        kernel.step().await.unwrap();
+        kernel.step().await.unwrap();
+        level.store(false, Ordering::SeqCst);
+        kernel.step().await.unwrap();
+    }
+
+    assert_eq!(sampled.occurrences(), 7);
+    assert_eq!((sampled.fires(), fired.load(Ordering::SeqCst)), (2, 2));
+    assert!(!sampled.is_completed());
+}
+
+#[tokio::test]
+async fn test_hysteresis_trigger_ignores_oscillation() {
+    let kernel = AsyncKernel::new();
+    let readings = Arc::new(std::sync::Mutex::new(vec![70.0, 81.0, 79.0, 80.5, 65.0, 59.0, 85.0]));
+    let cpu = {
+        let readings = readings.clone();
+        Arc::new(HysteresisTrigger::with_name(
+            "CpuHot",
+            move || readings.lock().unwrap().remove(0),
+            80.0,
+            60.0,
+        ))
+    };
+    let alerts = Arc::new(AtomicU32::new(0));
+    {
+        let alerts = alerts.clone();
+        cpu.add_triggered_callback(move || {
+            alerts.fetch_add(1, Ordering::SeqCst);
+        }).await;
+    }
+    kernel.root().add_child(cpu.clone()).await;
+
+    for expected_armed in [true, false, false, false, false, true, false] {
+        kernel.step().await.unwrap();
+        assert_eq!(cpu.is_armed(), expected_armed, "after reading {}", cpu.last_value());
+    }
+    assert_eq!((cpu.fires(), alerts.load(Ordering::SeqCst)), (2, 2));
+
+    let kernel = AsyncKernel::new();
+    let level = Arc::new(AtomicU32::new(50));
+    let disk = {
+        let level = level.clone();
+        Arc::new(HysteresisTrigger::new(move || level.load(Ordering::SeqCst) as f64, 10.0, 20.0))
+    };
+    kernel.root().add_child(disk.clone()).await;
+    for free in [15, 9, 12, 8, 25, 5] {
+        level.store(free, Ordering::SeqCst);
+        kernel.step().await.unwrap();
+    }
+    assert_eq!(disk.fires(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_held_trigger_needs_unbroken_condition() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let unhealthy = Arc::new(AtomicBool::new(true));
+    let trigger = {
+        let unhealthy = unhealthy.clone();
+        Arc::new(Trigger::with_name("Unhealthy", move || unhealthy.load(Ordering::SeqCst)).held_for(Duration::from_millis(30)))
+    };
+    kernel.root().add_child(trigger.clone()).await;
+
+    kernel.advance(20).await.unwrap();
+    assert!(trigger.holding_for().is_some());
+    unhealthy.store(false, Ordering::SeqCst);
+    kernel.advance(1).await.unwrap();
+    assert_eq!(trigger.holding_for(), None);
+    unhealthy.store(true, Ordering::SeqCst);
+    kernel.advance(25).await.unwrap();
+    assert!(!trigger.is_completed());
+
+    kernel.advance(10).await.unwrap();
+    assert!(trigger.is_completed() && trigger.is_triggered().await);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_held_trigger_starts_over_after_deactivation() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let trigger = Arc::new(Trigger::new(|| true).held_for(Duration::from_millis(30)));
+    kernel.root().add_child(trigger.clone()).await;
+
+    kernel.advance(20).await.unwrap();
+    trigger.deactivate();
+    assert_eq!(trigger.holding_for(), None);
+    kernel.advance(50).await.unwrap();
+    trigger.activate();
+    kernel.advance(20).await.unwrap();
+    assert!(!trigger.is_completed());
+
+    kernel.advance(15).await.unwrap();
+    assert!(trigger.is_completed());
+}
@@ -24,7 +24,7 @@ async fn test_timed_barrier_timeout_pattern() {
                 tasks_completed.fetch_add(1, Ordering::Relaxed);
                 Ok(())
             }
-        })).named(&format!("FastTask_{}", i));
+        })).named(format!("FastTask_{}", i));
         barrier.add_child(task).await;
     }
     
@@ -79,7 +79,7 @@ async fn test_timed_barrier_with_deadline() {
     
     // Add tasks that complete within deadline
     for i in 0..4 {
-        let timer = Arc::new(Timer::new(Duration::from_micros(80 + i * 15))).named(&format!("DeadlineTask_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_micros(80 + i * 15))).named(format!("DeadlineTask_{}", i));
         
         let completion_count_clone = completion_count.clone();
         timer.set_elapsed_callback(move || {
@@ -132,7 +132,7 @@ async fn test_nested_timed_barriers() {
     // Inner barrier 1 with fast timers
     let inner_barrier1 = Arc::new(Barrier::new()).named("InnerBarrier1");
     for i in 0..2 {
-        let timer = Arc::new(Timer::new(Duration::from_micros(60 + i * 10))).named(&format!("Inner1Timer_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_micros(60 + i * 10))).named(format!("Inner1Timer_{}", i));
         
         let total_completions_clone = total_completions.clone();
         timer.set_elapsed_callback(move || {
@@ -145,7 +145,7 @@ async fn test_nested_timed_barriers() {
     // Inner barrier 2 with medium timers
     let inner_barrier2 = Arc::new(Barrier::new()).named("InnerBarrier2");
     for i in 0..3 {
-        let timer = Arc::new(Timer::new(Duration::from_micros(90 + i * 15))).named(&format!("Inner2Timer_{}", i));
+        let timer = Arc::new(Timer::new(Duration::from_micros(90 + i * 15))).named(format!("Inner2Timer_{}", i));
         
         let total_completions_clone = total_completions.clone();
         timer.set_elapsed_callback(move || {
@@ -190,16 +190,16 @@ async fn test_timed_future_with_timeout() {
     let kernel = AsyncKernel::new();
     let root = kernel.root();
     
-    let future = Arc::new(AsyncFuture::<String>::new()).named("TimedFuture");
+    let (promise, future) = AsyncFuture::<String>::named_pair("TimedFuture");
     let timeout_occurred = Arc::new(AtomicBool::new(false));
     let future_received = Arc::new(AtomicBool::new(false));
     
     // Producer task with delay
     let producer = Arc::new(AsyncCoroutine::new({
-        let future = future.clone();
+        let promise = promise.clone();
         async move {
             sleep(Duration::from_micros(200)).await;
-            future.set_value("future_data".to_string()).await;
+            promise.set_value("future_data".to_string()).await;
             Ok(())
         }
     })).named("FutureProducer");
@@ -247,9 +247,9 @@ async fn test_multiple_timed_futures_coordination() {
     let kernel = AsyncKernel::new();
     let root = kernel.root();
     
-    let future1 = Arc::new(AsyncFuture::<u32>::new()).named("Future1");
-    let future2 = Arc::new(AsyncFuture::<u32>::new()).named("Future2");
-    let future3 = Arc::new(AsyncFuture::<u32>::new()).named("Future3");
+    let (promise1, future1) = AsyncFuture::<u32>::named_pair("Future1");
+    let (promise2, future2) = AsyncFuture::<u32>::named_pair("Future2");
+    let (promise3, future3) = AsyncFuture::<u32>::named_pair("Future3");
     
     let coordination_complete = Arc::new(AtomicBool::new(false));
     let result_sum = Arc::new(AtomicU32::new(0));
@@ -257,31 +257,28 @@ async fn test_multiple_timed_futures_coordination() {
     // Producers with different timing
     let producer1 = Arc::new(Timer::new(Duration::from_micros(80))).named("Producer1Timer");
     
-    let future1_clone = future1.clone();
     producer1.set_elapsed_callback(move || {
-        let future1 = future1_clone.clone();
+        let promise1 = promise1.clone();
         tokio::spawn(async move {
-            future1.set_value(10).await;
+            promise1.set_value(10).await;
         });
     }).await;
     
     let producer2 = Arc::new(Timer::new(Duration::from_micros(120))).named("Producer2Timer");
     
-    let future2_clone = future2.clone();
     producer2.set_elapsed_callback(move || {
-        let future2 = future2_clone.clone();
+        let promise2 = promise2.clone();
         tokio::spawn(async move {
-            future2.set_value(20).await;
+            promise2.set_value(20).await;
         });
     }).await;
     
     let producer3 = Arc::new(Timer::new(Duration::from_micros(160))).named("Producer3Timer");
     
-    let future3_clone = future3.clone();
     producer3.set_elapsed_callback(move || {
-        let future3 = future3_clone.clone();
+        let promise3 = promise3.clone();
         tokio::spawn(async move {
-            future3.set_value(30).await;
+            promise3.set_value(30).await;
         });
     }).await;
     
@@ -337,9 +334,9 @@ async fn test_timed_future_pipeline() {
     let kernel = AsyncKernel::new();
     let root = kernel.root();
     
-    let stage1_future = Arc::new(AsyncFuture::<u32>::new()).named("Stage1Future");
-    let stage2_future = Arc::new(AsyncFuture::<u32>::new()).named("Stage2Future");
-    let stage3_future = Arc::new(AsyncFuture::<u32>::new()).named("Stage3Future");
+    let (stage1_promise, stage1_future) = AsyncFuture::<u32>::named_pair("Stage1Future");
+    let (stage2_promise, stage2_future) = AsyncFuture::<u32>::named_pair("Stage2Future");
+    let (stage3_promise, stage3_future) = AsyncFuture::<u32>::named_pair("Stage3Future");
     
     let pipeline_complete = Arc::new(AtomicBool::new(false));
     let final_result = Arc::new(AtomicU32::new(0));
@@ -347,22 +344,20 @@ async fn test_timed_future_pipeline() {
     // Stage 1: Initial data production
     let stage1_timer = Arc::new(Timer::new(Duration::from_micros(70))).named("Stage1Timer");
     
-    let stage1_future_clone = stage1_future.clone();
     stage1_timer.set_elapsed_callback(move || {
-        let future = stage1_future_clone.clone();
+        let promise = stage1_promise.clone();
         tokio::spawn(async move {
-            future.set_value(5).await;
+            promise.set_value(5).await;
         });
     }).await;
     
     // Stage 2: Process stage 1 data
     let stage2_processor = Arc::new(AsyncCoroutine::new({
         let stage1_future = stage1_future.clone();
-        let stage2_future = stage2_future.clone();
         async move {
             let val = stage1_future.wait().await;
             sleep(Duration::from_micros(50)).await; // Processing time
-            stage2_future.set_value(val * 2).await; // Transform: 5 -> 10
+            stage2_promise.set_value(val * 2).await; // Transform: 5 -> 10
             Ok(())
         }
     })).named("Stage2Processor");
@@ -370,11 +365,10 @@ async fn test_timed_future_pipeline() {
     // Stage 3: Process stage 2 data
     let stage3_processor = Arc::new(AsyncCoroutine::new({
         let stage2_future = stage2_future.clone();
-        let stage3_future = stage3_future.clone();
         async move {
             let val = stage2_future.wait().await;
             sleep(Duration::from_micros(40)).await; // Processing time
-            stage3_future.set_value(val + 15).await; // Transform: 10 -> 25
+            stage3_promise.set_value(val + 15).await; // Transform: 10 -> 25
             Ok(())
         }
     })).named("Stage3Processor");
@@ -642,4 +636,994 @@ async fn test_timed_trigger_with_timeout_recovery() {
     // Should complete via timeout mechanism
     assert!(elapsed >= Duration::from_micros(50));
     assert!(elapsed <= Duration::from_secs(5));
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_future_combinator_pipeline() {
+    let kernel = AsyncKernel::new();
+    let (stage1_promise, stage1_future) = AsyncFuture::<u32>::named_pair("Stage1Future");
+
+    let stage2_future = stage1_future.map(|val| val * 2);
+    let stage3_future = stage2_future.and_then(|val| {
+        let (promise, future) = AsyncFuture::pair();
+        tokio::spawn(async move { promise.set_value(val + 15).await });
+        future
+    });
+    let (label_promise, label) = AsyncFuture::<&str>::pair();
+    let result = stage3_future.zip(&label);
+
+    let stage1_timer = Arc::new(Timer::new(Duration::from_micros(70))).named("Stage1Timer");
+    stage1_timer.set_elapsed_callback({
+        move || {
+            let stage1_promise = stage1_promise.clone();
+            let label_promise = label_promise.clone();
+            tokio::spawn(async move {
+                stage1_promise.set_value(5).await;
+                label_promise.set_value("final").await;
+            });
+        }
+    }).await;
+
+    kernel.root().add_child(stage1_timer).await;
+    kernel.root().add_child(result.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert_eq!(stage2_future.try_get(), Some(10));
+    assert_eq!(result.try_get(), Some((25, "final")));
+}
+
+#[tokio::test]
+async fn test_join_all_and_select_any_futures() {
+    let kernel = AsyncKernel::new();
+    let root = kernel.root();
+
+    let (promises, futures): (Vec<_>, Vec<_>) = (1..=3)
+        .map(|i| AsyncFuture::<u32>::named_pair(format!("Future{}", i)))
+        .unzip();
+
+    for (index, promise) in promises.iter().enumerate() {
+        let producer = Arc::new(Timer::new(Duration::from_millis(10 * (3 - index as u64))));
+        let promise = promise.clone();
+        producer.set_elapsed_callback(move || {
+            let promise = promise.clone();
+            tokio::spawn(async move {
+                promise.set_value(10 * (index as u32 + 1)).await;
+            });
+        }).await;
+        root.add_child(producer).await;
+    }
+
+    let all = join_all(futures.clone()).named("AllValues");
+    let first = select_any(futures.clone()).named("FirstValue");
+    root.add_child(all.clone()).await;
+    root.add_child(first.clone()).await;
+
+    kernel.run_until_complete().await.unwrap();
+
+    assert_eq!(all.try_get(), Some(vec![10, 20, 30]));
+    assert_eq!(all.try_get().unwrap().iter().sum::<u32>(), 60);
+    assert_eq!(first.try_get(), Some((2, 30)));
+}
+
+#[tokio::test]
+async fn test_async_stream_producer_consumer() {
+    let kernel = AsyncKernel::new();
+    let root = kernel.root();
+
+    let stream = Arc::new(AsyncStream::<u32>::with_name("Readings"));
+    let producer = {
+        let stream = stream.clone();
+        Arc::new(AsyncCoroutine::with_name("Producer", async move {
+            for reading in 1..=5 {
+                sleep(Duration::from_millis(5)).await;
+                stream.push(reading).unwrap();
+            }
+            stream.close();
+            Ok(())
+        }))
+    };
+    let total = Arc::new(AtomicU32::new(0));
+    let consumer = {
+        let (stream, total) = (stream.clone(), total.clone());
+        Arc::new(AsyncCoroutine::with_name("Consumer", async move {
+            while let Some(reading) = stream.next().await {
+                total.fetch_add(reading, Ordering::Relaxed);
+            }
+            Ok(())
+        }))
+    };
+
+    root.add_child(producer).await;
+    root.add_child(consumer).await;
+    root.add_child(stream.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert_eq!(total.load(Ordering::Relaxed), 15);
+    assert!(stream.is_completed());
+    assert_eq!(stream.push(6), Err(6));
+}
+
+#[tokio::test]
+async fn test_channel_source_and_sink_bridge() {
+    let kernel = AsyncKernel::new();
+    let root = kernel.root();
+
+    let (work_tx, work_rx) = tokio::sync::mpsc::channel::<u32>(2);
+    let (result_tx, mut result_rx) = tokio::sync::mpsc::channel::<u32>(4);
+
+    let sink = Arc::new(ChannelSink::with_name("Results", result_tx));
+    let source = {
+        let sink = sink.clone();
+        Arc::new(ChannelSource::with_name("Work", work_rx, move |item: u32| {
+            sink.send(item * 2).unwrap();
+        }).with_batch_limit(1))
+    };
+    {
+        let sink = sink.clone();
+        source.base().set_on_complete(move || sink.close());
+    }
+
+    tokio::spawn(async move {
+        for item in 1..=10 {
+            work_tx.send(item).await.unwrap();
+        }
+    });
+    let collector = tokio::spawn(async move {
+        let mut results = Vec::new();
+        while let Some(result) = result_rx.recv().await {
+            results.push(result);
+        }
+        results
+    });
+
+    root.add_child(source.clone()).await;
+    root.add_child(sink.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert!(source.is_completed());
+    assert!(sink.is_completed() && !sink.is_failed());
+    assert_eq!(collector.await.unwrap(), (1..=10).map(|i| i * 2).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_stream_node_drives_futures_stream() {
+    let kernel = AsyncKernel::new();
+    let root = kernel.root();
+
+    let ticks = futures::stream::unfold(0u32, |count| async move {
+        if count == 4 {
+            return None;
+        }
+        sleep(Duration::from_millis(5)).await;
+        Some((count + 1, count + 1))
+    });
+    let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let node = {
+        let received = received.clone();
+        Arc::new(StreamNode::with_name("Ticks", ticks, move |tick| {
+            received.lock().unwrap().push(tick);
+        }))
+    };
+    let lines = Arc::new(AtomicU32::new(0));
+    let lines_node = {
+        let lines = lines.clone();
+        Arc::new(StreamNode::new(futures::stream::iter(["a", "b", "c"]), move |_line| {
+            lines.fetch_add(1, Ordering::Relaxed);
+        }))
+    };
+
+    root.add_child(node.clone()).await;
+    root.add_child(lines_node.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert_eq!(*received.lock().unwrap(), vec![1, 2, 3, 4]);
+    assert_eq!(lines.load(Ordering::Relaxed), 3);
+    assert!(node.is_completed() && lines_node.is_completed());
+}
+
+#[tokio::test]
+async fn test_limited_caps_concurrent_children() {
+    let kernel = AsyncKernel::new();
+    let limited = Arc::new(Limited::with_name("Downloads", 3));
+
+    let in_flight = Arc::new(AtomicU32::new(0));
+    let peak = Arc::new(AtomicU32::new(0));
+    let finished = Arc::new(AtomicU32::new(0));
+    for index in 0..10 {
+        let (in_flight, peak, finished) = (in_flight.clone(), peak.clone(), finished.clone());
+        let download = AsyncCoroutine::lazy(format!("Download{}", index), move |_ctx| async move {
+            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            sleep(Duration::from_millis(10)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            finished.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+        limited.add_child(Arc::new(download)).await;
+    }
+
+    kernel.root().add_child(limited.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert!(limited.is_completed());
+    assert_eq!(finished.load(Ordering::SeqCst), 10);
+    assert_eq!(peak.load(Ordering::SeqCst), 3);
+    assert_eq!(limited.running_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_worker_pool_drains_job_sources() {
+    let kernel = AsyncKernel::new();
+
+    let (jobs, receiver) = tokio::sync::mpsc::channel::<u32>(16);
+    let in_flight = Arc::new(AtomicU32::new(0));
+    let peak = Arc::new(AtomicU32::new(0));
+    let total = Arc::new(AtomicU32::new(0));
+    let channel_pool = {
+        let (in_flight, peak, total) = (in_flight.clone(), peak.clone(), total.clone());
+        Arc::new(WorkerPool::from_channel(4, receiver, move |job: u32| {
+            let (in_flight, peak, total) = (in_flight.clone(), peak.clone(), total.clone());
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                sleep(Duration::from_millis(5)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                total.fetch_add(job, Ordering::SeqCst);
+                Ok(())
+            }
+        })).named("Etl")
+    };
+    tokio::spawn(async move {
+        for job in 1..=20 {
+            jobs.send(job).await.unwrap();
+        }
+    });
+
+    let mut remaining = vec!["a", "bad", "c"];
+    let fn_pool = Arc::new(WorkerPool::from_fn(2, move || remaining.pop(), |job: &str| async move {
+        if job == "bad" {
+            return Err("bad record".into());
+        }
+        Ok(())
+    }));
+
+    kernel.root().add_child(channel_pool.clone()).await;
+    kernel.root().add_child(fn_pool.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert_eq!(total.load(Ordering::SeqCst), 210);
+    assert_eq!(channel_pool.completed_jobs(), 20);
+    assert!(peak.load(Ordering::SeqCst) <= 4);
+    assert_eq!((fn_pool.completed_jobs(), fn_pool.failed_jobs()), (2, 1));
+    assert!(fn_pool.is_completed() && !fn_pool.is_failed());
+}
+
+#[tokio::test]
+async fn test_pipeline_relays_items_through_typed_stages() {
+    let kernel = AsyncKernel::new();
+
+    let (items, receiver) = tokio::sync::mpsc::channel::<u32>(4);
+    let in_flight = Arc::new(AtomicU32::new(0));
+    let peak = Arc::new(AtomicU32::new(0));
+    let stored = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let pipeline = {
+        let (in_flight, peak, stored) = (in_flight.clone(), peak.clone(), stored.clone());
+        Arc::new(
+            Pipeline::from_channel(receiver)
+                .capacity(2)
+                .stage(3, move |n: u32| {
+                    let (in_flight, peak) = (in_flight.clone(), peak.clone());
+                    async move {
+                        let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        sleep(Duration::from_millis(2)).await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        Ok(n * 2)
+                    }
+                })
+                .stage(1, |n: u32| async move {
+                    if n == 14 {
+                        return Err("unlucky".into());
+                    }
+                    Ok(format!("#{}", n))
+                })
+                .sink(1, move |label: String| {
+                    let stored = stored.clone();
+                    async move {
+                        stored.lock().unwrap().push(label);
+                        Ok(())
+                    }
+                }),
+        )
+        .named("Relay")
+    };
+    tokio::spawn(async move {
+        for n in 1..=10 {
+            items.send(n).await.unwrap();
+        }
+    });
+
+    kernel.root().add_child(pipeline.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    let mut stored = stored.lock().unwrap().clone();
+    stored.sort_by_key(|label| label[1..].parse::<u32>().unwrap());
+    let expected: Vec<String> = (1..=10).filter(|n| *n != 7).map(|n| format!("#{}", n * 2)).collect();
+    assert_eq!(stored, expected);
+    assert_eq!((pipeline.stages(), pipeline.processed(), pipeline.failed()), (3, 9, 1));
+    assert!(peak.load(Ordering::SeqCst) <= 3);
+    assert!(pipeline.is_completed() && !pipeline.is_failed());
+
+    let empty = Arc::new(Pipeline::from_items(Vec::<u32>::new()).sink(1, |_| async { Ok(()) }));
+    let kernel = AsyncKernel::new();
+    kernel.root().add_child(empty.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+    assert!(empty.is_completed());
+}
+
+#[tokio::test]
+async fn test_fan_out_queues_per_consumer() {
+    let kernel = AsyncKernel::new();
+
+    let (values, receiver) = tokio::sync::mpsc::channel::<u32>(8);
+    let fan_out = Arc::new(FanOut::from_channel(receiver)).named("Broadcast");
+
+    let fast_seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let fast = {
+        let fast_seen = fast_seen.clone();
+        fan_out.add_consumer(move |value: u32| {
+            let fast_seen = fast_seen.clone();
+            async move {
+                fast_seen.lock().unwrap().push(value);
+                Ok(())
+            }
+        })
+    };
+    let slow_total = Arc::new(AtomicU32::new(0));
+    let slow = {
+        let slow_total = slow_total.clone();
+        fan_out.add_consumer(move |value: u32| {
+            let slow_total = slow_total.clone();
+            async move {
+                sleep(Duration::from_millis(3)).await;
+                if value == 3 {
+                    return Err("slow consumer rejected 3".into());
+                }
+                slow_total.fetch_add(value, Ordering::SeqCst);
+                Ok(())
+            }
+        })
+    };
+    let flow_total = Arc::new(AtomicU32::new(0));
+    {
+        let flow_total = flow_total.clone();
+        fan_out.add_flow(move |value: u32| -> Arc<dyn Generator> {
+            let flow_total = flow_total.clone();
+            Arc::new(SyncCoroutine::new(move || {
+                flow_total.fetch_add(value, Ordering::SeqCst);
+                None::<()>
+            }))
+        });
+    }
+
+    for value in 1..=5 {
+        values.send(value).await.unwrap();
+    }
+    kernel.root().add_child(fan_out.clone()).await;
+    kernel.step().await.unwrap();
+    sleep(Duration::from_millis(1)).await;
+    assert_eq!(fast_seen.lock().unwrap().clone(), vec![1, 2, 3, 4, 5]);
+    assert_eq!(fan_out.backlog(fast), 0);
+    assert!(fan_out.backlog(slow) > 0);
+
+    drop(values);
+    kernel.run_until_complete().await.unwrap();
+    assert_eq!(fan_out.received(), 5);
+    assert_eq!(slow_total.load(Ordering::SeqCst), 12);
+    assert_eq!(flow_total.load(Ordering::SeqCst), 15);
+    assert_eq!(fan_out.failed(), 1);
+    assert!(fan_out.is_completed() && !fan_out.is_failed());
+
+    let (promise, future) = AsyncFuture::<String>::pair();
+    let once = Arc::new(FanOut::from_future(future));
+    let greetings = Arc::new(AtomicU32::new(0));
+    for _ in 0..2 {
+        let greetings = greetings.clone();
+        once.add_consumer(move |greeting: String| {
+            let greetings = greetings.clone();
+            async move {
+                assert_eq!(greeting, "hello");
+                greetings.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+    }
+    promise.set_value("hello".to_string()).await;
+    let kernel = AsyncKernel::new();
+    kernel.root().add_child(once.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+    assert_eq!(greetings.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_aggregator_joins_submissions() {
+    let kernel = AsyncKernel::new();
+
+    let aggregator = Arc::new(Aggregator::with_name("Results", 3));
+    let handle = aggregator.handle();
+    for worker in 0..3u32 {
+        let handle = handle.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(1 + worker as u64)).await;
+            handle.submit(worker * 10).unwrap();
+        });
+    }
+    kernel.root().add_child(aggregator.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    let aggregated = aggregator.result().wait().await;
+    assert_eq!((aggregated.items, aggregated.partial), (vec![0, 10, 20], false));
+    assert_eq!(handle.remaining(), 0);
+    assert_eq!(handle.submit(30), Err(30));
+
+    let kernel = AsyncKernel::new();
+    let short = Arc::new(Aggregator::new(4).timeout(Duration::from_millis(5)));
+    short.handle().submit("only").unwrap();
+    kernel.root().add_child(short.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    let aggregated = short.result().wait().await;
+    assert_eq!((aggregated.items, aggregated.partial), (vec!["only"], true));
+    assert!(short.is_completed() && !short.is_failed());
+    assert_eq!(short.handle().submit("late"), Err("late"));
+}
+
+#[tokio::test]
+async fn test_blocking_coroutine_does_not_stall_step_loop() {
+    let kernel = AsyncKernel::new();
+
+    let checksum = Arc::new(AtomicU32::new(0));
+    let heavy = {
+        let checksum = checksum.clone();
+        Arc::new(BlockingCoroutine::with_name("Checksum", move || {
+            std::thread::sleep(Duration::from_millis(50));
+            let sum = (1..=1000u32).fold(0u32, |acc, n| acc.wrapping_add(n * n));
+            checksum.store(sum, Ordering::SeqCst);
+            Ok(())
+        }))
+    };
+    let failing = Arc::new(BlockingCoroutine::new(|| Err("corrupt input".into())));
+    let ticker = Arc::new(PeriodicTimer::new(Duration::from_millis(5)));
+    let ticks = Arc::new(AtomicU32::new(0));
+    {
+        let ticks = ticks.clone();
+        ticker.set_elapsed_callback(move || {
+            ticks.fetch_add(1, Ordering::SeqCst);
+        }).await;
+    }
+
+    kernel.root().add_child(heavy.clone()).await;
+    kernel.root().add_child(failing.clone()).await;
+    kernel.root().add_child(ticker).await;
+    let kernel_for_wait = kernel.clone();
+    let waiter = tokio::spawn(async move {
+        heavy.wait_completed().await;
+        kernel_for_wait.break_flow().await;
+        heavy
+    });
+    kernel.run_until_complete().await.unwrap();
+    let heavy = waiter.await.unwrap();
+
+    assert!(heavy.is_completed() && !heavy.is_failed());
+    assert_eq!(checksum.load(Ordering::SeqCst), (1..=1000u32).fold(0u32, |acc, n| acc.wrapping_add(n * n)));
+    assert!(failing.is_failed());
+    assert!(ticks.load(Ordering::SeqCst) >= 3, "timer kept ticking while the blocking work ran");
+}
+
+#[tokio::test]
+async fn test_local_kernel_drives_non_send_coroutines() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let kernel = LocalKernel::new();
+    let script_state = Rc::new(RefCell::new(Vec::new()));
+
+    let script = {
+        let script_state = script_state.clone();
+        kernel.coroutine("Script", async move {
+            for line in ["load", "run", "done"] {
+                sleep(Duration::from_millis(5)).await;
+                script_state.borrow_mut().push(line);
+            }
+            Ok(())
+        })
+    };
+    let local_root = Rc::new(RefCell::new(0u32));
+    let inline = {
+        let local_root = local_root.clone();
+        kernel.run_until(async move {
+            Arc::new(LocalCoroutine::with_name("Inline", async move {
+                *local_root.borrow_mut() += 1;
+                Ok(())
+            }))
+        }).await
+    };
+
+    kernel.root().add_child(script.clone()).await;
+    kernel.root().add_child(inline.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert!(script.is_completed() && inline.is_completed());
+    assert_eq!(*script_state.borrow(), vec!["load", "run", "done"]);
+    assert_eq!(*local_root.borrow(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_batcher_flushes_by_size_or_age() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let flushed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let batcher = {
+        let flushed = flushed.clone();
+        Arc::new(Batcher::with_name("Writes", 2, Duration::from_millis(50), move |batch: Vec<u32>| {
+            flushed.lock().unwrap().push(batch);
+            Arc::new(Timer::new(Duration::from_millis(5))) as Arc<dyn Generator>
+        }))
+    };
+    let handle = batcher.handle();
+    kernel.root().add_child(batcher.clone()).await;
+
+    for item in 1..=5 {
+        handle.push(item).unwrap();
+    }
+    kernel.advance(1).await.unwrap();
+    assert_eq!(*flushed.lock().unwrap(), vec![vec![1, 2], vec![3, 4]]);
+    assert_eq!(batcher.pending(), 1);
+    assert_eq!(batcher.children().await.len(), 2);
+
+    kernel.advance(48).await.unwrap();
+    assert_eq!(flushed.lock().unwrap().len(), 2);
+    kernel.advance(2).await.unwrap();
+    assert_eq!(flushed.lock().unwrap().last(), Some(&vec![5]));
+
+    handle.push(6).unwrap();
+    handle.close();
+    assert_eq!(handle.push(7), Err(7));
+    kernel.advance(10).await.unwrap();
+    assert_eq!(flushed.lock().unwrap().last(), Some(&vec![6]));
+    assert_eq!(batcher.flushed_batches(), 4);
+    assert!(batcher.is_completed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_batcher_ages_batches_on_the_flow_clock() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let game = Arc::new(PauseGate::with_name("Game"));
+    let batcher = Arc::new(Batcher::new(10, Duration::from_millis(50), |_batch: Vec<u32>| {
+        Arc::new(Timer::new(Duration::from_millis(1))) as Arc<dyn Generator>
+    }));
+    let handle = batcher.handle();
+    game.add_child(batcher.clone()).await;
+    kernel.root().add_child(game.clone()).await;
+
+    handle.push(1).unwrap();
+    kernel.advance(20).await.unwrap();
+    game.pause();
+    kernel.advance(100).await.unwrap();
+    game.resume();
+    kernel.advance(10).await.unwrap();
+    assert_eq!(batcher.flushed_batches(), 0);
+    kernel.advance(25).await.unwrap();
+    assert_eq!(batcher.flushed_batches(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_window_groups_items_by_flow_time() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let tumbling = {
+        let seen = seen.clone();
+        Arc::new(Window::tumbling(Duration::from_millis(10), move |span, items: &[u32]| {
+            seen.lock().unwrap().push((span.start.as_millis(), items.to_vec()));
+        }))
+    };
+    let handle = tumbling.handle();
+    kernel.root().add_child(tumbling.clone()).await;
+
+    handle.push(1).unwrap();
+    handle.push(2).unwrap();
+    kernel.advance(1).await.unwrap();
+    kernel.advance(9).await.unwrap();
+    assert!(seen.lock().unwrap().is_empty());
+    handle.push(3).unwrap();
+    kernel.advance(1).await.unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec![(0, vec![1, 2])]);
+
+    kernel.advance(15).await.unwrap();
+    handle.push(4).unwrap();
+    handle.close();
+    assert_eq!(handle.push(5), Err(5));
+    kernel.advance(1).await.unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec![(0, vec![1, 2]), (10, vec![3]), (20, vec![4])]);
+    assert_eq!(tumbling.windows(), 3);
+    assert!(tumbling.is_completed());
+
+    let kernel = async_flow::testing::TestKernel::new();
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sliding = {
+        let seen = seen.clone();
+        Arc::new(Window::sliding(Duration::from_millis(10), Duration::from_millis(5), move |span, items: &[&str]| {
+            seen.lock().unwrap().push((span.start.as_millis(), span.end.as_millis(), items.to_vec()));
+        }))
+    };
+    let handle = sliding.handle();
+    kernel.root().add_child(sliding.clone()).await;
+
+    handle.push("early").unwrap();
+    kernel.advance(7).await.unwrap();
+    handle.push("late").unwrap();
+    kernel.advance(9).await.unwrap();
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![(0, 10, vec!["early", "late"]), (5, 15, vec!["late"])]
+    );
+    assert_eq!(sliding.pending(), 0);
+    handle.close();
+    kernel.advance(1).await.unwrap();
+    assert!(sliding.is_completed());
+    assert_eq!(sliding.windows(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_watchdog_detects_stalled_subtrees() {
+    let kernel = async_flow::testing::TestKernel::new();
+
+    let stuck = Arc::new(Trigger::with_name("NeverReady", || false));
+    let failing = Arc::new(Watchdog::with_name("Guard", stuck.clone(), Duration::from_millis(100)));
+    let steady = Arc::new(Watchdog::new(
+        SequenceBuilder::new()
+            .timer(Duration::from_millis(60))
+            .timer(Duration::from_millis(60))
+            .timer(Duration::from_millis(60))
+            .build(),
+        Duration::from_millis(100),
+    ));
+    let alerts = Arc::new(AtomicU32::new(0));
+    let alerting = {
+        let alerts = alerts.clone();
+        Arc::new(Watchdog::new(Arc::new(Trigger::new(|| false)), Duration::from_millis(100)).on_stall(move || {
+            alerts.fetch_add(1, Ordering::Relaxed);
+        }))
+    };
+    kernel.root().add_child(failing.clone()).await;
+    kernel.root().add_child(steady.clone()).await;
+    kernel.root().add_child(alerting.clone()).await;
+
+    kernel.advance(99).await.unwrap();
+    assert!(!failing.is_completed());
+    kernel.advance(3).await.unwrap();
+    assert!(failing.is_failed());
+    assert!(!stuck.is_active());
+
+    kernel.advance(150).await.unwrap();
+    assert!(steady.is_completed() && !steady.is_failed());
+    assert_eq!(alerting.stalls(), 2);
+    assert_eq!(alerts.load(Ordering::Relaxed), 2);
+    assert!(!alerting.is_completed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_fallback_runs_after_missed_deadline() {
+    let kernel = async_flow::testing::TestKernel::new();
+
+    let slow = Arc::new(Timer::with_name("SlowPrimary", Duration::from_millis(200)));
+    let cache = Arc::new(Timer::with_name("Cache", Duration::from_millis(20)));
+    let hedged = Arc::new(Fallback::new(slow.clone(), cache.clone(), Duration::from_millis(50)));
+    let quick = Arc::new(Fallback::new(
+        Arc::new(Timer::new(Duration::from_millis(10))),
+        Arc::new(Timer::new(Duration::from_millis(10))),
+        Duration::from_millis(50),
+    ));
+    let broken = Arc::new(Fallback::new(
+        Arc::new(AsyncCoroutine::lazy("Broken", |_ctx| async { Err("refused".into()) })),
+        Arc::new(Timer::new(Duration::from_millis(10))),
+        Duration::from_millis(50),
+    ));
+    kernel.root().add_child(hedged.clone()).await;
+    kernel.root().add_child(quick.clone()).await;
+    kernel.root().add_child(broken.clone()).await;
+
+    kernel.advance(30).await.unwrap();
+    assert!(quick.is_completed() && !quick.is_failed());
+    assert_eq!(quick.path(), FallbackPath::Primary);
+    assert!(broken.is_failed() && !broken.took_fallback());
+    assert_eq!(hedged.path(), FallbackPath::Primary);
+
+    kernel.advance(25).await.unwrap();
+    assert!(hedged.took_fallback() && !hedged.is_completed());
+    assert!(!slow.is_active());
+    assert_eq!(hedged.children().await.len(), 1);
+
+    kernel.advance(25).await.unwrap();
+    assert!(hedged.is_completed() && !hedged.is_failed());
+    assert!(cache.is_completed() && !slow.is_completed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_fallback_deadline_pauses_while_deactivated() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let primary = Arc::new(Timer::new(Duration::from_millis(60)));
+    let hedged = Arc::new(Fallback::new(primary.clone(), Arc::new(Timer::new(Duration::from_millis(10))), Duration::from_millis(50)));
+    kernel.root().add_child(hedged.clone()).await;
+
+    kernel.advance(30).await.unwrap();
+    hedged.deactivate();
+    kernel.advance(100).await.unwrap();
+    hedged.activate();
+    kernel.advance(10).await.unwrap();
+    assert_eq!(hedged.path(), FallbackPath::Primary);
+
+    kernel.advance(15).await.unwrap();
+    assert!(hedged.took_fallback());
+}
+
+// Builds a coroutine that fails on its first `failures` builds and
+// otherwise waits `ms` and succeeds, counting every build.
+fn flaky(name: &'static str, builds: Arc<AtomicU32>, failures: u32, ms: u64) -> impl Fn() -> Arc<dyn Generator> {
+    move || {
+        let build = builds.fetch_add(1, Ordering::SeqCst);
+        Arc::new(AsyncCoroutine::lazy(name, move |_ctx| async move {
+            if build < failures {
+                return Err("crashed".into());
+            }
+            sleep(Duration::from_millis(ms)).await;
+            Ok(())
+        }))
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_supervisor_restart_policies() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let counters: Vec<_> = (0..6).map(|_| Arc::new(AtomicU32::new(0))).collect();
+
+    let one = Arc::new(
+        Supervisor::new(RestartPolicy::OneForOne)
+            .intensity(5, Duration::from_secs(1))
+            .child(flaky("Flaky", counters[0].clone(), 2, 10))
+            .child(flaky("Steady", counters[1].clone(), 0, 50)),
+    );
+    let rest = Arc::new(
+        Supervisor::new(RestartPolicy::RestForOne)
+            .intensity(5, Duration::from_secs(1))
+            .child(flaky("Db", counters[2].clone(), 0, 50))
+            .child(flaky("Cache", counters[3].clone(), 1, 10))
+            .child(flaky("Api", counters[4].clone(), 0, 10)),
+    );
+    let doomed = Arc::new(
+        Supervisor::with_name("Doomed", RestartPolicy::OneForAll)
+            .child(flaky("Crasher", counters[5].clone(), u32::MAX, 10))
+            .child(|| Arc::new(Trigger::new(|| false)) as Arc<dyn Generator>),
+    );
+    kernel.root().add_child(one.clone()).await;
+    kernel.root().add_child(rest.clone()).await;
+    kernel.root().add_child(doomed.clone()).await;
+
+    kernel.advance(20).await.unwrap();
+    assert!(doomed.is_failed());
+    assert_eq!(doomed.restarts(), 2);
+    assert!(!doomed.children().await[1].is_active());
+
+    kernel.advance(80).await.unwrap();
+    assert!(one.is_completed() && !one.is_failed());
+    assert_eq!(one.restarts(), 2);
+    let builds: Vec<_> = counters[..5].iter().map(|builds| builds.load(Ordering::SeqCst)).collect();
+    assert_eq!(builds, [3, 1, 1, 2, 2]);
+    assert!(rest.is_completed() && !rest.is_failed());
+    assert_eq!(rest.policy(), RestartPolicy::RestForOne);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_one_for_all_restart_records_only_the_failure() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let crashes = Arc::new(AtomicU32::new(0));
+    let steady = Arc::new(AtomicU32::new(0));
+    let supervisor = Arc::new(
+        Supervisor::with_name("Sup", RestartPolicy::OneForAll)
+            .child(flaky("Crasher", crashes.clone(), 1, 10))
+            .child(flaky("Steady", steady.clone(), 0, 30)),
+    );
+    let handled = Arc::new(AtomicU32::new(0));
+    let counter = handled.clone();
+    kernel.set_error_handler(move |_, _| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    kernel.root().add_child(supervisor.clone()).await;
+
+    kernel.advance(60).await.unwrap();
+    assert!(supervisor.is_completed() && !supervisor.is_failed());
+    assert_eq!(supervisor.restarts(), 2);
+    assert_eq!(steady.load(Ordering::SeqCst), 2);
+    let errors: Vec<_> = kernel.take_errors().iter().map(|e| e.path.clone()).collect();
+    assert_eq!(errors, ["Root/Sup/Crasher"]);
+    assert_eq!(handled.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_with_resource_leases() {
+    let kernel = AsyncKernel::new();
+    let pool = LeasePool::new("db", 2);
+
+    let in_flight = Arc::new(AtomicU32::new(0));
+    let peak = Arc::new(AtomicU32::new(0));
+    let mut guarded = Vec::new();
+    for index in 0..6 {
+        let (in_flight, peak) = (in_flight.clone(), peak.clone());
+        let query = AsyncCoroutine::lazy(format!("Query{}", index), move |_ctx| async move {
+            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            sleep(Duration::from_millis(5)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            if index == 1 {
+                return Err("deadlock".into());
+            }
+            Ok(())
+        });
+        let node = Arc::new(WithResource::new(&pool, Arc::new(query)));
+        guarded.push(node.clone());
+        kernel.root().add_child(node).await;
+    }
+    kernel.run_until_complete().await.unwrap();
+
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+    assert_eq!(pool.available(), 2);
+    assert!(guarded[1].is_failed());
+    assert!(guarded.iter().all(|node| node.is_completed() && !node.holds_lease()));
+
+    // Cancelling hands the lease back even though the work never finished.
+    let stuck = Arc::new(WithResource::new(&pool, Arc::new(Trigger::new(|| false))));
+    kernel.root().add_child(stuck.clone()).await;
+    kernel.update(Duration::from_millis(1)).await.unwrap();
+    assert!(stuck.holds_lease());
+    assert_eq!(pool.available(), 1);
+    stuck.cancel();
+    assert_eq!(pool.available(), 2);
+}
+
+#[tokio::test]
+async fn test_exclusive_serialises_by_lock_name() {
+    let kernel = AsyncKernel::new();
+
+    let in_flight = Arc::new(AtomicU32::new(0));
+    let peak = Arc::new(AtomicU32::new(0));
+    let mut guarded = Vec::new();
+    for index in 0..4 {
+        let (in_flight, peak) = (in_flight.clone(), peak.clone());
+        let write = AsyncCoroutine::lazy(format!("Write{}", index), move |_ctx| async move {
+            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            sleep(Duration::from_millis(5)).await;
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        });
+        let node = Arc::new(Exclusive::new("serial-port", Arc::new(write)));
+        guarded.push(node.clone());
+        kernel.root().add_child(node).await;
+    }
+
+    // A different lock name doesn't wait on the serial port.
+    let other = Arc::new(Exclusive::new("log-file", Arc::new(Trigger::new(|| false))));
+    kernel.root().add_child(other.clone()).await;
+    kernel.update(Duration::from_millis(1)).await.unwrap();
+    assert!(other.holds_lock());
+    assert_eq!(guarded.iter().filter(|node| node.holds_lock()).count(), 1);
+    other.cancel();
+    other.complete();
+
+    kernel.run_until_complete().await.unwrap();
+    assert_eq!(peak.load(Ordering::SeqCst), 1);
+    assert!(guarded.iter().all(|node| node.is_completed() && !node.holds_lock()));
+    assert_eq!(guarded[0].lock_name(), "serial-port");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pause_gate_freezes_subtree() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let game = Arc::new(PauseGate::with_name("Game"));
+    let spawn_wave = Arc::new(Timer::new(Duration::from_millis(50)));
+    let ticks = Arc::new(AtomicU32::new(0));
+    let counter = {
+        let ticks = ticks.clone();
+        Arc::new(Trigger::new(move || {
+            ticks.fetch_add(1, Ordering::Relaxed);
+            false
+        }))
+    };
+    game.add_child(spawn_wave.clone()).await;
+    game.add_child(counter).await;
+    kernel.root().add_child(game.clone()).await;
+
+    kernel.advance(30).await.unwrap();
+    let menu = game.handle();
+    menu.pause();
+    let before = ticks.load(Ordering::Relaxed);
+    kernel.advance(100).await.unwrap();
+    assert!(game.is_paused());
+    assert_eq!(ticks.load(Ordering::Relaxed), before);
+    assert!(!spawn_wave.is_completed());
+    assert!(game.paused_for() >= Duration::from_millis(99));
+
+    // The timer resumes with the 20ms it had left.
+    menu.resume();
+    kernel.advance(15).await.unwrap();
+    assert!(!spawn_wave.is_completed());
+    kernel.advance(10).await.unwrap();
+    assert!(spawn_wave.is_completed());
+    assert!(ticks.load(Ordering::Relaxed) > before);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_watchdog_ignores_paused_and_inactive_time() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let game = Arc::new(PauseGate::with_name("Game"));
+    let frozen = Arc::new(Watchdog::new(Arc::new(Trigger::new(|| false)), Duration::from_millis(50)));
+    game.add_child(frozen.clone()).await;
+    let parked = Arc::new(Watchdog::new(Arc::new(Trigger::new(|| false)), Duration::from_millis(50)));
+    kernel.root().add_child(game.clone()).await;
+    kernel.root().add_child(parked.clone()).await;
+
+    kernel.advance(30).await.unwrap();
+    game.pause();
+    parked.deactivate();
+    kernel.advance(100).await.unwrap();
+    game.resume();
+    parked.activate();
+
+    // Neither counts the 100ms it spent paused or inactive.
+    kernel.advance(10).await.unwrap();
+    assert!(!frozen.is_failed() && !parked.is_failed());
+    kernel.advance(60).await.unwrap();
+    assert!(frozen.is_failed() && parked.is_failed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_active_window_steps_only_when_open() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let steps = Arc::new(AtomicU32::new(0));
+    let polling = {
+        let steps = steps.clone();
+        Arc::new(Trigger::new(move || steps.fetch_add(1, Ordering::Relaxed) + 1 >= 25))
+    };
+    // Open for the first 10ms of every 30ms, and from 100ms to 110ms.
+    let window = Arc::new(
+        ActiveWindow::with_name("Maintenance", polling.clone())
+            .every(Duration::from_millis(30), Duration::from_millis(10))
+            .between(Duration::from_millis(100), Duration::from_millis(110)),
+    );
+    kernel.root().add_child(window.clone()).await;
+
+    kernel.advance(10).await.unwrap();
+    assert!(window.is_open());
+    let first_slot = steps.load(Ordering::Relaxed);
+    assert!((9..=11).contains(&first_slot));
+    kernel.advance(15).await.unwrap();
+    assert!(!window.is_open());
+    assert_eq!(steps.load(Ordering::Relaxed), first_slot);
+
+    kernel.advance(35).await.unwrap();
+    assert!((18..=22).contains(&steps.load(Ordering::Relaxed)));
+    assert!(!window.is_completed());
+
+    kernel.advance(60).await.unwrap();
+    assert!(polling.is_completed());
+    assert!(window.is_completed() && !window.is_failed());
+}
+
+#[test]
+fn test_daily_utc_window_wraps_midnight() {
+    let hour = Duration::from_secs(3600);
+    let night_shift = ActiveWindow::new(Arc::new(Trigger::new(|| false))).daily_utc(22 * hour, 6 * hour);
+    assert_eq!(night_shift.windows(), &[TimeWindow::DailyUtc { start: 22 * hour, end: 6 * hour }]);
+    let all_day = ActiveWindow::new(Arc::new(Trigger::new(|| false))).daily_utc(Duration::ZERO, 24 * hour);
+    assert!(all_day.is_open());
+    let never = ActiveWindow::new(Arc::new(Trigger::new(|| false))).daily_utc(hour, hour);
+    assert!(!never.is_open());
+}
@@ -0,0 +1,93 @@
+use async_flow::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+#[derive(Clone, Default)]
+struct SpanRecorder {
+    spans: Arc<Mutex<Vec<(String, String, String)>>>,
+}
+
+struct FieldVisitor {
+    node: String,
+    kind: String,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "kind" {
+            self.kind = value.to_string();
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "node" {
+            self.node = format!("{:?}", value);
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for SpanRecorder {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor { node: String::new(), kind: String::new() };
+        attrs.record(&mut visitor);
+        self.spans.lock().unwrap().push((attrs.metadata().name().to_string(), visitor.node, visitor.kind));
+    }
+}
+
+#[tokio::test]
+async fn test_step_and_coroutine_spans() {
+    let recorder = SpanRecorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let kernel = AsyncKernel::new();
+    let sequence = Arc::new(Sequence::new()).named("Pipeline");
+    sequence.add_child(Arc::new(Timer::new(Duration::from_millis(5))).named("Delay")).await;
+    sequence.add_child(Arc::new(AsyncCoroutine::with_name("Upload", async { Ok(()) }))).await;
+    kernel.root().add_child(sequence).await;
+    kernel.run_until_complete().await.unwrap();
+
+    let spans = recorder.spans.lock().unwrap();
+    let has = |name: &str, node: &str, kind: &str| {
+        spans.iter().any(|(n, d, k)| n == name && d == node && k == kind)
+    };
+    assert!(has("flow.step", "Pipeline", "Sequence"));
+    assert!(has("flow.step", "Delay", "Timer"));
+    assert!(has("flow.step", "Upload", "AsyncCoroutine"));
+    assert!(has("coroutine", "Upload", ""));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_profile_report() {
+    let kernel = async_flow::testing::TestKernel::new();
+    assert!(kernel.profile_report().nodes.is_empty());
+    kernel.set_profiling(true);
+
+    let sequence = Arc::new(Sequence::with_name("Pipeline"));
+    sequence.add_child(Arc::new(Timer::with_name("Delay", Duration::from_millis(5)))).await;
+    kernel.root().add_child(sequence).await;
+    kernel.advance(3).await.unwrap();
+
+    let report = kernel.profile_report();
+    let root = report.node("Root").expect("root profiled");
+    assert_eq!(root.kind, "Node");
+    assert_eq!(root.steps.count, 3);
+    let delay = report.node("Root/Pipeline/Delay").expect("timer profiled");
+    assert_eq!(delay.kind, "Timer");
+    assert_eq!(delay.steps.count, 3);
+    assert_eq!(delay.steps.buckets.iter().sum::<u64>(), 3);
+    assert!(delay.steps.min <= delay.steps.max);
+    assert_eq!(report.nodes[0].path, "Root");
+    assert!(report.to_string().contains("Root/Pipeline (Sequence)"));
+
+    kernel.reset_profile();
+    assert!(kernel.profile_report().nodes.is_empty());
+    kernel.set_profiling(false);
+    kernel.advance(1).await.unwrap();
+    assert!(!kernel.is_profiling());
+    assert!(kernel.profile_report().nodes.is_empty());
+}
@@ -0,0 +1,993 @@
+use async_flow::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_find_by_name_and_id_in_nested_tree() {
+    let kernel = AsyncKernel::new();
+
+    let pipeline = Arc::new(Sequence::new()).named("Pipeline");
+    let stage1 = Arc::new(Timer::new(Duration::from_millis(10))).named("Stage1Processor");
+    let stages = Arc::new(Barrier::new()).named("ParallelStages");
+    let stage2 = Arc::new(Timer::new(Duration::from_millis(10))).named("Stage2Processor");
+
+    stages.add_child(stage2.clone()).await;
+    pipeline.add_child(stage1.clone()).await;
+    pipeline.add_child(stages.clone()).await;
+
+    let outer = Arc::new(Node::new()).named("Outer");
+    outer.add_child(pipeline.clone()).await;
+    kernel.root().add_child(outer.clone()).await;
+
+    let found = outer.find_by_name("Stage2Processor").await.unwrap();
+    assert_eq!(found.id(), stage2.id());
+
+    let found = kernel.find_by_id(stages.id()).await.unwrap();
+    assert_eq!(found.name().as_deref(), Some("ParallelStages"));
+
+    let found = kernel.find_by_name("Root").await.unwrap();
+    assert_eq!(found.id(), kernel.root().id());
+
+    assert!(outer.find_by_name("Missing").await.is_none());
+    assert!(stage1.find_by_name("Stage1Processor").await.is_none());
+}
+
+#[tokio::test]
+async fn test_node_positional_editing() {
+    let node = Arc::new(Node::new()).named("Editable");
+    let a = Arc::new(Node::new()).named("A");
+    let b = Arc::new(Node::new()).named("B");
+    let c = Arc::new(Node::new()).named("C");
+    let d = Arc::new(Node::new()).named("D");
+
+    node.add_child(a.clone()).await;
+    node.add_child(c.clone()).await;
+    node.insert_at(1, b.clone()).await;
+    assert!(node.insert_before(a.id(), d.clone()).await);
+
+    let names: Vec<_> = node.children().await.iter().map(|c| c.name().unwrap()).collect();
+    assert_eq!(names, vec!["D", "A", "B", "C"]);
+
+    let replacement = Arc::new(Node::new()).named("B2");
+    assert!(node.replace_child(b.id(), replacement).await);
+    assert!(!node.replace_child(b.id(), Arc::new(Node::new())).await);
+
+    let names: Vec<_> = node.children().await.iter().map(|c| c.name().unwrap()).collect();
+    assert_eq!(names, vec!["D", "A", "B2", "C"]);
+}
+
+#[tokio::test]
+async fn test_node_priority_and_frame_budget() {
+    let node = Arc::new(Node::with_name("Agents"));
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let agent = |name: &'static str, work: Duration| {
+        let log = log.clone();
+        Arc::new(SyncCoroutine::new(move || {
+            std::thread::sleep(work);
+            log.lock().unwrap().push(name);
+            Some(())
+        }))
+    };
+
+    node.add_child(agent("Ambient", Duration::ZERO)).await;
+    node.add_child_with_priority(agent("Combat", Duration::from_millis(5)), 10).await;
+    node.add_child_with_priority(agent("Pathing", Duration::ZERO), 10).await;
+    node.step().await.unwrap();
+    assert_eq!(*log.lock().unwrap(), vec!["Combat", "Pathing", "Ambient"]);
+
+    log.lock().unwrap().clear();
+    node.set_frame_budget(Some(Duration::from_millis(2)));
+    node.step().await.unwrap();
+    assert_eq!(*log.lock().unwrap(), vec!["Combat", "Pathing"]);
+}
+
+#[tokio::test]
+async fn test_node_round_robin_step_mode() {
+    let node = Arc::new(Node::with_name("Crowd"));
+    let counts: Vec<_> = (0..5).map(|_| Arc::new(std::sync::atomic::AtomicU32::new(0))).collect();
+    for count in &counts {
+        let count = count.clone();
+        node.add_child(Arc::new(SyncCoroutine::new(move || {
+            count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Some(())
+        }))).await;
+    }
+    node.set_step_mode(StepMode::RoundRobin(2));
+
+    let snapshot = || counts.iter().map(|c| c.load(std::sync::atomic::Ordering::Relaxed)).collect::<Vec<_>>();
+    node.step().await.unwrap();
+    assert_eq!(snapshot(), vec![1, 1, 0, 0, 0]);
+    node.step().await.unwrap();
+    assert_eq!(snapshot(), vec![1, 1, 1, 1, 0]);
+    node.step().await.unwrap();
+    assert_eq!(snapshot(), vec![2, 1, 1, 1, 1]);
+
+    node.set_step_mode(StepMode::All);
+    node.step().await.unwrap();
+    assert_eq!(snapshot(), vec![3, 2, 2, 2, 2]);
+}
+
+#[tokio::test]
+async fn test_sequence_hot_swap_failing_stage() {
+    let kernel = AsyncKernel::new();
+
+    let stage = |name: &str| Arc::new(Timer::new(Duration::from_millis(5))).named(name);
+
+    let broken = Arc::new(Timer::new(Duration::from_secs(10))).named("Broken");
+    let sequence = Arc::new(Sequence::new()).named("Pipeline");
+    sequence.add_child(stage("Load")).await;
+    sequence.add_child(broken.clone()).await;
+    sequence.add_child(stage("Final")).await;
+
+    kernel.root().add_child(sequence.clone()).await;
+    kernel.run_for(Duration::from_millis(20)).await.unwrap();
+    assert_eq!(sequence.current_index().await, 1);
+
+    assert!(sequence.replace_child(broken.id(), stage("Fixed")).await);
+    sequence.insert_at(0, stage("Preflight")).await;
+    assert_eq!(sequence.current_index().await, 2);
+
+    let names: Vec<_> = sequence.children().await.iter().map(|c| c.name().unwrap()).collect();
+    assert_eq!(names, vec!["Preflight", "Load", "Fixed", "Final"]);
+
+    kernel.run_until_complete().await.unwrap();
+
+    assert!(sequence.is_completed());
+    assert!(!broken.is_completed());
+    assert_eq!(sequence.current_index().await, 4);
+}
+
+#[tokio::test]
+async fn test_add_children_and_snapshot() {
+    let kernel = AsyncKernel::new();
+    let barrier = Arc::new(Barrier::new()).named("Bulk");
+
+    let timers: Vec<Arc<dyn Generator>> = (0..5)
+        .map(|i| Arc::new(Timer::new(Duration::from_millis(5))).named(format!("Timer{}", i)) as Arc<dyn Generator>)
+        .collect();
+    barrier.add_children(timers).await;
+
+    let sequence = Arc::new(Sequence::new()).named("Wrapper");
+    sequence.add_children([barrier.clone() as Arc<dyn Generator>, Arc::new(Node::new())]).await;
+
+    assert_eq!(barrier.child_count().await, 5);
+    let snapshot = barrier.children().await;
+    assert_eq!(snapshot[3].name().as_deref(), Some("Timer3"));
+    assert_eq!(sequence.children().await.len(), 2);
+
+    kernel.root().add_child(barrier.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert!(barrier.is_completed());
+    assert_eq!(snapshot.len(), 5);
+}
+
+struct PathCollector {
+    entered: Vec<(usize, String, GeneratorState)>,
+    left: Vec<String>,
+}
+
+impl FlowVisitor for PathCollector {
+    fn enter(&mut self, generator: &dyn Generator, info: &VisitInfo) -> VisitControl {
+        self.entered.push((info.depth, info.path.clone(), info.state));
+        if generator.name().as_deref() == Some("Skipped") {
+            VisitControl::SkipChildren
+        } else {
+            VisitControl::Continue
+        }
+    }
+
+    fn leave(&mut self, _generator: &dyn Generator, info: &VisitInfo) {
+        self.left.push(info.path.clone());
+    }
+}
+
+#[tokio::test]
+async fn test_visitor_depth_first_traversal() {
+    let main = Arc::new(Sequence::new()).named("Main");
+    let downloads = Arc::new(Barrier::new()).named("Downloads");
+    let skipped = Arc::new(Node::new()).named("Skipped");
+    let done = Arc::new(Timer::new(Duration::from_millis(1))).named("Done");
+    done.complete();
+
+    downloads.add_child(Arc::new(Timer::new(Duration::from_millis(1))).named("D1")).await;
+    skipped.add_child(Arc::new(Node::new()).named("Hidden")).await;
+    main.add_children([downloads.clone() as Arc<dyn Generator>, skipped, done]).await;
+
+    let mut collector = PathCollector { entered: Vec::new(), left: Vec::new() };
+    main.visit(&mut collector).await;
+
+    let paths: Vec<_> = collector.entered.iter().map(|(d, p, _)| (*d, p.as_str())).collect();
+    assert_eq!(paths, vec![
+        (0, "Main"),
+        (1, "Main/Downloads"),
+        (2, "Main/Downloads/D1"),
+        (1, "Main/Skipped"),
+        (1, "Main/Done"),
+    ]);
+    assert_eq!(collector.entered[4].2, GeneratorState::Completed);
+    assert_eq!(collector.entered[0].2, GeneratorState::Running);
+    assert_eq!(collector.left, vec![
+        "Main/Downloads/D1",
+        "Main/Downloads",
+        "Main/Skipped",
+        "Main/Done",
+        "Main",
+    ]);
+}
+
+#[tokio::test]
+async fn test_parent_links_and_detach() {
+    let kernel = AsyncKernel::new();
+    let outer = Arc::new(Barrier::new()).named("Outer");
+    let sequence = Arc::new(Sequence::new()).named("Steps");
+    let first = Arc::new(Timer::new(Duration::from_millis(1))).named("First");
+    let optional = Arc::new(Timer::new(Duration::from_secs(30))).named("Optional");
+    let last = Arc::new(Timer::new(Duration::from_millis(1))).named("Last");
+
+    sequence.add_children([first.clone() as Arc<dyn Generator>, optional.clone(), last.clone()]).await;
+    outer.add_child(sequence.clone()).await;
+    kernel.root().add_child(outer.clone()).await;
+
+    assert_eq!(optional.parent_id(), Some(sequence.id()));
+    assert_eq!(optional.ancestors(), vec![sequence.id(), outer.id(), kernel.root().id()]);
+    assert_eq!(kernel.root().parent_id(), None);
+
+    kernel.run_for(Duration::from_millis(20)).await.unwrap();
+    assert_eq!(sequence.current_index().await, 1);
+
+    let detacher = Arc::new(AsyncCoroutine::new({
+        let optional = optional.clone();
+        async move {
+            assert!(optional.detach().await);
+            Ok(())
+        }
+    })).named("Detacher");
+    kernel.root().add_child(detacher).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert!(sequence.is_completed());
+    assert!(last.is_completed());
+    assert!(!optional.is_completed());
+    assert_eq!(optional.parent_id(), None);
+    assert!(!optional.detach().await);
+}
+
+#[tokio::test]
+async fn test_render_tree_and_display() {
+    let kernel = AsyncKernel::new();
+    let main = Arc::new(Sequence::new()).named("Main");
+    let downloads = Arc::new(Barrier::new()).named("Downloads");
+    let done = Arc::new(Timer::new(Duration::from_millis(1))).named("Done");
+    done.complete();
+
+    downloads.add_child(AsyncFuture::<String>::named_pair("Manifest").1).await;
+    main.add_children([downloads as Arc<dyn Generator>, done]).await;
+    kernel.root().add_child(main).await;
+
+    let expected = "\
+AsyncKernel [AsyncKernel] running
+  Root [Node] running
+    Main [Sequence] running
+      Downloads [Barrier] running
+        Manifest [AsyncFuture] running
+      Done [Timer] completed
+";
+    assert_eq!(kernel.render_tree().await, expected);
+    assert_eq!(kernel.to_string(), expected);
+    assert!(kernel.root().to_string().starts_with("Root [Node] running\n  Main [Sequence]"));
+}
+
+#[tokio::test]
+async fn test_mermaid_export() {
+    let main = Arc::new(Sequence::new()).named("Main");
+    let downloads = Arc::new(Barrier::new()).named("Downloads");
+    let d1 = Arc::new(Timer::new(Duration::from_millis(1))).named("Download1");
+    let d2 = Arc::new(Timer::new(Duration::from_millis(1))).named("Download2");
+    let compress = Arc::new(Timer::new(Duration::from_millis(1))).named("Compress");
+    d1.complete();
+
+    downloads.add_children([d1.clone() as Arc<dyn Generator>, d2.clone()]).await;
+    main.add_children([downloads.clone() as Arc<dyn Generator>, compress.clone()]).await;
+
+    let chart = main.to_mermaid().await;
+    let id = |g: &dyn Generator| format!("n{}", g.id().simple());
+
+    assert!(chart.starts_with("flowchart TD\n"));
+    assert!(chart.contains(&format!("subgraph {}[\"Main [Sequence] running 0/2\"]", id(main.as_ref()))));
+    assert!(chart.contains(&format!("subgraph {}[\"Downloads [Barrier] running 1/2\"]", id(downloads.as_ref()))));
+    assert!(chart.contains(&format!("{}[\"Download1 [Timer] completed\"]", id(d1.as_ref()))));
+    assert!(chart.contains(&format!("{} --> {}", id(downloads.as_ref()), id(compress.as_ref()))));
+    assert!(chart.contains(&format!("class {} completed", id(d1.as_ref()))));
+    assert_eq!(chart.matches("subgraph").count(), chart.matches("\n    end\n").count() + chart.matches("\n        end\n").count());
+}
+
+#[tokio::test]
+async fn test_serializable_snapshot() {
+    let kernel = AsyncKernel::new();
+    let pipeline = Arc::new(Sequence::new()).named("Pipeline");
+    let warmup = Arc::new(Timer::new(Duration::from_millis(1))).named("Warmup");
+    let wait = Arc::new(Timer::new(Duration::from_secs(60))).named("LongWait");
+
+    pipeline.add_children([warmup as Arc<dyn Generator>, wait]).await;
+    kernel.root().add_child(pipeline.clone()).await;
+    kernel.run_for(Duration::from_millis(30)).await.unwrap();
+
+    let snapshot = kernel.snapshot().await;
+    assert_eq!(snapshot.kind, "AsyncKernel");
+    let root = &snapshot.children[0];
+    let pipeline_snapshot = &root.children[0];
+    assert_eq!(pipeline_snapshot.name.as_deref(), Some("Pipeline"));
+    assert_eq!(pipeline_snapshot.sequence_index, Some(1));
+
+    let long_wait = &pipeline_snapshot.children[1];
+    assert_eq!(long_wait.state, GeneratorState::Running);
+    let remaining = long_wait.timer_remaining.unwrap();
+    assert!(remaining > Duration::from_secs(59) && remaining <= Duration::from_secs(60));
+
+    let json = serde_json::to_value(&snapshot).unwrap();
+    let pipeline_json = &json["children"][0]["children"][0];
+    assert_eq!(pipeline_json["type"], "Sequence");
+    assert_eq!(pipeline_json["state"], "running");
+    assert_eq!(pipeline_json["sequence_index"], 1);
+    assert_eq!(pipeline_json["children"][0]["state"], "completed");
+
+    let round_trip: NodeSnapshot = serde_json::from_value(json).unwrap();
+    assert_eq!(round_trip, snapshot);
+}
+
+#[tokio::test]
+async fn test_flow_macro_builds_nested_tree() {
+    let kernel = AsyncKernel::new();
+    let download1 = Arc::new(Timer::new(Duration::from_millis(5))).named("Download1");
+    let download2 = Arc::new(Timer::new(Duration::from_millis(5))).named("Download2");
+    let compress = Arc::new(Timer::new(Duration::from_millis(5))).named("Compress");
+
+    let main = flow! {
+        sequence "Main" {
+            barrier "Downloads" { task download1.clone(), task download2 },
+            node { },
+            timer Duration::from_millis(1),
+            task compress.clone()
+        }
+    };
+
+    assert_eq!(main.name().as_deref(), Some("Main"));
+    assert_eq!(main.child_count().await, 4);
+    let downloads = main.find_by_name("Downloads").await.unwrap();
+    assert_eq!(downloads.type_name(), "Barrier");
+    assert_eq!(downloads.children().await.len(), 2);
+
+    main.remove_child(main.children().await[1].id()).await;
+    kernel.root().add_child(main.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert!(main.is_completed());
+    assert!(download1.is_completed());
+    assert!(compress.is_completed());
+}
+
+#[tokio::test]
+async fn test_fluent_builders() {
+    let kernel = AsyncKernel::new();
+    let ready = Arc::new(std::sync::atomic::AtomicBool::new(true));
+    let compress = Arc::new(Timer::new(Duration::from_millis(5))).named("Compress");
+
+    let main = SequenceBuilder::with_name("Main")
+        .parallel(|b| b.timer(Duration::from_millis(5)).timer(Duration::from_millis(10)))
+        .trigger({
+            let ready = ready.clone();
+            move || ready.load(std::sync::atomic::Ordering::Relaxed)
+        })
+        .then(compress.clone())
+        .build();
+
+    assert_eq!(main.name().as_deref(), Some("Main"));
+    assert_eq!(main.child_count().await, 3);
+    let children = main.children().await;
+    assert_eq!(children[0].type_name(), "Barrier");
+    assert_eq!(children[0].children().await.len(), 2);
+    assert_eq!(compress.parent_id(), Some(main.id()));
+
+    let barrier = Barrier::builder()
+        .sequence(|s| s.timer(Duration::from_millis(1)))
+        .build();
+    assert_eq!(barrier.child_count().await, 1);
+
+    kernel.root().add_child(main.clone()).await;
+    kernel.run_until_complete().await.unwrap();
+    assert!(main.is_completed());
+    assert!(compress.is_completed());
+}
+
+#[flow_task]
+async fn upload(ctx: FlowCtx, bytes: usize, log: Arc<std::sync::Mutex<Vec<String>>>) -> Result<()> {
+    log.lock().unwrap().push(format!("{} {}", ctx.name().unwrap(), bytes));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_flow_task_spawns_lazily() {
+    let kernel = AsyncKernel::new();
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let task = upload(1024, log.clone());
+    assert_eq!(task.name().as_deref(), Some("upload"));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(log.lock().unwrap().is_empty());
+
+    let sequence = SequenceBuilder::new()
+        .timer(Duration::from_millis(5))
+        .then(task.clone())
+        .build();
+    kernel.root().add_child(sequence).await;
+    kernel.run_until_complete().await.unwrap();
+
+    assert!(task.is_completed());
+    assert_eq!(*log.lock().unwrap(), vec!["upload 1024".to_string()]);
+}
+
+#[tokio::test]
+async fn test_named_after_clone_and_rename() {
+    let timer = Arc::new(Timer::new(Duration::from_millis(5)));
+    let shared = timer.clone();
+    let timer = timer.named("Shared");
+    assert_eq!(shared.name().as_deref(), Some("Shared"));
+
+    shared.set_name("Renamed".to_string());
+    assert_eq!(timer.name().as_deref(), Some("Renamed"));
+
+    let sequence = Arc::new(Sequence::new());
+    sequence.add_child(timer.clone()).await;
+    assert!(sequence.find_by_name("Renamed").await.is_some());
+}
+
+#[tokio::test]
+async fn test_cycles_rejected_and_validate() {
+    let outer = Arc::new(Node::with_name("Outer"));
+    let middle = Arc::new(Sequence::with_name("Middle"));
+    let inner = Arc::new(Barrier::with_name("Inner"));
+    outer.add_child(middle.clone()).await;
+    middle.add_child(inner.clone()).await;
+
+    let err = inner.try_add_child(outer.clone()).await.unwrap_err();
+    assert_eq!(err.to_string(), "adding Outer under Inner would create a cycle");
+    assert!(middle.try_add_child(middle.clone()).await.is_err());
+    inner.add_child(middle.clone()).await;
+    assert_eq!(inner.child_count().await, 0);
+    outer.insert_at(0, outer.clone()).await;
+    assert_eq!(outer.child_count().await, 1);
+    outer.validate().await.unwrap();
+
+    let shared = Arc::new(Timer::with_name("Shared", Duration::from_millis(1)));
+    inner.add_child(shared.clone()).await;
+    outer.add_child(shared.clone()).await;
+    let err = outer.validate().await.unwrap_err();
+    assert_eq!(err.to_string(), "Shared appears more than once in the tree");
+
+    let kernel = AsyncKernel::new();
+    kernel.root().add_child(Arc::new(Timer::new(Duration::from_millis(1)))).await;
+    kernel.validate().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_nested_pruning() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let outer = Arc::new(Node::with_name("Outer"));
+    let inner = Arc::new(Node::with_name("Inner"));
+    let pruning = Arc::new(Node::with_name("Pruning"));
+    pruning.set_prune_completed(true);
+    outer.add_child(inner.clone()).await;
+    outer.add_child(pruning.clone()).await;
+    kernel.root().add_child(outer.clone()).await;
+
+    for _ in 0..3 {
+        inner.add_child(Arc::new(Timer::new(Duration::from_millis(1)))).await;
+        pruning.add_child(Arc::new(Timer::new(Duration::from_millis(1)))).await;
+    }
+    inner.add_child(Arc::new(Timer::new(Duration::from_secs(60)))).await;
+    kernel.advance(5).await.unwrap();
+
+    assert_eq!(pruning.child_count().await, 0);
+    assert_eq!(inner.child_count().await, 4);
+    assert_eq!(kernel.prune().await, 3);
+    assert_eq!(inner.child_count().await, 1);
+    assert_eq!(kernel.prune().await, 0);
+}
+
+#[tokio::test]
+async fn test_sequence_advances_within_one_step() {
+    let sequence = Arc::new(Sequence::new());
+    for _ in 0..6 {
+        sequence.add_child(Arc::new(Trigger::new(|| true))).await;
+    }
+    sequence.step().await.unwrap();
+    assert!(sequence.is_completed());
+
+    let limited = Arc::new(Sequence::new());
+    limited.set_advance_limit(2);
+    for _ in 0..5 {
+        limited.add_child(Arc::new(Trigger::new(|| true))).await;
+    }
+    limited.step().await.unwrap();
+    assert_eq!(limited.current_index().await, 2);
+    limited.step().await.unwrap();
+    limited.step().await.unwrap();
+    assert!(limited.is_completed());
+
+    let waiting = Arc::new(Sequence::new());
+    waiting.add_child(Arc::new(Trigger::new(|| true))).await;
+    waiting.add_child(Arc::new(Timer::new(Duration::from_secs(60)))).await;
+    waiting.step().await.unwrap();
+    assert_eq!(waiting.current_index().await, 1);
+    assert!(!waiting.is_completed());
+}
+
+#[tokio::test]
+async fn test_sequence_skip_and_jump() {
+    let sequence = Arc::new(Sequence::new());
+    for name in ["Load", "Optional", "Render", "Publish"] {
+        sequence
+            .add_child(Arc::new(Timer::with_name(name, Duration::from_secs(60))))
+            .await;
+    }
+
+    assert!(sequence.skip_current().await);
+    assert_eq!(sequence.current_index().await, 1);
+    assert!(sequence.jump_to_name("Publish").await);
+    assert_eq!(sequence.current_index().await, 3);
+    assert!(!sequence.jump_to_name("Missing").await);
+    assert!(sequence.jump_to(0).await);
+    assert!(!sequence.jump_to(5).await);
+
+    assert!(sequence.jump_to(4).await);
+    assert!(!sequence.skip_current().await);
+    sequence.step().await.unwrap();
+    assert!(sequence.is_completed());
+}
+
+#[tokio::test]
+async fn test_sequence_progress() {
+    let sequence = Arc::new(Sequence::new());
+    assert_eq!(sequence.progress().await, 0.0);
+    for name in ["First", "Second", "Third", "Fourth"] {
+        sequence
+            .add_child(Arc::new(Timer::with_name(name, Duration::from_secs(60))))
+            .await;
+    }
+
+    assert_eq!(sequence.remaining_count().await, 4);
+    assert_eq!(sequence.current_child().await.unwrap().name().as_deref(), Some("First"));
+    sequence.current_child().await.unwrap().complete();
+    sequence.step().await.unwrap();
+    assert_eq!(sequence.progress().await, 0.25);
+    assert_eq!(sequence.current_child().await.unwrap().name().as_deref(), Some("Second"));
+    assert_eq!(sequence.remaining_count().await, 3);
+
+    sequence.jump_to(4).await;
+    assert_eq!(sequence.progress().await, 1.0);
+    assert!(sequence.current_child().await.is_none());
+    assert_eq!(sequence.remaining_count().await, 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_sequence_reset_reruns() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let sequence = Arc::new(Sequence::new());
+    let (promise, future) = AsyncFuture::<u32>::pair();
+    sequence.add_child(Arc::new(Timer::new(Duration::from_millis(10)))).await;
+    sequence.add_child(future.clone()).await;
+    kernel.root().add_child(sequence.clone()).await;
+
+    promise.set_value(1).await;
+    kernel.advance(12).await.unwrap();
+    assert!(sequence.is_completed());
+
+    assert!(sequence.reset().await);
+    assert_eq!(sequence.current_index().await, 0);
+    assert!(!future.is_ready());
+    kernel.root().add_child(sequence.clone()).await;
+    kernel.advance(5).await.unwrap();
+    assert!(!sequence.is_completed());
+    promise.set_value(2).await;
+    kernel.advance(7).await.unwrap();
+    assert!(sequence.is_completed());
+    assert_eq!(future.set_count(), 2);
+
+    let coroutine = AsyncCoroutine::new(async { Ok(()) });
+    assert!(!coroutine.reset().await);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_once_skips_child_on_later_passes() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let setups = Arc::new(std::sync::atomic::AtomicU32::new(0));
+    let init = {
+        let setups = setups.clone();
+        Arc::new(Once::with_name("Init", Arc::new(SyncCoroutine::new(move || {
+            setups.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            None::<()>
+        }))))
+    };
+    let round = Arc::new(Sequence::new());
+    round.add_child(init.clone()).await;
+    round.add_child(Arc::new(Timer::new(Duration::from_millis(3)))).await;
+
+    for pass in 0..3 {
+        kernel.root().add_child(round.clone()).await;
+        kernel.advance(5).await.unwrap();
+        assert!(round.is_completed());
+        assert_eq!(init.was_skipped(), pass > 0);
+        assert!(round.reset().await);
+    }
+    assert_eq!(setups.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert!(init.has_run());
+
+    let migrations = Arc::new(std::sync::atomic::AtomicU32::new(0));
+    let build = || {
+        let migrations = migrations.clone();
+        Arc::new(Once::keyed("migrate", Arc::new(SyncCoroutine::new(move || {
+            migrations.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            None::<()>
+        }))))
+    };
+    let first = build();
+    kernel.root().add_child(first.clone()).await;
+    kernel.advance(1).await.unwrap();
+    let rebuilt = build();
+    kernel.root().add_child(rebuilt.clone()).await;
+    kernel.advance(1).await.unwrap();
+    assert!(first.is_completed() && rebuilt.is_completed());
+    assert!(!first.was_skipped() && rebuilt.was_skipped());
+    assert_eq!(migrations.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    let elsewhere = async_flow::testing::TestKernel::new();
+    let fresh = build();
+    elsewhere.root().add_child(fresh.clone()).await;
+    elsewhere.advance(1).await.unwrap();
+    assert!(!fresh.was_skipped());
+    assert_eq!(migrations.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_cached_refreshes_after_ttl() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let fetches = Arc::new(std::sync::atomic::AtomicU32::new(0));
+    let config = {
+        let fetches = fetches.clone();
+        Arc::new(Cached::with_name("Config", Duration::from_millis(50), move |promise: Promise<u32>| {
+            let version = fetches.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            Arc::new(AsyncCoroutine::lazy("FetchConfig", move |_ctx| async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                promise.set_value(version).await;
+                Ok(())
+            })) as Arc<dyn Generator>
+        }))
+    };
+
+    kernel.root().add_child(config.clone()).await;
+    kernel.advance(2).await.unwrap();
+    assert_eq!(config.children().await.len(), 1);
+    kernel.advance(6).await.unwrap();
+    assert!(config.is_completed());
+    assert_eq!(config.value().try_get(), Some(1));
+
+    for _ in 0..2 {
+        assert!(config.reset().await);
+        kernel.root().add_child(config.clone()).await;
+        kernel.advance(1).await.unwrap();
+        assert!(config.is_completed());
+    }
+    assert_eq!((config.hits(), config.refreshes()), (2, 1));
+
+    kernel.advance(50).await.unwrap();
+    assert!(!config.is_fresh());
+    assert!(config.reset().await);
+    kernel.root().add_child(config.clone()).await;
+    kernel.advance(8).await.unwrap();
+    assert!(config.is_completed());
+    assert_eq!(config.value().try_get(), Some(2));
+    assert_eq!(fetches.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+    let broken = Arc::new(Cached::new(Duration::from_secs(1), |_promise: Promise<u32>| {
+        Arc::new(Timer::new(Duration::from_millis(1))) as Arc<dyn Generator>
+    }));
+    kernel.root().add_child(broken.clone()).await;
+    kernel.advance(3).await.unwrap();
+    assert!(broken.is_failed());
+    assert!(!broken.value().is_ready());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_first_of_and_all_of_combinators() {
+    let kernel = async_flow::testing::TestKernel::new();
+
+    let slow: Arc<dyn Generator> = Arc::new(Timer::with_name("Slow", Duration::from_millis(50)));
+    let fast: Arc<dyn Generator> = Arc::new(Timer::with_name("Fast", Duration::from_millis(10)));
+    let broken: Arc<dyn Generator> = Arc::new(AsyncCoroutine::lazy("Broken", |_ctx| async { Err("refused".into()) }));
+    let race = combine::first_of([broken.clone(), slow.clone(), fast.clone()]);
+    let both = combine::all_of([
+        Arc::new(Timer::new(Duration::from_millis(5))) as Arc<dyn Generator>,
+        Arc::new(Timer::new(Duration::from_millis(20))),
+    ]);
+    let hopeless = combine::first_of([
+        Arc::new(AsyncCoroutine::lazy("AlsoBroken", |_ctx| async { Err("refused".into()) })) as Arc<dyn Generator>,
+    ]);
+    kernel.root().add_child(race.clone()).await;
+    kernel.root().add_child(both.clone()).await;
+    kernel.root().add_child(hopeless.clone()).await;
+
+    kernel.advance(12).await.unwrap();
+    assert!(race.is_completed() && !race.is_failed());
+    assert_eq!(race.winner_index(), Some(2));
+    assert_eq!(race.winner().unwrap().id(), fast.id());
+    assert!(broken.is_failed() && !slow.is_active() && !slow.is_completed());
+    assert!(hopeless.is_failed() && hopeless.winner().is_none());
+    assert!(!both.is_completed());
+
+    kernel.advance(10).await.unwrap();
+    assert!(both.is_completed());
+    assert_eq!(both.child_count().await, 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_barrier_prunes_completed_children() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let barrier = Arc::new(Barrier::new());
+    barrier.set_prune_completed(true);
+    let short = Arc::new(Timer::new(Duration::from_millis(2)));
+    barrier.add_child(short.clone()).await;
+    barrier.add_child(Arc::new(Timer::new(Duration::from_millis(20)))).await;
+    kernel.root().add_child(barrier.clone()).await;
+
+    kernel.advance(5).await.unwrap();
+    assert_eq!(barrier.child_count().await, 1);
+    assert_eq!(barrier.completed_count().await, 1);
+    assert_eq!(Arc::strong_count(&short), 1);
+    assert!(!barrier.is_completed());
+
+    kernel.advance(20).await.unwrap();
+    assert!(barrier.is_completed());
+    assert_eq!(barrier.completed_count().await, 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_barrier_progress_counters() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let barrier = Arc::new(Barrier::new());
+    let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+    barrier.on_child_complete({
+        let reports = reports.clone();
+        move |child, done, total| {
+            reports.lock().unwrap().push(format!("{} {}/{}", child.name().unwrap(), done, total));
+        }
+    });
+    for (name, millis) in [("a", 2), ("b", 4), ("c", 30)] {
+        barrier
+            .add_child(Arc::new(Timer::with_name(name, Duration::from_millis(millis))))
+            .await;
+    }
+    kernel.root().add_child(barrier.clone()).await;
+
+    kernel.advance(10).await.unwrap();
+    assert_eq!(barrier.completed_count().await, 2);
+    assert_eq!(barrier.pending_count().await, 1);
+    assert_eq!(*reports.lock().unwrap(), vec!["a 1/3", "b 2/3"]);
+
+    kernel.advance(25).await.unwrap();
+    assert!(barrier.is_completed());
+    assert_eq!(reports.lock().unwrap().last().unwrap(), "c 3/3");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_barrier_child_timeout() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let barrier = Arc::new(Barrier::new());
+    let slow = Arc::new(Timer::with_name("Slow", Duration::from_secs(60)));
+    let quick = Arc::new(Timer::with_name("Quick", Duration::from_millis(5)));
+    barrier.add_child_with_timeout(slow.clone(), Duration::from_millis(20)).await;
+    barrier.add_child_with_timeout(quick.clone(), Duration::from_millis(20)).await;
+    kernel.root().add_child(barrier.clone()).await;
+
+    kernel.advance(15).await.unwrap();
+    assert!(!barrier.is_completed());
+    kernel.advance(10).await.unwrap();
+    assert!(barrier.is_completed());
+    assert_eq!(slow.state(), GeneratorState::Failed);
+    assert_eq!(quick.state(), GeneratorState::Completed);
+    assert_eq!(barrier.timed_out_count(), 1);
+
+    assert!(barrier.reset().await);
+    assert_eq!(barrier.timed_out_count(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_barrier_deadline_pauses_while_deactivated() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let barrier = Arc::new(Barrier::new());
+    let slow = Arc::new(Timer::with_name("Slow", Duration::from_secs(60)));
+    barrier.add_child_with_timeout(slow.clone(), Duration::from_millis(20)).await;
+    kernel.root().add_child(barrier.clone()).await;
+
+    kernel.advance(10).await.unwrap();
+    barrier.deactivate();
+    kernel.advance(50).await.unwrap();
+    barrier.activate();
+    kernel.advance(5).await.unwrap();
+    assert!(!slow.is_failed());
+    kernel.advance(10).await.unwrap();
+    assert!(slow.is_failed());
+    assert_eq!(barrier.timed_out_count(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_deactivation_suspends_subtree() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let group = Arc::new(Node::new());
+    let timer = Arc::new(Timer::new(Duration::from_millis(20)));
+    let periodic = Arc::new(PeriodicTimer::new(Duration::from_millis(15)));
+    let fired = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    periodic
+        .set_elapsed_callback({
+            let fired = fired.clone();
+            move || {
+                fired.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        })
+        .await;
+    let inner = Arc::new(Sequence::new());
+    inner.add_child(timer.clone()).await;
+    group.add_child(inner.clone()).await;
+    group.add_child(periodic.clone()).await;
+    kernel.root().add_child(group.clone()).await;
+
+    kernel.advance(10).await.unwrap();
+    let fired_before = fired.load(std::sync::atomic::Ordering::Relaxed);
+    group.deactivate();
+    assert!(!inner.is_active() && !timer.is_active() && !periodic.is_active());
+
+    kernel.advance(100).await.unwrap();
+    group.activate();
+    assert!(timer.is_active());
+    kernel.advance(1).await.unwrap();
+    assert!(!timer.is_completed());
+    assert_eq!(fired.load(std::sync::atomic::Ordering::Relaxed), fired_before);
+    assert!(timer.remaining().await > Duration::from_millis(5));
+
+    kernel.advance(12).await.unwrap();
+    assert!(timer.is_completed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_reset_restarts_every_kind() {
+    let kernel = async_flow::testing::TestKernel::new();
+    let armed = Arc::new(std::sync::atomic::AtomicBool::new(true));
+    let trigger = Arc::new(Trigger::new({
+        let armed = armed.clone();
+        move || armed.load(std::sync::atomic::Ordering::Relaxed)
+    }));
+    let barrier = Arc::new(Barrier::new());
+    barrier.add_child(Arc::new(Timer::new(Duration::from_millis(5)))).await;
+    barrier.add_child(trigger.clone()).await;
+    let node = Arc::new(Node::new());
+    node.add_child(Arc::new(PeriodicTimer::new(Duration::from_millis(3)))).await;
+    let watchdog = Arc::new(Watchdog::new(barrier.clone(), Duration::from_secs(1)));
+    let body = Arc::new(Sequence::new());
+    body.add_child(watchdog.clone()).await;
+    body.add_child(node.clone()).await;
+
+    kernel.root().add_child(body.clone()).await;
+    kernel.advance(8).await.unwrap();
+    assert!(barrier.is_completed());
+    node.complete();
+    kernel.advance(1).await.unwrap();
+    assert!(body.is_completed());
+
+    armed.store(false, std::sync::atomic::Ordering::Relaxed);
+    assert!(body.reset().await);
+    assert!(!trigger.is_completed() && !barrier.is_completed() && !watchdog.is_completed());
+    kernel.root().add_child(body.clone()).await;
+    kernel.advance(8).await.unwrap();
+    assert!(!barrier.is_completed());
+    armed.store(true, std::sync::atomic::Ordering::Relaxed);
+    kernel.advance(1).await.unwrap();
+    assert!(barrier.is_completed());
+}
+
+#[tokio::test]
+async fn test_child_handles() {
+    let node = Node::new();
+    let mut handles = Vec::new();
+    for i in 0..100 {
+        let timer = Arc::new(Timer::with_name(format!("t{}", i), Duration::from_secs(1)));
+        handles.push(node.add_child(timer).await.unwrap());
+    }
+
+    // Enough removals to compact the slab; the remaining handles still hold.
+    for handle in handles.iter().step_by(2) {
+        assert!(node.remove_handle(*handle).await);
+        assert!(!node.remove_handle(*handle).await);
+    }
+    assert_eq!(node.child_count().await, 50);
+    assert_eq!(node.child(handles[1]).await.unwrap().name().as_deref(), Some("t1"));
+    assert!(node.child(handles[0]).await.is_none());
+
+    let names: Vec<_> = node.children().await.iter().take(3).filter_map(|c| c.name()).collect();
+    assert_eq!(names, vec!["t1", "t3", "t5"]);
+
+    // A reused slot does not revive a stale handle.
+    let fresh = node.add_child(Arc::new(Timer::new(Duration::from_secs(1)))).await.unwrap();
+    assert!(node.child(handles[0]).await.is_none());
+    assert!(node.child(fresh).await.is_some());
+
+    let inserted = node.insert_at(0, Arc::new(Timer::with_name("first", Duration::from_secs(1)))).await.unwrap();
+    assert_eq!(node.children().await[0].name().as_deref(), Some("first"));
+    assert_eq!(node.child(handles[99]).await.unwrap().name().as_deref(), Some("t99"));
+
+    let detached = node.child(inserted).await.unwrap();
+    assert!(detached.base().detach().await);
+    assert!(node.child(inserted).await.is_none());
+    assert_eq!(node.child_count().await, 51);
+}
+
+#[test]
+fn test_sequential_ids() {
+    set_id_mode(IdMode::Sequential);
+    let first = Timer::new(Duration::from_secs(1));
+    let second = Timer::new(Duration::from_secs(1));
+    set_id_mode(IdMode::Random);
+    let random = Timer::new(Duration::from_secs(1));
+
+    let (high, low) = first.id().as_u64_pair();
+    assert_eq!(high, 0);
+    assert!(second.id().as_u64_pair().1 > low);
+    assert_eq!(first.base().label(), format!("#{}", low));
+    assert_ne!(random.id().as_u64_pair().0, 0);
+    assert_eq!(random.base().label().len(), 8);
+}
+
+#[tokio::test]
+async fn test_downcast_found_generators() {
+    let kernel = AsyncKernel::new();
+    let pipeline = Arc::new(Sequence::with_name("Pipeline"));
+    pipeline.add_child(Arc::new(Timer::with_name("Timeout", Duration::from_secs(5)))).await;
+    kernel.root().add_child(pipeline.clone()).await;
+
+    let found = kernel.root().find_by_name("Timeout").await.unwrap();
+    assert!(found.is::<Timer>());
+    assert!(!found.is::<Sequence>());
+    assert!(!found.downcast_ref::<Timer>().unwrap().is_elapsed().await);
+    assert!(found.as_any().downcast_ref::<Sequence>().is_none());
+
+    let found = kernel.root().find_by_name("Pipeline").await.unwrap();
+    let sequence = downcast_arc::<Sequence>(found).unwrap();
+    assert!(Arc::ptr_eq(&sequence, &pipeline));
+    let found = kernel.root().find_by_name("Pipeline").await.unwrap();
+    assert!(downcast_arc::<Timer>(found).is_none());
+}
+
+#[tokio::test]
+async fn test_find_typed() {
+    let kernel = AsyncKernel::new();
+    let pipeline = Arc::new(Sequence::with_name("Pipeline"));
+    pipeline.add_child(Arc::new(Timer::with_name("Timeout", Duration::from_secs(5)))).await;
+    pipeline.add_child(Arc::new(Trigger::with_name("Timeout", || false))).await;
+    pipeline.add_child(Arc::new(Timer::with_name("Retry", Duration::from_secs(1)))).await;
+    kernel.root().add_child(pipeline).await;
+
+    let timer = kernel.root().find_typed::<Timer>("Timeout").await.unwrap();
+    assert!(!timer.is_elapsed().await);
+    assert!(kernel.root().find_typed::<Trigger>("Timeout").await.is_some());
+    assert!(kernel.root().find_typed::<Sequence>("Timeout").await.is_none());
+
+    let sequence = kernel.find_typed::<Sequence>("Pipeline").await.unwrap();
+    assert_eq!(sequence.current_index().await, 0);
+
+    let timers = kernel.root().find_all_typed::<Timer>().await;
+    let names: Vec<_> = timers.iter().filter_map(|timer| timer.name()).collect();
+    assert_eq!(names, vec!["Timeout", "Retry"]);
+
+    let root: Arc<dyn Generator> = kernel.root();
+    assert_eq!(find_all_typed::<Timer>(root.as_ref()).await.len(), 2);
+    assert!(find_typed::<Timer>(root.as_ref(), "Retry").await.is_some());
+}